@@ -2,8 +2,8 @@ use crate::*;
 
 /// A soft limit on the amount of references that may be made to an `Arc`.
 ///
-/// Going above this limit will abort your program (although not
-/// necessarily) at _exactly_ `MAX_REFCOUNT + 1` references.
+/// Going above this limit will trigger the overflow policy (see [`OverflowAction`]), although not
+/// necessarily at _exactly_ `MAX_REFCOUNT + 1` references.
 const MAX_REFCOUNT: usize = (isize::MAX) as usize;
 
 /// The object allocated by an Arc<T>
@@ -44,6 +44,40 @@ impl<T: ?Sized> ArcInner<T> {
     }
 }
 
+impl<T> ArcInner<[T]> {
+    /// Get the layout of an `ArcInner` containing a slice of length `len`, as well as the offset of the slice
+    #[inline]
+    pub fn slice_layout(len: usize) -> (Layout, usize) {
+        let atomic_layout = Layout::new::<atomic::AtomicUsize>();
+        let array_layout = Layout::array::<T>(len).unwrap_or_else(|_| abort());
+        let (layout, offset) = atomic_layout
+            .extend(array_layout)
+            .unwrap_or_else(|_| abort());
+        let layout = layout.pad_to_align();
+        (layout, offset)
+    }
+}
+
+/// Record a new allocation with the given layout and a reference count of one, given a pointer to its data
+#[inline]
+#[allow(unused_variables)]
+pub(crate) fn track_alloc<T: ?Sized>(ptr: *const T, layout: Layout) {
+    #[cfg(feature = "debug-tracking")]
+    debug::register(ptr, unsafe { ArcInner::refcount_ptr(ptr) });
+    #[cfg(feature = "stats")]
+    stats::record_alloc::<T>(layout.size());
+}
+
+/// Record that an allocation with the given layout is about to be freed, given a pointer to its data
+#[inline]
+#[allow(unused_variables)]
+pub(crate) fn track_dealloc<T: ?Sized>(ptr: *const T, layout: Layout) {
+    #[cfg(feature = "debug-tracking")]
+    debug::unregister(ptr);
+    #[cfg(feature = "stats")]
+    stats::record_dealloc::<T>(layout.size());
+}
+
 unsafe impl<T: ?Sized + Sync + Send> Send for ArcInner<T> {}
 unsafe impl<T: ?Sized + Sync + Send> Sync for ArcInner<T> {}
 
@@ -94,17 +128,38 @@ impl<T> Arc<T> {
             ptr::write(allocation, inner);
             &*allocation
         };
+        track_alloc(&alloc_ref.data, layout);
         Arc {
             ptr: (&alloc_ref.data).into(),
             phantom: PhantomData,
         }
     }
+    /// Convert an optional `Arc<T>` to a raw pointer, which is null for `None`, suitable for use across FFI as a
+    /// nullable C `T*`
+    #[inline]
+    pub fn option_into_raw(this: Option<Self>) -> *const T {
+        this.map_or(ptr::null(), Arc::into_raw)
+    }
+    /// Convert a raw pointer obtained from `option_into_raw()`, or from a nullable C `T*` produced that way, back
+    /// into an optional `Arc<T>`
+    ///
+    /// # Safety
+    /// This function must be called with a null pointer or a pointer obtained from `into_raw()` or
+    /// `option_into_raw()`, which is then invalidated.
+    #[inline]
+    pub unsafe fn option_from_raw(ptr: *const T) -> Option<Arc<T>> {
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Arc::from_raw(ptr))
+        }
+    }
 }
 
 impl<T: ?Sized> Arc<T> {
     /// Borrow this `Arc<T>` as an `ArcBorrow<T>`
     #[inline]
-    pub fn borrow_arc(&self) -> ArcBorrow<T> {
+    pub fn borrow_arc(&self) -> ArcBorrow<'_, T> {
         unsafe { ArcBorrow::from_ref(self.deref()) }
     }
     /// Leak this `Arc<T>`, getting an `ArcBorrow<'static, T>`
@@ -141,6 +196,8 @@ impl<T: ?Sized> Arc<T> {
     /// is then invalidated.
     #[inline]
     pub unsafe fn from_raw(ptr: *const T) -> Arc<T> {
+        #[cfg(feature = "debug-tracking")]
+        debug::check_live(ptr, "Arc::from_raw");
         Arc {
             ptr: ptr::NonNull::new_unchecked(ptr as *mut T),
             phantom: PhantomData,
@@ -153,6 +210,7 @@ impl<T: ?Sized> Arc<T> {
         ptr::drop_in_place(self.ptr.as_ptr());
         // Step 2: free Inner
         let (layout, data) = ArcInner::inner_ptr_mut(self.ptr.as_ptr());
+        track_dealloc(self.ptr.as_ptr(), layout);
         dealloc(data, layout)
     }
     /// Get a reference to the reference count of this `Arc`
@@ -186,16 +244,80 @@ impl<T: ?Sized> Arc<T> {
     /// true if they come from the same allocation
     #[inline]
     pub fn ptr_eq(this: &Self, other: &Self) -> bool {
-        this.ptr == other.ptr
+        ptr::eq(this.ptr.as_ptr(), other.ptr.as_ptr())
     }
-}
-
-impl<T: ?Sized> Drop for Arc<T> {
+    /// Increment the reference count of this `Arc` by `n` with a single atomic operation, without creating
+    /// any new `Arc`s.
+    ///
+    /// The new references are leaked unless they are later released, e.g. using [`Arc::decrement_by`] or
+    /// [`ArcBorrow::into_arc_n`].
     #[inline]
-    fn drop(&mut self) {
+    pub fn increment_by(this: &Self, n: usize) {
+        // See `Arc::clone` for the reasoning behind the relaxed ordering and the overflow check. We also treat
+        // `n` itself being too large as an overflow, to guarantee `fetch_add` cannot overflow.
+        if n > MAX_REFCOUNT {
+            refcount_overflow(this.borrow_refcount(), 0);
+            return;
+        }
+        let old_size = this.borrow_refcount().fetch_add(n, Relaxed);
+        if old_size > MAX_REFCOUNT - n {
+            refcount_overflow(this.borrow_refcount(), n);
+        }
+        #[cfg(feature = "stats")]
+        stats::record_clones::<T>(n);
+    }
+    /// Decrement the reference count of this `Arc` by `n` with a single atomic operation.
+    ///
+    /// # Safety
+    /// The caller must own `n` references to this `Arc`'s allocation *other* than `this`, e.g. created
+    /// by [`Arc::increment_by`], which are invalidated.
+    #[inline]
+    pub unsafe fn decrement_by(this: &Self, n: usize) {
+        // Since `this` holds a reference, this can never release the last reference to the allocation
+        let old_size = this.borrow_refcount().release(n);
+        #[cfg(feature = "stats")]
+        stats::record_drops::<T>(n);
+        // A saturated count is never lowered
+        let old_size = match old_size {
+            Some(old_size) => old_size,
+            None => return,
+        };
+        #[cfg(feature = "debug-tracking")]
+        debug::check_underflow(this.ptr.as_ptr(), old_size, n);
+        debug_assert!(old_size > n, "Reference count underflow");
+    }
+    /// Create `n` clones of this `Arc` with a single atomic operation
+    ///
+    /// Any clones not taken from the returned iterator are released, again with a single atomic operation,
+    /// when it is dropped. If this saturates the reference count (see [`OverflowAction::Saturate`]), the clones
+    /// never release their references, as the allocation is then leaked.
+    #[inline]
+    pub fn clone_n(this: &Self, n: usize) -> ArcClones<T> {
+        Arc::increment_by(this, n);
+        unsafe { ArcClones::new(this.ptr, n) }
+    }
+    /// Release `n` references to this `Arc`'s allocation with a single atomic operation,
+    /// deallocating it if these were the last ones.
+    ///
+    /// # Safety
+    /// The caller must own `n >= 1` references to this allocation, including `self`, which is invalidated.
+    #[inline]
+    unsafe fn release(&mut self, n: usize) {
         // Because `fetch_sub` is already atomic, we do not need to synchronize
         // with other threads unless we are going to delete the object.
-        if self.borrow_refcount().fetch_sub(1, Release) != 1 {
+        #[cfg(feature = "debug-tracking")]
+        debug::check_live(self.ptr.as_ptr(), "release");
+        let old_size = self.borrow_refcount().release(n);
+        #[cfg(feature = "stats")]
+        stats::record_drops::<T>(n);
+        // A saturated count is never lowered, so its allocation is never freed
+        let old_size = match old_size {
+            Some(old_size) => old_size,
+            None => return,
+        };
+        #[cfg(feature = "debug-tracking")]
+        debug::check_underflow(self.ptr.as_ptr(), old_size, n);
+        if old_size != n {
             return;
         }
 
@@ -221,9 +343,14 @@ impl<T: ?Sized> Drop for Arc<T> {
         // [2]: https://github.com/rust-lang/rust/pull/41714
         self.borrow_refcount().load(Acquire);
 
-        unsafe {
-            self.drop_slow();
-        }
+        self.drop_slow();
+    }
+}
+
+impl<T: ?Sized> Drop for Arc<T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { self.release(1) }
     }
 }
 
@@ -250,11 +377,14 @@ impl<T: ?Sized> Clone for Arc<T> {
         // the reference count at once. This branch will never be taken in
         // any realistic program.
         //
-        // We abort because such a program is incredibly degenerate, and we
-        // don't care to support it.
+        // By default, we abort because such a program is incredibly degenerate,
+        // and we don't care to support it, but this can be configured to panic
+        // or saturate instead (see `OverflowAction`).
         if old_size > MAX_REFCOUNT {
-            abort();
+            refcount_overflow(self.borrow_refcount(), 1);
         }
+        #[cfg(feature = "stats")]
+        stats::record_clones::<T>(1);
 
         Arc {
             ptr: self.ptr,
@@ -263,6 +393,82 @@ impl<T: ?Sized> Clone for Arc<T> {
     }
 }
 
+/// An iterator over clones of an `Arc` whose references were acquired in bulk
+///
+/// Returned by [`Arc::clone_n`] and [`ArcBorrow::into_arc_n`]. The references not yet yielded are all
+/// released with a single atomic operation when the iterator is dropped.
+pub struct ArcClones<T: ?Sized> {
+    ptr: ptr::NonNull<T>,
+    remaining: usize,
+    phantom: PhantomData<T>,
+}
+
+unsafe impl<T: ?Sized + Sync + Send> Send for ArcClones<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Sync for ArcClones<T> {}
+
+impl<T: ?Sized> ArcClones<T> {
+    /// Create an iterator yielding `n` `Arc`s pointing to `ptr`
+    ///
+    /// # Safety
+    /// The caller must own `n` references to the `Arc` allocation `ptr` points into
+    #[inline]
+    pub(crate) unsafe fn new(ptr: ptr::NonNull<T>, n: usize) -> ArcClones<T> {
+        ArcClones {
+            ptr,
+            remaining: n,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Iterator for ArcClones<T> {
+    type Item = Arc<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Arc<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(Arc {
+            ptr: self.ptr,
+            phantom: PhantomData,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: ?Sized> ExactSizeIterator for ArcClones<T> {}
+
+impl<T: ?Sized> FusedIterator for ArcClones<T> {}
+
+impl<T: ?Sized> Drop for ArcClones<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.remaining != 0 {
+            let mut arc = mem::ManuallyDrop::new(Arc {
+                ptr: self.ptr,
+                phantom: PhantomData,
+            });
+            unsafe { arc.release(self.remaining) }
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for ArcClones<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // We do not print the value, since it may have been freed if there are no references remaining
+        f.debug_struct("ArcClones")
+            .field("ptr", &self.ptr)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
 impl<T: ?Sized> Deref for Arc<T> {
     type Target = T;
 
@@ -272,10 +478,24 @@ impl<T: ?Sized> Deref for Arc<T> {
     }
 }
 
-impl<T: Clone> Arc<T> {
+impl<T: ?Sized + CloneToUninit> Arc<T> {
+    /// Allocate a new, unique `Arc<T>` containing a clone of `value`
+    ///
+    /// Unlike `Arc::new(value.clone())`, this works for unsized types such as `[T]` and `str`,
+    /// cloning directly into the new allocation.
+    pub fn new_clone(value: &T) -> Arc<T> {
+        let (layout, offset) = ArcInner::data_offset(value);
+        let ptr = clone_to_new_alloc(atomic::AtomicUsize::new(1), value, layout, offset);
+        track_alloc(ptr.as_ptr(), layout);
+        Arc {
+            ptr,
+            phantom: PhantomData,
+        }
+    }
     /// Makes a mutable reference to the `ArcHandle`, cloning if necessary
     ///
-    /// This is functionally equivalent to [`Arc::make_mut`][mm] from the standard library.
+    /// This is functionally equivalent to [`Arc::make_mut`][mm] from the standard library,
+    /// but also supports unsized types implementing [`CloneToUninit`], such as `[T]` and `str`.
     ///
     /// If this `ArcHandle` is uniquely owned, `make_mut()` will provide a mutable
     /// reference to the contents. If not, `make_mut()` will create a _new_ `ArcHandle`
@@ -290,7 +510,7 @@ impl<T: Clone> Arc<T> {
     pub fn make_mut(this: &mut Self) -> &mut T {
         if !this.is_unique() {
             // Another pointer exists; clone
-            *this = Arc::new((**this).clone());
+            *this = Arc::new_clone(&**this);
         }
 
         unsafe {
@@ -308,7 +528,7 @@ impl<T: Clone> Arc<T> {
         if this.is_unique() {
             ArcBox(this)
         } else {
-            ArcBox::new(this.deref().clone())
+            ArcBox(Arc::new_clone(this.deref()))
         }
     }
 }
@@ -391,17 +611,54 @@ impl<T> From<T> for Arc<T> {
     }
 }
 
+impl<T> From<Vec<T>> for Arc<[T]> {
+    #[inline]
+    fn from(v: Vec<T>) -> Self {
+        ArcVec::from(v).freeze()
+    }
+}
+
+impl<T: Clone> From<&[T]> for Arc<[T]> {
+    #[inline]
+    fn from(s: &[T]) -> Self {
+        ArcVec::from(s).freeze()
+    }
+}
+
+impl<T> FromIterator<T> for Arc<[T]> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<ArcVec<_>>().freeze()
+    }
+}
+
+impl From<&str> for Arc<str> {
+    #[inline]
+    fn from(s: &str) -> Self {
+        let bytes: Arc<[u8]> = s.as_bytes().into();
+        // This is safe since the bytes were copied from a valid `str`
+        unsafe { Arc::from_raw(Arc::into_raw(bytes) as *const str) }
+    }
+}
+
+impl From<String> for Arc<str> {
+    #[inline]
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
 impl<T: ?Sized> Borrow<T> for Arc<T> {
     #[inline]
     fn borrow(&self) -> &T {
-        &**self
+        self
     }
 }
 
 impl<T: ?Sized> AsRef<T> for Arc<T> {
     #[inline]
     fn as_ref(&self) -> &T {
-        &**self
+        self
     }
 }
 
@@ -441,6 +698,26 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for Arc<T> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Arc<str> {
+    fn deserialize<D>(deserializer: D) -> Result<Arc<str>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Arc::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Arc<[T]> {
+    fn deserialize<D>(deserializer: D) -> Result<Arc<[T]>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        ArcVec::deserialize(deserializer).map(ArcVec::freeze)
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<T: ?Sized + Serialize> Serialize for Arc<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -467,6 +744,8 @@ unsafe impl<T: ?Sized + Erasable> ErasablePtr for Arc<T> {
 mod slice_dst_impl {
     use super::*;
     use slice_dst::{AllocSliceDst, SliceDst, TryAllocSliceDst};
+    #[cfg(feature = "serde")]
+    use slice_dst::{SliceWithHeader, StrWithHeader};
 
     unsafe impl<S: ?Sized + SliceDst> TryAllocSliceDst<S> for Arc<S> {
         unsafe fn try_new_slice_dst<I, E>(len: usize, init: I) -> Result<Self, E>
@@ -511,6 +790,7 @@ mod slice_dst_impl {
 
             // Successful construction: forget the drop guard and make an `Arc`
             mem::forget(drop_guard);
+            track_alloc(ptr.as_ptr(), inner_layout);
             Ok(Arc {
                 ptr,
                 phantom: PhantomData,
@@ -532,24 +812,277 @@ mod slice_dst_impl {
             }
         }
     }
+
+    /// Deserializes a `SliceWithHeader` from a `(header, slice)` tuple
+    #[cfg(feature = "serde")]
+    impl<'de, H: Deserialize<'de>, T: Deserialize<'de>> Deserialize<'de>
+        for Arc<SliceWithHeader<H, T>>
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: ::serde::de::Deserializer<'de>,
+        {
+            let (header, slice): (H, Vec<T>) = Deserialize::deserialize(deserializer)?;
+            Ok(SliceWithHeader::new(header, slice))
+        }
+    }
+
+    /// Deserializes a `StrWithHeader` from a `(header, str)` tuple
+    #[cfg(feature = "serde")]
+    impl<'de, H: Deserialize<'de>> Deserialize<'de> for Arc<StrWithHeader<H>> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: ::serde::de::Deserializer<'de>,
+        {
+            let (header, s): (H, String) = Deserialize::deserialize(deserializer)?;
+            Ok(StrWithHeader::new(header, &s))
+        }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+pub(crate) use rkyv_impl::alloc_rkyv;
+
+#[cfg(feature = "rkyv")]
+mod rkyv_impl {
+    use super::*;
+    use core::alloc::LayoutError;
+    use rkyv::de::{FromMetadata, Metadata, Pooling, PoolingExt, SharedPointer};
+    use rkyv::ptr_meta::{self, Pointee};
+    use rkyv::rancor::{Fallible, Source};
+    use rkyv::rc::{ArcFlavor, ArchivedRc, RcResolver};
+    use rkyv::ser::{Sharing, Writer};
+    use rkyv::traits::LayoutRaw;
+    use rkyv::{Archive, ArchiveUnsized, DeserializeUnsized, Place, SerializeUnsized};
+
+    /// Allocate an `ArcInner` with a reference count of one for a value with the given metadata, returning its
+    /// layout, the allocation and a pointer to the (uninitialized) data
+    pub(crate) fn alloc_rkyv<T: LayoutRaw + ?Sized>(
+        metadata: T::Metadata,
+    ) -> Result<(Layout, *mut u8, *mut T), LayoutError> {
+        let (layout, offset) =
+            Layout::new::<atomic::AtomicUsize>().extend(T::layout_raw(metadata)?)?;
+        let layout = layout.pad_to_align();
+        unsafe {
+            let base = alloc(layout);
+            if base.is_null() {
+                handle_alloc_error(layout)
+            }
+            ptr::write(
+                base as *mut atomic::AtomicUsize,
+                atomic::AtomicUsize::new(1),
+            );
+            let data = ptr_meta::from_raw_parts_mut(base.add(offset) as *mut (), metadata);
+            track_alloc(data, layout);
+            Ok((layout, base, data))
+        }
+    }
+
+    /// Archived as an `ArchivedRc`, sharing the representation of `std::sync::Arc`
+    impl<T: ArchiveUnsized + ?Sized> Archive for Arc<T> {
+        type Archived = ArchivedRc<T::Archived, ArcFlavor>;
+        type Resolver = RcResolver;
+
+        fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+            ArchivedRc::resolve_from_ref(&**self, resolver, out)
+        }
+    }
+
+    /// Serialized using the serializer's shared pointer registry, so each allocation is archived only once
+    impl<T, S> rkyv::Serialize<S> for Arc<T>
+    where
+        T: SerializeUnsized<S> + ?Sized + 'static,
+        S: Fallible + Writer + Sharing + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            ArchivedRc::<T::Archived, ArcFlavor>::serialize_from_ref(&**self, serializer)
+        }
+    }
+
+    unsafe impl<T: LayoutRaw + Pointee + ?Sized> SharedPointer<T> for Arc<T> {
+        fn alloc(metadata: T::Metadata) -> Result<*mut T, LayoutError> {
+            alloc_rkyv::<T>(metadata).map(|(_, _, data)| data)
+        }
+
+        unsafe fn from_value(ptr: *mut T) -> *mut T {
+            // The reference count was initialized to one by `alloc`
+            ptr
+        }
+
+        unsafe fn drop(ptr: *mut T) {
+            mem::drop(Arc::from_raw(ptr))
+        }
+    }
+
+    /// Deserialized using the deserializer's shared pointer pool, so each archived allocation is deserialized
+    /// into a single shared `Arc`
+    impl<T, D> rkyv::Deserialize<Arc<T>, D> for ArchivedRc<T::Archived, ArcFlavor>
+    where
+        T: ArchiveUnsized + LayoutRaw + Pointee + ?Sized + 'static,
+        T::Archived: DeserializeUnsized<T, D>,
+        T::Metadata: Into<Metadata> + FromMetadata,
+        D: Fallible + Pooling + ?Sized,
+        D::Error: Source,
+    {
+        fn deserialize(&self, deserializer: &mut D) -> Result<Arc<T>, D::Error> {
+            let ptr = deserializer.deserialize_shared::<T, Arc<T>>(self.get())?;
+            // The pool holds its own reference, which we do not take ownership of
+            let pooled = mem::ManuallyDrop::new(unsafe { Arc::from_raw(ptr) });
+            Ok(Arc::clone(&pooled))
+        }
+    }
 }
 
+#[cfg(feature = "proptest")]
+mod proptest_impl {
+    use super::*;
+    use proptest::arbitrary::{any_with, Arbitrary, StrategyFor};
+    use proptest::strategy::{MapInto, Strategy};
+
+    impl<T: Arbitrary> Arbitrary for Arc<T> {
+        type Parameters = T::Parameters;
+        type Strategy = MapInto<StrategyFor<T>, Arc<T>>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<T>(args).prop_map_into()
+        }
+    }
+
+    impl<T: Arbitrary> Arbitrary for Arc<[T]> {
+        type Parameters = <Vec<T> as Arbitrary>::Parameters;
+        type Strategy = MapInto<StrategyFor<Vec<T>>, Arc<[T]>>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<T>>(args).prop_map_into()
+        }
+    }
+
+    impl Arbitrary for Arc<str> {
+        type Parameters = <String as Arbitrary>::Parameters;
+        type Strategy = MapInto<StrategyFor<String>, Arc<str>>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<String>(args).prop_map_into()
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+pub use arbitrary_impl::SharedArcs;
+
 #[cfg(feature = "arbitrary")]
 mod arbitrary_impl {
     use super::*;
     use arbitrary::{Arbitrary, Result, Unstructured};
-    impl<T: Arbitrary> Arbitrary for Arc<T> {
-        fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+    #[cfg(feature = "slice-dst")]
+    use slice_dst::{SliceWithHeader, StrWithHeader};
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Arc<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
             T::arbitrary(u).map(Arc::new)
         }
-        fn arbitrary_take_rest(u: Unstructured<'_>) -> Result<Self> {
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
             T::arbitrary_take_rest(u).map(Arc::new)
         }
         fn size_hint(depth: usize) -> (usize, Option<usize>) {
             T::size_hint(depth)
         }
-        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-            Box::new(self.deref().shrink().map(Arc::new))
+    }
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Arc<[T]> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+            (0, None)
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for Arc<str> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            <&str>::arbitrary(u).map(Arc::from)
+        }
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            <&str>::arbitrary_take_rest(u).map(Arc::from)
+        }
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            <&str>::size_hint(depth)
+        }
+    }
+
+    #[cfg(feature = "slice-dst")]
+    impl<'a, H: Arbitrary<'a>, T: Arbitrary<'a>> Arbitrary<'a> for Arc<SliceWithHeader<H, T>> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let header = H::arbitrary(u)?;
+            let slice = u.arbitrary_iter()?.collect::<Result<Vec<T>>>()?;
+            Ok(SliceWithHeader::new(header, slice))
+        }
+        fn arbitrary_take_rest(mut u: Unstructured<'a>) -> Result<Self> {
+            let header = H::arbitrary(&mut u)?;
+            let slice = u.arbitrary_take_rest_iter()?.collect::<Result<Vec<T>>>()?;
+            Ok(SliceWithHeader::new(header, slice))
+        }
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            H::size_hint(depth)
+        }
+    }
+
+    #[cfg(feature = "slice-dst")]
+    impl<'a, H: Arbitrary<'a>> Arbitrary<'a> for Arc<StrWithHeader<H>> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let header = H::arbitrary(u)?;
+            Ok(StrWithHeader::new(header, <&str>::arbitrary(u)?))
+        }
+        fn arbitrary_take_rest(mut u: Unstructured<'a>) -> Result<Self> {
+            let header = H::arbitrary(&mut u)?;
+            Ok(StrWithHeader::new(header, <&str>::arbitrary_take_rest(u)?))
+        }
+        fn size_hint(depth: usize) -> (usize, Option<usize>) {
+            arbitrary::size_hint::and(H::size_hint(depth), <&str>::size_hint(depth))
+        }
+    }
+
+    /// A collection of `Arc`s with genuine sharing, for exercising `is_unique`, `make_mut` and friends in fuzzers
+    ///
+    /// An arbitrary `SharedArcs<T>` is generated by creating a pool of at most as many distinct allocations as the
+    /// collection's length, and then filling the collection with clones of arbitrarily chosen members of the pool,
+    /// so the same allocation may appear any number of times. Allocations appearing only once are unique.
+    ///
+    /// ```rust
+    /// # use elysees::{Arc, SharedArcs};
+    /// # use arbitrary::{Arbitrary, Unstructured};
+    /// let mut u = Unstructured::new(&[0xFF; 32]);
+    /// let SharedArcs(arcs) = SharedArcs::<u8>::arbitrary(&mut u).unwrap();
+    /// assert!(arcs.iter().any(|arc| !arc.is_unique()));
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct SharedArcs<T: ?Sized>(pub Vec<Arc<T>>);
+
+    impl<'a, T: ?Sized> Arbitrary<'a> for SharedArcs<T>
+    where
+        Arc<T>: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let len = u.arbitrary_len::<u32>()?;
+            if len == 0 {
+                return Ok(SharedArcs(Vec::new()));
+            }
+            let pool_len = u.int_in_range(1..=len)?;
+            let pool = (0..pool_len)
+                .map(|_| Arc::<T>::arbitrary(u))
+                .collect::<Result<Vec<_>>>()?;
+            let mut arcs = Vec::with_capacity(len);
+            for _ in 0..len {
+                arcs.push(u.choose(&pool)?.clone())
+            }
+            Ok(SharedArcs(arcs))
+        }
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+            (0, None)
         }
     }
 }
@@ -565,7 +1098,7 @@ mod tests {
             id: usize,
             name: &'static str,
             hash: u64,
-        };
+        }
         let inner = ArcInner {
             count: atomic::AtomicUsize::new(1),
             data: MyStruct {
@@ -0,0 +1,419 @@
+use crate::*;
+
+/// A soft limit on the amount of references that may be made to an `Arc32`.
+const MAX_REFCOUNT32: u32 = i32::MAX as u32;
+
+/// The object allocated by an `Arc32<T>`
+#[repr(C)]
+pub struct Arc32Inner<T: ?Sized> {
+    pub(crate) count: atomic::AtomicU32,
+    pub(crate) data: T,
+}
+
+impl<T: ?Sized> Arc32Inner<T> {
+    /// Get the theoretical offset of a piece of data in an `Arc32Inner`, as well as the layout of that
+    /// `Arc32Inner`
+    #[inline]
+    pub fn data_offset(data: &T) -> (Layout, usize) {
+        let atomic_layout = Layout::new::<atomic::AtomicU32>();
+        let (layout, offset) = atomic_layout
+            .extend(Layout::for_value(data))
+            .unwrap_or_else(|_| abort());
+        let layout = layout.pad_to_align();
+        (layout, offset)
+    }
+    /// Get an untyped mutable pointer to the inner data from a data pointer, along with a layout
+    #[inline]
+    unsafe fn inner_ptr_mut(ptr: *mut T) -> (Layout, *mut u8) {
+        let (layout, data_offset) = Arc32Inner::data_offset(&*ptr);
+        (layout, (ptr as *mut u8).sub(data_offset))
+    }
+    /// Get a reference to the reference count of an allocation from a data pointer
+    #[inline]
+    unsafe fn refcount_ptr<'a>(ptr: *const T) -> &'a atomic::AtomicU32 {
+        #[allow(clippy::cast_ptr_alignment)]
+        &*(Arc32Inner::inner_ptr_mut(ptr as *mut T).1 as *const atomic::AtomicU32)
+    }
+}
+
+/// Record a new `Arc32` allocation with the given layout and a reference count of one, given a pointer to its data
+#[inline]
+#[allow(unused_variables)]
+fn track_alloc32<T: ?Sized>(ptr: *const T, layout: Layout) {
+    #[cfg(feature = "debug-tracking")]
+    debug::register(ptr, unsafe { Arc32Inner::refcount_ptr(ptr) });
+    #[cfg(feature = "stats")]
+    stats::record_alloc::<T>(layout.size());
+}
+
+/// Record that an `Arc32` allocation with the given layout is about to be freed, given a pointer to its data
+#[inline]
+#[allow(unused_variables)]
+fn track_dealloc32<T: ?Sized>(ptr: *const T, layout: Layout) {
+    #[cfg(feature = "debug-tracking")]
+    debug::unregister(ptr);
+    #[cfg(feature = "stats")]
+    stats::record_dealloc::<T>(layout.size());
+}
+
+/// An atomically reference counted shared pointer with a 32-bit reference count
+///
+/// This behaves exactly like an [`Arc`], but stores its reference count in an `AtomicU32` rather than an
+/// `AtomicUsize`, saving space on 64-bit targets for small payloads: for example, an `Arc32<u32>` fits in an
+/// 8-byte allocation, rather than the 16 bytes taken by an `Arc<u32>`. In exchange, an `Arc32` supports at most
+/// `i32::MAX` references; exceeding this limit triggers the overflow policy (see [`OverflowAction`]).
+///
+/// ```rust
+/// # use elysees::Arc32;
+/// let x = Arc32::new(5u32);
+/// let y = x.clone();
+/// assert_eq!(*y, 5);
+/// assert!(Arc32::ptr_eq(&x, &y));
+/// assert!(!x.is_unique());
+/// ```
+#[repr(transparent)]
+pub struct Arc32<T: ?Sized> {
+    ptr: ptr::NonNull<T>,
+    phantom: PhantomData<T>,
+}
+
+unsafe impl<T: ?Sized + Sync + Send> Send for Arc32<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Sync for Arc32<T> {}
+
+impl<T> Arc32<T> {
+    /// Construct an `Arc32<T>`
+    #[inline]
+    pub fn new(data: T) -> Self {
+        let inner = Arc32Inner {
+            count: atomic::AtomicU32::new(1),
+            data,
+        };
+        let layout = Layout::for_value(&inner);
+        let alloc_ref = unsafe {
+            let allocation = alloc(layout) as *mut Arc32Inner<T>;
+            if allocation.is_null() {
+                handle_alloc_error(layout)
+            }
+            ptr::write(allocation, inner);
+            &*allocation
+        };
+        track_alloc32(&alloc_ref.data, layout);
+        Arc32 {
+            ptr: (&alloc_ref.data).into(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Arc32<T> {
+    /// Convert the `Arc32<T>` to a raw pointer
+    ///
+    /// Note: This returns a pointer to the data T, which is offset in the allocation.
+    #[inline]
+    pub fn into_raw(this: Self) -> *const T {
+        let ptr = this.ptr;
+        mem::forget(this);
+        ptr.as_ptr()
+    }
+    /// Get the raw pointer underlying this `Arc32<T>`
+    #[inline]
+    pub fn as_ptr(this: &Arc32<T>) -> *const T {
+        this.ptr.as_ptr()
+    }
+    /// Convert the `Arc32<T>` from a raw pointer obtained from `into_raw()`
+    ///
+    /// # Safety
+    /// This function must be called with a pointer obtained from `Arc32::into_raw()`, which
+    /// is then invalidated.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *const T) -> Arc32<T> {
+        #[cfg(feature = "debug-tracking")]
+        debug::check_live(ptr, "Arc32::from_raw");
+        Arc32 {
+            ptr: ptr::NonNull::new_unchecked(ptr as *mut T),
+            phantom: PhantomData,
+        }
+    }
+    /// Get a reference to the reference count of this `Arc32`
+    #[inline]
+    fn borrow_refcount(&self) -> &atomic::AtomicU32 {
+        unsafe { Arc32Inner::refcount_ptr(self.ptr.as_ptr()) }
+    }
+    /// Whether or not the `Arc32` is uniquely owned (is the refcount 1?).
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        // See `Arc::is_unique` for why this needs to be Acquire.
+        Arc32::count(self, Acquire) == 1
+    }
+    /// Get the reference count of this `Arc32` with a given ordering
+    #[inline]
+    pub fn count(this: &Arc32<T>, ordering: LoadOrdering) -> u32 {
+        this.borrow_refcount().load(ordering)
+    }
+    /// Compare two `Arc32`s via pointer equality. Will only return
+    /// true if they come from the same allocation
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ptr::eq(this.ptr.as_ptr(), other.ptr.as_ptr())
+    }
+    /// Increment the reference count of this `Arc32` by `n` with a single atomic operation, without creating
+    /// any new `Arc32`s.
+    ///
+    /// The new references are leaked unless they are later released using [`Arc32::decrement_by`].
+    #[inline]
+    pub fn increment_by(this: &Self, n: u32) {
+        // See `Arc::increment_by`
+        if n > MAX_REFCOUNT32 {
+            refcount_overflow(this.borrow_refcount(), 0);
+            return;
+        }
+        let old_size = this.borrow_refcount().fetch_add(n, Relaxed);
+        if old_size > MAX_REFCOUNT32 - n {
+            refcount_overflow(this.borrow_refcount(), n as usize);
+        }
+        #[cfg(feature = "stats")]
+        stats::record_clones::<T>(n as usize);
+    }
+    /// Decrement the reference count of this `Arc32` by `n` with a single atomic operation.
+    ///
+    /// # Safety
+    /// The caller must own `n` references to this `Arc32`'s allocation *other* than `this`, e.g. created
+    /// by [`Arc32::increment_by`], which are invalidated.
+    #[inline]
+    pub unsafe fn decrement_by(this: &Self, n: u32) {
+        // Since `this` holds a reference, this can never release the last reference to the allocation. A saturated
+        // count is never lowered.
+        let old_size = this.borrow_refcount().release(n as usize);
+        #[cfg(feature = "stats")]
+        stats::record_drops::<T>(n as usize);
+        if let Some(old_size) = old_size {
+            #[cfg(feature = "debug-tracking")]
+            debug::check_underflow(this.ptr.as_ptr(), old_size, n as usize);
+            debug_assert!(old_size > n as usize, "Reference count underflow");
+        }
+    }
+    /// Get a mutable reference to the contents of this `Arc32` if it is unique
+    #[inline]
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if this.is_unique() {
+            Some(unsafe { &mut *this.ptr.as_ptr() })
+        } else {
+            None
+        }
+    }
+    // Non-inlined part of `drop`. Just invokes the destructor.
+    #[inline(never)]
+    unsafe fn drop_slow(&mut self) {
+        ptr::drop_in_place(self.ptr.as_ptr());
+        let (layout, data) = Arc32Inner::inner_ptr_mut(self.ptr.as_ptr());
+        track_dealloc32(self.ptr.as_ptr(), layout);
+        dealloc(data, layout)
+    }
+}
+
+impl<T: ?Sized> Drop for Arc32<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // See `Arc::release` for the reasoning behind these orderings. A saturated count is never lowered, so its
+        // allocation is never freed.
+        #[cfg(feature = "debug-tracking")]
+        debug::check_live(self.ptr.as_ptr(), "release");
+        let old_size = self.borrow_refcount().release(1);
+        #[cfg(feature = "stats")]
+        stats::record_drops::<T>(1);
+        #[cfg(feature = "debug-tracking")]
+        if let Some(old_size) = old_size {
+            debug::check_underflow(self.ptr.as_ptr(), old_size, 1);
+        }
+        if old_size != Some(1) {
+            return;
+        }
+        self.borrow_refcount().load(Acquire);
+        unsafe { self.drop_slow() }
+    }
+}
+
+impl<T: ?Sized> Clone for Arc32<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        // See `Arc::clone` for the reasoning behind the relaxed ordering and the overflow check. The much lower
+        // limit still leaves room for ~2 billion threads to race past it before the count itself overflows.
+        let old_size = self.borrow_refcount().fetch_add(1, Relaxed);
+        if old_size > MAX_REFCOUNT32 {
+            refcount_overflow(self.borrow_refcount(), 1);
+        }
+        #[cfg(feature = "stats")]
+        stats::record_clones::<T>(1);
+        Arc32 {
+            ptr: self.ptr,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for Arc32<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { &*self.ptr.as_ptr() }
+    }
+}
+
+impl<T: ?Sized + CloneToUninit> Arc32<T> {
+    /// Allocate a new, unique `Arc32<T>` containing a clone of `value`
+    ///
+    /// Unlike `Arc32::new(value.clone())`, this works for unsized types such as `[T]` and `str`,
+    /// cloning directly into the new allocation.
+    pub fn new_clone(value: &T) -> Arc32<T> {
+        let (layout, offset) = Arc32Inner::data_offset(value);
+        let ptr = clone_to_new_alloc(atomic::AtomicU32::new(1), value, layout, offset);
+        track_alloc32(ptr.as_ptr(), layout);
+        Arc32 {
+            ptr,
+            phantom: PhantomData,
+        }
+    }
+    /// Makes a mutable reference to the contents of this `Arc32`, cloning them if necessary
+    ///
+    /// See [`Arc::make_mut`] for details.
+    #[inline]
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        if !this.is_unique() {
+            // Another pointer exists; clone
+            *this = Arc32::new_clone(&**this);
+        }
+        // We are now the only pointer to the data, and hold it mutably
+        unsafe { &mut *this.ptr.as_ptr() }
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for Arc32<T> {
+    fn eq(&self, other: &Arc32<T>) -> bool {
+        *(*self) == *(*other)
+    }
+}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for Arc32<T> {
+    fn partial_cmp(&self, other: &Arc32<T>) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for Arc32<T> {
+    fn cmp(&self, other: &Arc32<T>) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for Arc32<T> {}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for Arc32<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Arc32<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> fmt::Pointer for Arc32<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Pointer::fmt(&Arc32::as_ptr(self), f)
+    }
+}
+
+impl<T: Default> Default for Arc32<T> {
+    #[inline]
+    fn default() -> Arc32<T> {
+        Arc32::new(Default::default())
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for Arc32<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T> From<T> for Arc32<T> {
+    #[inline]
+    fn from(t: T) -> Self {
+        Arc32::new(t)
+    }
+}
+
+impl<T: Clone> From<&[T]> for Arc32<[T]> {
+    #[inline]
+    fn from(s: &[T]) -> Self {
+        Arc32::new_clone(s)
+    }
+}
+
+impl From<&str> for Arc32<str> {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Arc32::new_clone(s)
+    }
+}
+
+impl From<String> for Arc32<str> {
+    #[inline]
+    fn from(s: String) -> Self {
+        Arc32::new_clone(s.as_str())
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for Arc32<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for Arc32<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T: ?Sized> StableDeref for Arc32<T> {}
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T: ?Sized> CloneStableDeref for Arc32<T> {}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Arc32<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Arc32<T>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Arc32::new)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized + Serialize> Serialize for Arc32<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "erasable")]
+unsafe impl<T: ?Sized + Erasable> ErasablePtr for Arc32<T> {
+    fn erase(this: Self) -> ErasedPtr {
+        let ptr = unsafe { ptr::NonNull::new_unchecked(Arc32::into_raw(this) as *mut _) };
+        T::erase(ptr)
+    }
+
+    unsafe fn unerase(this: ErasedPtr) -> Self {
+        Self::from_raw(T::unerase(this).as_ptr())
+    }
+}
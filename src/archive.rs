@@ -0,0 +1,395 @@
+use crate::*;
+use alloc::boxed::Box;
+use rkyv::bytecheck::CheckBytes;
+use rkyv::de::{ErasedPtr, Pooling, PoolingState};
+use rkyv::primitive::ArchivedUsize;
+use rkyv::ptr_meta::Pointee;
+use rkyv::rancor::{Fallible, Source, Trace};
+use rkyv::rc::{ArcFlavor, ArchivedRc, RcResolver};
+use rkyv::ser::{Sharing, Writer, WriterExt};
+use rkyv::traits::{ArchivePointee, LayoutRaw};
+use rkyv::with::{ArchiveWith, DeserializeWith, SerializeWith};
+use rkyv::{
+    Archive, ArchiveUnsized, Archived, ArchivedMetadata, Place, Portable, SerializeUnsized,
+};
+use slice_dst::{SliceWithHeader, StrWithHeader};
+
+/// An `rkyv` wrapper for `Arc<SliceWithHeader<H, T>>` and `Arc<StrWithHeader<H>>` fields
+///
+/// Like a plain `Arc`, the slice-dst types are archived as an `ArchivedRc`, each allocation being archived only
+/// once and deserialized back into a single shared `Arc`.
+///
+/// ```rust
+/// # use elysees::{Arc, ArchiveSliceDst};
+/// # use slice_dst::SliceWithHeader;
+/// #[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+/// struct Tagged {
+///     #[rkyv(with = ArchiveSliceDst)]
+///     data: Arc<SliceWithHeader<u32, u8>>,
+/// }
+///
+/// let data: Arc<SliceWithHeader<u32, u8>> = SliceWithHeader::new(7, vec![1, 2, 3]);
+/// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&Tagged { data }).unwrap();
+/// let archived = rkyv::access::<ArchivedTagged, rkyv::rancor::Error>(&bytes).unwrap();
+/// assert_eq!(archived.data.header, 7);
+/// assert_eq!(archived.data.slice, [1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ArchiveSliceDst;
+
+/// The archived form of a `SliceWithHeader<H, T>`
+#[repr(C)]
+pub struct ArchivedSliceWithHeader<H, T> {
+    /// The archived header
+    pub header: H,
+    /// The archived slice
+    pub slice: [T],
+}
+
+/// The archived form of a `StrWithHeader<H>`
+#[repr(C)]
+pub struct ArchivedStrWithHeader<H> {
+    /// The archived header
+    pub header: H,
+    /// The archived string
+    pub str: str,
+}
+
+unsafe impl<H, T> Pointee for ArchivedSliceWithHeader<H, T> {
+    type Metadata = usize;
+}
+
+unsafe impl<H> Pointee for ArchivedStrWithHeader<H> {
+    type Metadata = usize;
+}
+
+unsafe impl<H: Portable, T: Portable> Portable for ArchivedSliceWithHeader<H, T> {}
+
+unsafe impl<H: Portable> Portable for ArchivedStrWithHeader<H> {}
+
+impl<H, T> ArchivePointee for ArchivedSliceWithHeader<H, T> {
+    type ArchivedMetadata = ArchivedUsize;
+
+    #[inline]
+    fn pointer_metadata(archived: &ArchivedUsize) -> usize {
+        archived.to_native() as usize
+    }
+}
+
+impl<H> ArchivePointee for ArchivedStrWithHeader<H> {
+    type ArchivedMetadata = ArchivedUsize;
+
+    #[inline]
+    fn pointer_metadata(archived: &ArchivedUsize) -> usize {
+        archived.to_native() as usize
+    }
+}
+
+impl<H, T> LayoutRaw for ArchivedSliceWithHeader<H, T> {
+    #[inline]
+    fn layout_raw(len: usize) -> Result<Layout, core::alloc::LayoutError> {
+        let (layout, _) = Layout::new::<H>().extend(Layout::array::<T>(len)?)?;
+        Ok(layout.pad_to_align())
+    }
+}
+
+impl<H> LayoutRaw for ArchivedStrWithHeader<H> {
+    #[inline]
+    fn layout_raw(len: usize) -> Result<Layout, core::alloc::LayoutError> {
+        let (layout, _) = Layout::new::<H>().extend(Layout::array::<u8>(len)?)?;
+        Ok(layout.pad_to_align())
+    }
+}
+
+unsafe impl<H, T, C> CheckBytes<C> for ArchivedSliceWithHeader<H, T>
+where
+    H: CheckBytes<C>,
+    T: CheckBytes<C>,
+    C: Fallible + ?Sized,
+    C::Error: Trace,
+{
+    unsafe fn check_bytes(value: *const Self, context: &mut C) -> Result<(), C::Error> {
+        H::check_bytes(ptr::addr_of!((*value).header), context)?;
+        <[T]>::check_bytes(ptr::addr_of!((*value).slice), context)
+    }
+}
+
+unsafe impl<H, C> CheckBytes<C> for ArchivedStrWithHeader<H>
+where
+    H: CheckBytes<C>,
+    C: Fallible + ?Sized,
+    C::Error: Source,
+{
+    unsafe fn check_bytes(value: *const Self, context: &mut C) -> Result<(), C::Error> {
+        H::check_bytes(ptr::addr_of!((*value).header), context)?;
+        str::check_bytes(ptr::addr_of!((*value).str), context)
+    }
+}
+
+impl<H: fmt::Debug, T: fmt::Debug> fmt::Debug for ArchivedSliceWithHeader<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArchivedSliceWithHeader")
+            .field("header", &self.header)
+            .field("slice", &&self.slice)
+            .finish()
+    }
+}
+
+impl<H: fmt::Debug> fmt::Debug for ArchivedStrWithHeader<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArchivedStrWithHeader")
+            .field("header", &self.header)
+            .field("str", &&self.str)
+            .finish()
+    }
+}
+
+/// A `SliceWithHeader` viewed as a serializable type. Its address is that of the `SliceWithHeader`, which is what
+/// the serializer's shared pointer registry is keyed on.
+#[repr(transparent)]
+struct SliceWithHeaderSource<H, T>(SliceWithHeader<H, T>);
+
+/// A `StrWithHeader` viewed as a serializable type
+#[repr(transparent)]
+struct StrWithHeaderSource<H>(StrWithHeader<H>);
+
+impl<H, T> SliceWithHeaderSource<H, T> {
+    #[inline]
+    fn new(value: &SliceWithHeader<H, T>) -> &Self {
+        unsafe { &*(value as *const SliceWithHeader<H, T> as *const Self) }
+    }
+}
+
+impl<H> StrWithHeaderSource<H> {
+    #[inline]
+    fn new(value: &StrWithHeader<H>) -> &Self {
+        unsafe { &*(value as *const StrWithHeader<H> as *const Self) }
+    }
+}
+
+unsafe impl<H, T> Pointee for SliceWithHeaderSource<H, T> {
+    type Metadata = usize;
+}
+
+unsafe impl<H> Pointee for StrWithHeaderSource<H> {
+    type Metadata = usize;
+}
+
+impl<H: Archive, T: Archive> ArchiveUnsized for SliceWithHeaderSource<H, T> {
+    type Archived = ArchivedSliceWithHeader<Archived<H>, Archived<T>>;
+
+    #[inline]
+    fn archived_metadata(&self) -> ArchivedMetadata<Self> {
+        ArchivedUsize::from_native(self.0.slice.len() as _)
+    }
+}
+
+impl<H: Archive> ArchiveUnsized for StrWithHeaderSource<H> {
+    type Archived = ArchivedStrWithHeader<Archived<H>>;
+
+    #[inline]
+    fn archived_metadata(&self) -> ArchivedMetadata<Self> {
+        ArchivedUsize::from_native(self.0.str.len() as _)
+    }
+}
+
+impl<H, T, S> SerializeUnsized<S> for SliceWithHeaderSource<H, T>
+where
+    H: rkyv::Serialize<S>,
+    T: rkyv::Serialize<S>,
+    S: Fallible + Writer + ?Sized,
+{
+    fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
+        // Dependencies must be written before the value itself
+        let header = self.0.header.serialize(serializer)?;
+        let slice = self
+            .0
+            .slice
+            .iter()
+            .map(|item| item.serialize(serializer))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pos = serializer.align_for::<(Archived<H>, [Archived<T>; 0])>()?;
+        unsafe {
+            serializer.resolve_aligned(&self.0.header, header)?;
+            serializer.align_for::<Archived<T>>()?;
+            for (item, resolver) in self.0.slice.iter().zip(slice) {
+                serializer.resolve_aligned(item, resolver)?;
+            }
+        }
+        serializer.align_for::<(Archived<H>, [Archived<T>; 0])>()?;
+        Ok(pos)
+    }
+}
+
+impl<H, S> SerializeUnsized<S> for StrWithHeaderSource<H>
+where
+    H: rkyv::Serialize<S>,
+    S: Fallible + Writer + ?Sized,
+{
+    fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
+        let header = self.0.header.serialize(serializer)?;
+        let pos = serializer.align_for::<Archived<H>>()?;
+        unsafe { serializer.resolve_aligned(&self.0.header, header)? };
+        serializer.write(self.0.str.as_bytes())?;
+        serializer.align_for::<Archived<H>>()?;
+        Ok(pos)
+    }
+}
+
+#[derive(Debug)]
+struct CyclicSharedPointerError;
+
+impl fmt::Display for CyclicSharedPointerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "encountered cyclic shared pointers while deserializing")
+    }
+}
+
+impl core::error::Error for CyclicSharedPointerError {}
+
+/// Deserialize a shared `Arc` from the deserializer's pool, or create it with `create` if this is the first time
+/// `archived` has been deserialized
+fn deserialize_pooled<A: ?Sized, P: Clone, D>(
+    archived: &A,
+    deserializer: &mut D,
+    create: impl FnOnce(&mut D) -> Result<P, D::Error>,
+) -> Result<P, D::Error>
+where
+    D: Fallible + Pooling + ?Sized,
+    D::Error: Source,
+{
+    unsafe fn drop_pooled<P>(ptr: ErasedPtr) {
+        mem::drop(Box::from_raw(ptr.downcast_unchecked::<P>()))
+    }
+
+    let address = archived as *const A as *const () as usize;
+    match deserializer.start_pooling(address) {
+        PoolingState::Started => {
+            let value = create(deserializer)?;
+            // The slice-dst types are not `Pointee`, so the pool holds a boxed `Arc` instead
+            let pooled = Box::into_raw(Box::new(value.clone()));
+            unsafe {
+                deserializer.finish_pooling(address, ErasedPtr::new(pooled), drop_pooled::<P>)?;
+            }
+            Ok(value)
+        }
+        PoolingState::Pending => Err(D::Error::new(CyclicSharedPointerError)),
+        PoolingState::Finished(ptr) => Ok(unsafe { (*ptr.downcast_unchecked::<P>()).clone() }),
+    }
+}
+
+impl<H: Archive, T: Archive> ArchiveWith<Arc<SliceWithHeader<H, T>>> for ArchiveSliceDst {
+    type Archived = ArchivedRc<ArchivedSliceWithHeader<Archived<H>, Archived<T>>, ArcFlavor>;
+    type Resolver = RcResolver;
+
+    fn resolve_with(
+        field: &Arc<SliceWithHeader<H, T>>,
+        resolver: RcResolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedRc::resolve_from_ref(SliceWithHeaderSource::new(field), resolver, out)
+    }
+}
+
+impl<H, T, S> SerializeWith<Arc<SliceWithHeader<H, T>>, S> for ArchiveSliceDst
+where
+    H: rkyv::Serialize<S>,
+    T: rkyv::Serialize<S>,
+    S: Fallible + Writer + Sharing + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &Arc<SliceWithHeader<H, T>>,
+        serializer: &mut S,
+    ) -> Result<RcResolver, S::Error> {
+        ArchivedRc::<Self::Archived, ArcFlavor>::serialize_from_ref(
+            SliceWithHeaderSource::new(field),
+            serializer,
+        )
+    }
+}
+
+impl<H, T, D>
+    DeserializeWith<
+        ArchivedRc<ArchivedSliceWithHeader<Archived<H>, Archived<T>>, ArcFlavor>,
+        Arc<SliceWithHeader<H, T>>,
+        D,
+    > for ArchiveSliceDst
+where
+    H: Archive,
+    T: Archive,
+    Archived<H>: rkyv::Deserialize<H, D>,
+    Archived<T>: rkyv::Deserialize<T, D>,
+    D: Fallible + Pooling + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedRc<ArchivedSliceWithHeader<Archived<H>, Archived<T>>, ArcFlavor>,
+        deserializer: &mut D,
+    ) -> Result<Arc<SliceWithHeader<H, T>>, D::Error> {
+        let archived = field.get();
+        deserialize_pooled(archived, deserializer, |deserializer| {
+            let header = rkyv::Deserialize::deserialize(&archived.header, deserializer)?;
+            let slice = archived
+                .slice
+                .iter()
+                .map(|item| rkyv::Deserialize::deserialize(item, deserializer))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(SliceWithHeader::new(header, slice))
+        })
+    }
+}
+
+impl<H: Archive> ArchiveWith<Arc<StrWithHeader<H>>> for ArchiveSliceDst {
+    type Archived = ArchivedRc<ArchivedStrWithHeader<Archived<H>>, ArcFlavor>;
+    type Resolver = RcResolver;
+
+    fn resolve_with(
+        field: &Arc<StrWithHeader<H>>,
+        resolver: RcResolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedRc::resolve_from_ref(StrWithHeaderSource::new(field), resolver, out)
+    }
+}
+
+impl<H, S> SerializeWith<Arc<StrWithHeader<H>>, S> for ArchiveSliceDst
+where
+    H: rkyv::Serialize<S>,
+    S: Fallible + Writer + Sharing + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &Arc<StrWithHeader<H>>,
+        serializer: &mut S,
+    ) -> Result<RcResolver, S::Error> {
+        ArchivedRc::<Self::Archived, ArcFlavor>::serialize_from_ref(
+            StrWithHeaderSource::new(field),
+            serializer,
+        )
+    }
+}
+
+impl<H, D>
+    DeserializeWith<
+        ArchivedRc<ArchivedStrWithHeader<Archived<H>>, ArcFlavor>,
+        Arc<StrWithHeader<H>>,
+        D,
+    > for ArchiveSliceDst
+where
+    H: Archive,
+    Archived<H>: rkyv::Deserialize<H, D>,
+    D: Fallible + Pooling + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedRc<ArchivedStrWithHeader<Archived<H>>, ArcFlavor>,
+        deserializer: &mut D,
+    ) -> Result<Arc<StrWithHeader<H>>, D::Error> {
+        let archived = field.get();
+        deserialize_pooled(archived, deserializer, |deserializer| {
+            let header = rkyv::Deserialize::deserialize(&archived.header, deserializer)?;
+            Ok(StrWithHeader::new(header, &archived.str))
+        })
+    }
+}
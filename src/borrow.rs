@@ -1,4 +1,6 @@
 use crate::*;
+#[cfg(feature = "erasable")]
+use erasable::Thin;
 
 /// A "borrowed `Arc`". This is a pointer to
 /// a T that is known to have been allocated within an
@@ -37,6 +39,17 @@ impl<'a, T: ?Sized> ArcBorrow<'a, T> {
         self.as_arc().clone()
     }
 
+    /// Take ownership of `n` references to this `Arc` as an iterator of `Arc<T>`s.
+    /// Any references not taken from the iterator are released with a single atomic operation when it is dropped.
+    ///
+    /// # Safety
+    /// The caller must own `n` references to this `Arc` which are not otherwise used, e.g. created by
+    /// [`Arc::increment_by`] or leaked with [`Arc::into_raw`]
+    #[inline]
+    pub unsafe fn into_arc_n(self, n: usize) -> ArcClones<T> {
+        ArcClones::new(self.ptr, n)
+    }
+
     /// Borrow this as an `Arc<T>`. This does *not* bump the refcount.
     #[inline]
     pub fn as_arc(&self) -> &Arc<T> {
@@ -60,12 +73,27 @@ impl<'a, T: ?Sized> ArcBorrow<'a, T> {
     /// This pointer should come from `Arc::into_raw`: this, however, will *not* consume it!
     #[inline]
     pub unsafe fn from_raw(ptr: *const T) -> Self {
+        #[cfg(feature = "debug-tracking")]
+        debug::check_live(ptr, "ArcBorrow::from_raw");
         ArcBorrow {
             ptr: ptr::NonNull::new_unchecked(ptr as *mut T),
             phantom: PhantomData,
         }
     }
 
+    /// For constructing from a nullable pointer, such as a C `T*`, which is either null or known to be Arc-backed
+    ///
+    /// # Safety
+    /// This pointer should be null or come from `Arc::into_raw`: this, however, will *not* consume it!
+    #[inline]
+    pub unsafe fn from_nullable(ptr: *const T) -> Option<Self> {
+        if ptr.is_null() {
+            None
+        } else {
+            Some(ArcBorrow::from_raw(ptr))
+        }
+    }
+
     /// Get the internal pointer of an `ArcBorrow`
     #[inline]
     pub fn into_raw(this: Self) -> *const T {
@@ -76,7 +104,7 @@ impl<'a, T: ?Sized> ArcBorrow<'a, T> {
     /// true if they come from the same allocation
     #[inline]
     pub fn ptr_eq(this: Self, other: Self) -> bool {
-        this.ptr == other.ptr
+        ptr::eq(this.ptr.as_ptr(), other.ptr.as_ptr())
     }
 
     /// Similar to deref, but uses the lifetime |a| rather than the lifetime of
@@ -90,6 +118,39 @@ impl<'a, T: ?Sized> ArcBorrow<'a, T> {
     pub fn count(this: ArcBorrow<'a, T>, ordering: LoadOrdering) -> usize {
         Arc::count(this.as_arc(), ordering)
     }
+
+    /// Reborrow this for the lifetime of `self`, e.g. to pass on a borrow stored in a longer-lived struct
+    #[inline]
+    pub fn reborrow(&self) -> ArcBorrow<'_, T> {
+        *self
+    }
+
+    /// Project this borrow onto a part of the value, such as a field, keeping track of the `Arc` it belongs to
+    #[inline]
+    pub fn map<U: ?Sized, F: FnOnce(&'a T) -> &'a U>(
+        this: Self,
+        f: F,
+    ) -> MappedArcBorrow<'a, T, U> {
+        MappedArcBorrow {
+            owner: this,
+            value: f(this.get()),
+        }
+    }
+}
+
+impl<'a, T: ?Sized> From<&'a Arc<T>> for ArcBorrow<'a, T> {
+    #[inline]
+    fn from(arc: &'a Arc<T>) -> Self {
+        arc.borrow_arc()
+    }
+}
+
+#[cfg(feature = "erasable")]
+impl<'a, T: ?Sized + Erasable> From<&'a Thin<Arc<T>>> for ArcBorrow<'a, T> {
+    #[inline]
+    fn from(arc: &'a Thin<Arc<T>>) -> Self {
+        SharedArcPtr::borrow_arc(arc)
+    }
 }
 
 impl<'a, T: ?Sized> Deref for ArcBorrow<'a, T> {
@@ -184,4 +245,86 @@ unsafe impl<'a, T: ?Sized + Erasable> ErasablePtr for ArcBorrow<'a, T> {
             phantom: PhantomData,
         }
     }
-}
\ No newline at end of file
+}
+
+/// A borrow of a part of a value stored in an `Arc`, obtained with [`ArcBorrow::map`]
+///
+/// An `ArcBorrow<U>` has to point to the start of an `Arc` allocation, so a borrow of a field of an `Arc`'s value
+/// cannot be one. Instead, this pairs the borrowed part with the `ArcBorrow<T>` of the whole value, so that it is
+/// just as cheap to copy around, and can still give back an owning `Arc<T>` when one is needed.
+///
+/// ```rust
+/// # use elysees::{Arc, ArcBorrow, MappedArcBorrow};
+/// struct Person {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let person = Arc::new(Person { name: "Ada".to_string(), age: 36 });
+/// let name = ArcBorrow::map(person.borrow_arc(), |person| person.name.as_str());
+/// assert_eq!(&*name, "Ada");
+/// let initial = MappedArcBorrow::map(name, |name| &name[..1]);
+/// assert_eq!(initial.get(), "A");
+/// assert!(Arc::ptr_eq(&MappedArcBorrow::clone_owner(&initial), &person));
+/// assert_eq!(MappedArcBorrow::owner(initial).age, 36);
+/// ```
+pub struct MappedArcBorrow<'a, T: ?Sized + 'a, U: ?Sized + 'a> {
+    owner: ArcBorrow<'a, T>,
+    value: &'a U,
+}
+
+impl<'a, T: ?Sized, U: ?Sized> Copy for MappedArcBorrow<'a, T, U> {}
+impl<'a, T: ?Sized, U: ?Sized> Clone for MappedArcBorrow<'a, T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized> MappedArcBorrow<'a, T, U> {
+    /// Get the borrow of the whole value this is a part of
+    #[inline]
+    pub fn owner(this: Self) -> ArcBorrow<'a, T> {
+        this.owner
+    }
+
+    /// Clone the `Arc` owning the whole value this is a part of. This bumps the refcount.
+    #[inline]
+    pub fn clone_owner(this: &Self) -> Arc<T> {
+        this.owner.clone_arc()
+    }
+
+    /// Project this borrow further onto a part of the borrowed part
+    #[inline]
+    pub fn map<V: ?Sized, F: FnOnce(&'a U) -> &'a V>(
+        this: Self,
+        f: F,
+    ) -> MappedArcBorrow<'a, T, V> {
+        MappedArcBorrow {
+            owner: this.owner,
+            value: f(this.value),
+        }
+    }
+
+    /// Similar to deref, but uses the lifetime |a| rather than the lifetime of
+    /// self, which is incompatible with the signature of the Deref trait.
+    #[inline]
+    pub fn get(&self) -> &'a U {
+        self.value
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized> Deref for MappedArcBorrow<'a, T, U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        self.value
+    }
+}
+
+impl<'a, T: ?Sized, U: ?Sized + fmt::Debug> fmt::Debug for MappedArcBorrow<'a, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.value, f)
+    }
+}
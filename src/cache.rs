@@ -0,0 +1,336 @@
+use crate::*;
+use alloc::collections::BTreeMap;
+use core::hash::BuildHasher;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// The smallest number of entries an `ArcCache` grows to before it sweeps itself
+const MIN_SWEEP: usize = 32;
+
+/// An entry of an `ArcCache`
+struct CacheEntry<V> {
+    value: Arc<V>,
+    /// When this entry was last used, which is also its key in the cache's LRU order
+    used: u64,
+}
+
+/// The contents of an `ArcCache`, behind its lock
+struct CacheState<K, V, S> {
+    entries: HashMap<K, CacheEntry<V>, S>,
+    /// The keys of all entries, from least to most recently used
+    order: BTreeMap<u64, K>,
+    /// The time of the next use
+    clock: u64,
+    /// The number of entries at which to next sweep the cache
+    next_sweep: usize,
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> CacheState<K, V, S> {
+    /// Look up an entry and mark it as just used
+    fn touch<Q>(&mut self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.get_mut(key)?;
+        let key = self
+            .order
+            .remove(&entry.used)
+            .expect("every entry is in the LRU order");
+        entry.used = self.clock;
+        self.order.insert(self.clock, key);
+        self.clock += 1;
+        Some(entry.value.clone())
+    }
+    /// Insert a new entry, making room for it if the cache is bounded and full
+    fn insert(
+        &mut self,
+        key: K,
+        value: Arc<V>,
+        max_entries: Option<usize>,
+        removed: &mut Vec<Arc<V>>,
+    ) {
+        if self.entries.len() >= self.next_sweep {
+            self.sweep(removed);
+            self.next_sweep = MIN_SWEEP.max(2 * self.entries.len());
+        }
+        if let Some(max_entries) = max_entries {
+            self.evict(max_entries.saturating_sub(1), removed);
+        }
+        self.order.insert(self.clock, key.clone());
+        let entry = CacheEntry {
+            value,
+            used: self.clock,
+        };
+        self.clock += 1;
+        if let Some(old) = self.entries.insert(key, entry) {
+            self.order.remove(&old.used);
+            removed.push(old.value);
+        }
+    }
+    /// Remove every entry held only by the cache
+    fn sweep(&mut self, removed: &mut Vec<Arc<V>>) {
+        let order = &mut self.order;
+        self.entries.retain(|_, entry| {
+            // Nobody else can clone the `Arc` while we hold the lock, so this cannot go stale
+            if entry.value.is_unique() {
+                order.remove(&entry.used);
+                removed.push(entry.value.clone());
+                false
+            } else {
+                true
+            }
+        });
+    }
+    /// Shrink the cache to at most `max_entries` entries, forgetting the least recently used unreferenced entries, and
+    /// then the least recently used entries still in use elsewhere
+    fn evict(&mut self, max_entries: usize, removed: &mut Vec<Arc<V>>) {
+        let excess = self.entries.len().saturating_sub(max_entries);
+        if excess == 0 {
+            return;
+        }
+        let entries = &self.entries;
+        let idle: Vec<u64> = self
+            .order
+            .iter()
+            .filter(|(_, key)| entries[*key].value.is_unique())
+            .map(|(used, _)| *used)
+            .take(excess)
+            .collect();
+        for used in idle {
+            self.remove_used(used, removed);
+        }
+        while self.entries.len() > max_entries {
+            let used = *self.order.keys().next().expect("the cache is not empty");
+            self.remove_used(used, removed);
+        }
+    }
+    /// Remove the entry last used at `used`
+    fn remove_used(&mut self, used: u64, removed: &mut Vec<Arc<V>>) {
+        let key = self
+            .order
+            .remove(&used)
+            .expect("`used` is in the LRU order");
+        let entry = self
+            .entries
+            .remove(&key)
+            .expect("every key in the LRU order has an entry");
+        removed.push(entry.value);
+    }
+}
+
+/// A concurrent cache of `Arc`s which forgets values once nothing outside of it refers to them
+///
+/// This is meant for memoization and interning: [`get_or_insert_with`](ArcCache::get_or_insert_with) hands out clones
+/// of the `Arc` stored for a key, computing it on a miss, so that everyone asking for the same key shares the same
+/// value for as long as somebody holds on to it. The cache notices that it holds the only reference to a value by
+/// its count, and then drops it when it is [swept](ArcCache::sweep). Sweeping happens by hand, and also
+/// automatically whenever inserting would double the number of entries since the last sweep, which keeps the cost
+/// of sweeping constant per insertion.
+///
+/// A cache created with [`with_max_entries`](ArcCache::with_max_entries) also holds at most that many entries,
+/// making room for new ones by forgetting the least recently used entry which is no longer referenced, or failing
+/// that, the least recently used entry overall. A value forgotten while still in use stays alive for its other
+/// owners, but a later lookup of its key will compute a new one.
+///
+/// All methods take `&self`, using a lock internally, which is never held while computing or dropping a value. Keys
+/// are hashed, compared and cloned while it is held, though, so if any of those panic, the lock is poisoned and every
+/// later use of the cache panics too.
+///
+/// ```rust
+/// # use elysees::{Arc, ArcCache};
+/// let cache = ArcCache::new();
+/// let squares: Vec<Arc<u64>> = (0..10).map(|n| cache.get_or_insert_with(n, || n * n)).collect();
+/// assert!(Arc::ptr_eq(&cache.get_or_insert_with(3, || unreachable!()), &squares[3]));
+///
+/// // Values only the cache refers to are dropped when it is swept
+/// drop(squares);
+/// let four = cache.get(&2).unwrap();
+/// assert_eq!(cache.sweep(), 9);
+/// assert_eq!(cache.len(), 1);
+/// assert_eq!(*four, 4);
+/// ```
+pub struct ArcCache<K, V, S = RandomState> {
+    state: Mutex<CacheState<K, V, S>>,
+    max_entries: Option<usize>,
+}
+
+impl<K, V> ArcCache<K, V> {
+    /// Create a new, empty, unbounded cache
+    #[inline]
+    pub fn new() -> ArcCache<K, V> {
+        ArcCache::with_hasher(RandomState::new())
+    }
+    /// Create a new, empty cache which holds at most `max_entries` entries
+    ///
+    /// # Panics
+    /// Panics if `max_entries` is `0`
+    #[inline]
+    pub fn with_max_entries(max_entries: usize) -> ArcCache<K, V> {
+        ArcCache::with_max_entries_and_hasher(max_entries, RandomState::new())
+    }
+}
+
+impl<K, V, S> ArcCache<K, V, S> {
+    /// Create a new, empty, unbounded cache which uses `hasher` to hash keys
+    #[inline]
+    pub fn with_hasher(hasher: S) -> ArcCache<K, V, S> {
+        ArcCache::from_parts(None, hasher)
+    }
+    /// Create a new, empty cache which holds at most `max_entries` entries and uses `hasher` to hash keys
+    ///
+    /// # Panics
+    /// Panics if `max_entries` is `0`
+    #[inline]
+    pub fn with_max_entries_and_hasher(max_entries: usize, hasher: S) -> ArcCache<K, V, S> {
+        assert!(
+            max_entries > 0,
+            "an `ArcCache` must have room for at least one entry"
+        );
+        ArcCache::from_parts(Some(max_entries), hasher)
+    }
+    /// Get the maximum number of entries in this cache, if it is bounded
+    #[inline]
+    pub fn max_entries(&self) -> Option<usize> {
+        self.max_entries
+    }
+    /// Get the number of entries in this cache, including any unreferenced entries which have not been swept yet
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+    /// Check whether this cache is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Remove every entry from this cache
+    pub fn clear(&self) {
+        let removed = {
+            let mut state = self.lock();
+            state.order.clear();
+            state
+                .entries
+                .drain()
+                .map(|(_, entry)| entry.value)
+                .collect::<Vec<_>>()
+        };
+        drop(removed)
+    }
+
+    #[inline]
+    fn from_parts(max_entries: Option<usize>, hasher: S) -> ArcCache<K, V, S> {
+        ArcCache {
+            state: Mutex::new(CacheState {
+                entries: HashMap::with_hasher(hasher),
+                order: BTreeMap::new(),
+                clock: 0,
+                next_sweep: MIN_SWEEP,
+            }),
+            max_entries,
+        }
+    }
+    /// Lock the contents of this cache.
+    ///
+    /// # Panics
+    /// Panics if a key panicked while being hashed, compared or cloned under the lock, which may have left the
+    /// entries and their LRU order out of step.
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, CacheState<K, V, S>> {
+        self.state
+            .lock()
+            .expect("a key panicked while the `ArcCache` was locked")
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> ArcCache<K, V, S> {
+    /// Get the value cached for `key`, if any
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lock().touch(key)
+    }
+    /// Check whether a value is cached for `key`
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lock().entries.contains_key(key)
+    }
+    /// Get the value cached for `key`, or compute, cache and return one if there is none
+    ///
+    /// `f` is called without holding the cache's lock, so it may itself use the cache. If another thread caches a
+    /// value for `key` in the meantime, that value is returned and the one computed by `f` is dropped.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&self, key: K, f: F) -> Arc<V> {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = Arc::new(f());
+        let mut removed = Vec::new();
+        let result = {
+            let mut state = self.lock();
+            if let Some(existing) = state.touch(&key) {
+                removed.push(value);
+                existing
+            } else {
+                state.insert(key, value.clone(), self.max_entries, &mut removed);
+                value
+            }
+        };
+        drop(removed);
+        result
+    }
+    /// Cache `value` for `key`, returning the value previously cached for it, if any
+    pub fn insert(&self, key: K, value: Arc<V>) -> Option<Arc<V>> {
+        let mut removed = Vec::new();
+        let mut state = self.lock();
+        let old = state.entries.remove(&key).map(|entry| {
+            state.order.remove(&entry.used);
+            entry.value
+        });
+        state.insert(key, value, self.max_entries, &mut removed);
+        drop(state);
+        drop(removed);
+        old
+    }
+    /// Remove the value cached for `key`, returning it
+    #[inline]
+    pub fn remove<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut state = self.lock();
+        let entry = state.entries.remove(key)?;
+        state.order.remove(&entry.used);
+        Some(entry.value)
+    }
+    /// Drop every value which only this cache refers to, returning how many were dropped
+    pub fn sweep(&self) -> usize {
+        let mut removed = Vec::new();
+        self.lock().sweep(&mut removed);
+        removed.len()
+    }
+}
+
+impl<K, V, S: Default> Default for ArcCache<K, V, S> {
+    #[inline]
+    fn default() -> ArcCache<K, V, S> {
+        ArcCache::from_parts(None, S::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for ArcCache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArcCache")
+            .field("len", &self.len())
+            .field("max_entries", &self.max_entries)
+            .finish_non_exhaustive()
+    }
+}
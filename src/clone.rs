@@ -0,0 +1,152 @@
+use crate::*;
+
+/// A generalization of `Clone` to dynamically-sized types, mirroring the unstable `core::clone::CloneToUninit`
+///
+/// This is what allows `Arc::make_mut` and `Arc::unique` to work with `Arc<[T]>`, `Arc<str>` and
+/// `slice-dst` types, which cannot be cloned onto the stack.
+///
+/// # Safety
+/// Implementations must fully initialize `dst` with a valid clone of `self`, having the same size and metadata,
+/// and `ptr_at` must return a pointer to `dst` with the metadata of `self`.
+pub unsafe trait CloneToUninit {
+    /// Clone `self` into the uninitialized memory pointed to by `dst`
+    ///
+    /// # Safety
+    /// `dst` must be valid for writes of `size_of_val(self)` bytes and aligned to `align_of_val(self)`.
+    /// If this function panics, any parts of the clone which were already written will have been dropped.
+    unsafe fn clone_to_uninit(&self, dst: *mut u8);
+    /// Get a pointer to `dst` carrying the metadata of `self`, such as its length, e.g. to refer to a clone of
+    /// `self` written to `dst` by `clone_to_uninit`
+    fn ptr_at(&self, dst: *mut u8) -> *mut Self;
+}
+
+unsafe impl<T: Clone> CloneToUninit for T {
+    #[inline]
+    unsafe fn clone_to_uninit(&self, dst: *mut u8) {
+        ptr::write(dst as *mut T, self.clone())
+    }
+    #[inline]
+    fn ptr_at(&self, dst: *mut u8) -> *mut T {
+        dst as *mut T
+    }
+}
+
+unsafe impl<T: Clone> CloneToUninit for [T] {
+    #[inline]
+    unsafe fn clone_to_uninit(&self, dst: *mut u8) {
+        clone_slice_to_uninit(self, dst as *mut T)
+    }
+    #[inline]
+    fn ptr_at(&self, dst: *mut u8) -> *mut [T] {
+        ptr::slice_from_raw_parts_mut(dst as *mut T, self.len())
+    }
+}
+
+unsafe impl CloneToUninit for str {
+    #[inline]
+    unsafe fn clone_to_uninit(&self, dst: *mut u8) {
+        ptr::copy_nonoverlapping(self.as_ptr(), dst, self.len())
+    }
+    #[inline]
+    fn ptr_at(&self, dst: *mut u8) -> *mut str {
+        ptr::slice_from_raw_parts_mut(dst, self.len()) as *mut str
+    }
+}
+
+/// Allocate a reference counted clone of `value`, with the given layout, count, and offset of the value after
+/// the count, returning a pointer to the clone
+pub(crate) fn clone_to_new_alloc<C, T: ?Sized + CloneToUninit>(
+    count: C,
+    value: &T,
+    layout: Layout,
+    offset: usize,
+) -> ptr::NonNull<T> {
+    // Frees the allocation if cloning panics
+    struct RawAlloc(*mut u8, Layout);
+
+    impl Drop for RawAlloc {
+        fn drop(&mut self) {
+            unsafe { dealloc(self.0, self.1) }
+        }
+    }
+
+    unsafe {
+        let inner = alloc(layout);
+        if inner.is_null() {
+            handle_alloc_error(layout)
+        }
+        let guard = RawAlloc(inner, layout);
+        #[allow(clippy::cast_ptr_alignment)]
+        ptr::write(inner as *mut C, count);
+        let data = inner.add(offset);
+        value.clone_to_uninit(data);
+        mem::forget(guard);
+        ptr::NonNull::new_unchecked(value.ptr_at(data))
+    }
+}
+
+/// Clone a slice into uninitialized memory, dropping the elements cloned so far on panic
+unsafe fn clone_slice_to_uninit<T: Clone>(slice: &[T], dst: *mut T) {
+    struct InProgress<T> {
+        dst: *mut T,
+        written: usize,
+    }
+
+    impl<T> Drop for InProgress<T> {
+        fn drop(&mut self) {
+            unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.dst, self.written)) }
+        }
+    }
+
+    let mut guard = InProgress { dst, written: 0 };
+    for item in slice {
+        ptr::write(dst.add(guard.written), item.clone());
+        guard.written += 1;
+    }
+    mem::forget(guard);
+}
+
+#[cfg(feature = "slice-dst")]
+mod slice_dst_impl {
+    use super::*;
+    use alloc::boxed::Box;
+    use slice_dst::{SliceWithHeader, StrWithHeader};
+
+    // `slice-dst` keeps the length of its types private, so these are cloned through its constructors into a
+    // temporary box, which is then moved into place
+
+    /// Move the contents of a box into uninitialized memory, freeing the box
+    unsafe fn move_box_to_uninit<T: ?Sized>(boxed: Box<T>, dst: *mut u8) {
+        let layout = Layout::for_value(&*boxed);
+        let raw = Box::into_raw(boxed);
+        ptr::copy_nonoverlapping(raw as *const u8, dst, layout.size());
+        if layout.size() != 0 {
+            dealloc(raw as *mut u8, layout)
+        }
+    }
+
+    unsafe impl<H: Clone, T: Clone> CloneToUninit for SliceWithHeader<H, T> {
+        #[inline]
+        unsafe fn clone_to_uninit(&self, dst: *mut u8) {
+            let clone: Box<Self> =
+                SliceWithHeader::new(self.header.clone(), self.slice.iter().cloned());
+            move_box_to_uninit(clone, dst)
+        }
+        #[inline]
+        fn ptr_at(&self, dst: *mut u8) -> *mut Self {
+            ptr::slice_from_raw_parts_mut(dst as *mut T, self.slice.len()) as *mut Self
+        }
+    }
+
+    unsafe impl<H: Clone> CloneToUninit for StrWithHeader<H> {
+        #[inline]
+        unsafe fn clone_to_uninit(&self, dst: *mut u8) {
+            let clone: Box<Self> = StrWithHeader::new(self.header.clone(), &self.str);
+            move_box_to_uninit(clone, dst)
+        }
+        #[inline]
+        fn ptr_at(&self, dst: *mut u8) -> *mut Self {
+            ptr::slice_from_raw_parts_mut(dst, self.str.len()) as *mut Self
+        }
+    }
+}
@@ -0,0 +1,162 @@
+/*!
+Leak and double-free tracking for `Arc` and `Arc32` allocations.
+
+With the `debug-tracking` feature enabled, every live `Arc` or `Arc32` allocation is recorded in a global registry
+along with its type name and a backtrace of where it was created, which can be inspected with [`live_allocations`].
+The registry is also used to catch misuse of reference counts: dropping an `Arc` whose allocation has already been
+freed, underflowing a reference count, or calling `Arc::from_raw`, `Arc32::from_raw` or `ArcBorrow::from_raw` on a
+freed pointer all panic rather than causing undefined behaviour.
+
+Creation backtraces are captured with [`Backtrace::capture`], so they are only resolved if backtraces are enabled
+via the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables.
+
+```rust
+# use elysees::{debug, Arc};
+let arc = Arc::new(5u32);
+let address = Arc::as_ptr(&arc) as *const ();
+let allocation = debug::live_allocations()
+    .into_iter()
+    .find(|allocation| allocation.address() == address)
+    .unwrap();
+assert_eq!(allocation.type_name(), "u32");
+assert_eq!(allocation.count(), 1);
+drop(arc);
+assert!(!debug::is_live(address));
+```
+*/
+use crate::*;
+use std::backtrace::Backtrace;
+use std::collections::BTreeMap;
+use std::sync::{Arc as StdArc, Mutex, MutexGuard, PoisonError};
+
+/// The registry of live allocations, keyed by the address of their data
+static REGISTRY: Mutex<BTreeMap<usize, Entry>> = Mutex::new(BTreeMap::new());
+
+/// A registered allocation
+struct Entry {
+    /// The allocation's reference count
+    count: *const dyn RefCount,
+    /// The name of the type stored in the allocation
+    type_name: &'static str,
+    /// Where the allocation was created
+    backtrace: StdArc<Backtrace>,
+}
+
+// The reference count is only read while the allocation is registered, i.e. still alive, and is atomic
+unsafe impl Send for Entry {}
+
+/// Lock the registry, ignoring poisoning, since we never leave it in an inconsistent state
+fn registry() -> MutexGuard<'static, BTreeMap<usize, Entry>> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A snapshot of a live `Arc` allocation, as returned by [`live_allocations`]
+#[derive(Clone)]
+pub struct LiveAllocation {
+    address: *const (),
+    count: usize,
+    type_name: &'static str,
+    backtrace: StdArc<Backtrace>,
+}
+
+unsafe impl Send for LiveAllocation {}
+unsafe impl Sync for LiveAllocation {}
+
+impl LiveAllocation {
+    /// The address of the allocation's data, as returned by `Arc::as_ptr`
+    #[inline]
+    pub fn address(&self) -> *const () {
+        self.address
+    }
+    /// The reference count of the allocation when the snapshot was taken
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// The name of the type stored in the allocation
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+    /// A backtrace of where the allocation was created
+    #[inline]
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+}
+
+impl fmt::Debug for LiveAllocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LiveAllocation")
+            .field("address", &self.address)
+            .field("count", &self.count)
+            .field("type_name", &self.type_name)
+            .finish()
+    }
+}
+
+/// Get a snapshot of every live `Arc` allocation, in order of address
+pub fn live_allocations() -> Vec<LiveAllocation> {
+    registry()
+        .iter()
+        .map(|(&address, entry)| LiveAllocation {
+            address: address as *const (),
+            // Registered allocations are only freed after being unregistered, which cannot happen while we hold
+            // the lock
+            count: unsafe { (*entry.count).current() },
+            type_name: entry.type_name,
+            backtrace: entry.backtrace.clone(),
+        })
+        .collect()
+}
+
+/// Whether `ptr` points to the data of a live `Arc` allocation
+pub fn is_live<T: ?Sized>(ptr: *const T) -> bool {
+    registry().contains_key(&(ptr as *const u8 as usize))
+}
+
+/// Register a new allocation, with a reference count of one, given pointers to its data and its reference count
+pub(crate) fn register<T: ?Sized>(ptr: *const T, count: *const dyn RefCount) {
+    let entry = Entry {
+        count,
+        type_name: core::any::type_name::<T>(),
+        backtrace: StdArc::new(Backtrace::capture()),
+    };
+    registry().insert(ptr as *const u8 as usize, entry);
+}
+
+/// Unregister an allocation which is about to be freed, given a pointer to its data
+pub(crate) fn unregister<T: ?Sized>(ptr: *const T) {
+    let entry = registry().remove(&(ptr as *const u8 as usize));
+    if entry.is_none() {
+        panic!(
+            "elysees: freed {:p}, which is not a live Arc allocation",
+            ptr
+        )
+    }
+}
+
+/// Panic if `ptr` does not point to the data of a live `Arc` allocation
+pub(crate) fn check_live<T: ?Sized>(ptr: *const T, operation: &str) {
+    if !is_live(ptr) {
+        panic!(
+            "elysees: {} of {:p} ({}), which is not a live Arc allocation",
+            operation,
+            ptr,
+            core::any::type_name::<T>()
+        )
+    }
+}
+
+/// Panic if releasing `n` references from a reference count of `old` underflowed
+pub(crate) fn check_underflow<T: ?Sized>(ptr: *const T, old: usize, n: usize) {
+    if old < n {
+        panic!(
+            "elysees: reference count underflow releasing {} references to {:p} ({}) with a count of {}",
+            n,
+            ptr,
+            core::any::type_name::<T>(),
+            old
+        )
+    }
+}
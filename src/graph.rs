@@ -0,0 +1,583 @@
+use crate::*;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use core::any::Any;
+use core::convert::TryFrom;
+
+/// The magic bytes at the start of every `ArcGraphWriter` stream
+const MAGIC: &[u8; 4] = b"EAG\x01";
+
+/// How deeply an `ArcGraphReader` decodes nodes recursively before deferring them to its explicit stack
+const MAX_DECODE_DEPTH: usize = 64;
+
+/// A type which can be stored as a node of a DAG of `Arc`s by an `ArcGraphWriter`
+///
+/// Children are written with `ArcGraphEncoder::write_arc` and read back with `ArcGraphDecoder::read_arc`, in the
+/// same order; all other fields are written and read with the encoder's primitive methods.
+///
+/// Graphs are written and read with explicit stacks, so that arbitrarily deep graphs do not overflow the call stack.
+/// To that end, a node may be encoded again once the children it writes have been, and decoding a node may be
+/// retried once the children it reads have been, which is signalled by `read_arc` returning
+/// [`ArcGraphError::Deferred`]. `encode` must hence write the same data every time, and `decode` should pass errors
+/// from `read_arc` on.
+pub trait ArcGraphNode: Sized + 'static {
+    /// Encode the contents of this node
+    fn encode(&self, encoder: &mut ArcGraphEncoder);
+    /// Decode a node previously written by `encode`
+    fn decode(decoder: &mut ArcGraphDecoder) -> Result<Self, ArcGraphError>;
+}
+
+/// An error reading an `ArcGraphWriter` stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArcGraphError {
+    /// The stream does not begin with the expected magic bytes
+    InvalidMagic,
+    /// The stream or a node ended unexpectedly
+    UnexpectedEof,
+    /// A varint was too long to fit in a `u64`
+    InvalidVarint,
+    /// A string was not valid UTF-8
+    InvalidUtf8,
+    /// A node referenced an ID which does not exist
+    InvalidId(u64),
+    /// A node referenced an ID which was not written before it, which would allow cycles
+    ForwardReference(u64),
+    /// A node was read with a different type than it was previously read with
+    TypeMismatch(u64),
+    /// A node did not consume all of its data when decoded
+    TrailingBytes(u64),
+    /// The stream had data left over after its root table
+    TrailingData,
+    /// A node's data was otherwise invalid
+    Invalid(&'static str),
+    /// A child was nested too deeply to be decoded right away. Its parent is decoded again once it has been, so
+    /// this is only ever returned by `ArcGraphDecoder::read_arc`, never by `ArcGraphReader` itself.
+    Deferred,
+}
+
+impl fmt::Display for ArcGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArcGraphError::InvalidMagic => write!(f, "invalid magic bytes"),
+            ArcGraphError::UnexpectedEof => write!(f, "unexpected end of data"),
+            ArcGraphError::InvalidVarint => write!(f, "varint overflows a u64"),
+            ArcGraphError::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            ArcGraphError::InvalidId(id) => write!(f, "invalid node ID {}", id),
+            ArcGraphError::ForwardReference(id) => write!(f, "forward reference to node {}", id),
+            ArcGraphError::TypeMismatch(id) => write!(f, "node {} read with mismatched types", id),
+            ArcGraphError::TrailingBytes(id) => write!(f, "node {} has trailing bytes", id),
+            ArcGraphError::TrailingData => write!(f, "trailing data after root table"),
+            ArcGraphError::Invalid(msg) => write!(f, "invalid node: {}", msg),
+            ArcGraphError::Deferred => write!(f, "node decoding deferred"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArcGraphError {}
+
+/// Write a LEB128 varint
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8)
+}
+
+/// Read a LEB128 varint, advancing `data`
+fn read_varint(data: &mut &[u8]) -> Result<u64, ArcGraphError> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = data.split_first().ok_or(ArcGraphError::UnexpectedEof)?;
+        *data = rest;
+        if shift == 63 && byte > 1 {
+            return Err(ArcGraphError::InvalidVarint);
+        }
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err(ArcGraphError::InvalidVarint);
+        }
+    }
+}
+
+/// Read `len` bytes, advancing `data`
+fn read_slice<'a>(data: &mut &'a [u8], len: u64) -> Result<&'a [u8], ArcGraphError> {
+    if len > data.len() as u64 {
+        return Err(ArcGraphError::UnexpectedEof);
+    }
+    let (head, rest) = data.split_at(len as usize);
+    *data = rest;
+    Ok(head)
+}
+
+/// A writer for a DAG of `Arc`s, which stores every allocation exactly once
+///
+/// Nodes are identified by their `Arc::as_ptr`, and assigned sequential IDs as they are written. Every node's
+/// children are written before it, so the resulting stream is topologically ordered and can be read back by an
+/// `ArcGraphReader` with identical sharing.
+///
+/// ```rust
+/// # use elysees::*;
+/// struct Node(u64, Vec<Arc<Node>>);
+///
+/// impl ArcGraphNode for Node {
+///     fn encode(&self, encoder: &mut ArcGraphEncoder) {
+///         encoder.write_u64(self.0);
+///         encoder.write_len(self.1.len());
+///         for child in &self.1 {
+///             encoder.write_arc(child);
+///         }
+///     }
+///     fn decode(decoder: &mut ArcGraphDecoder) -> Result<Node, ArcGraphError> {
+///         let value = decoder.read_u64()?;
+///         let len = decoder.read_len()?;
+///         let children = (0..len).map(|_| decoder.read_arc()).collect::<Result<_, _>>()?;
+///         Ok(Node(value, children))
+///     }
+/// }
+///
+/// let leaf = Arc::new(Node(1, vec![]));
+/// let root = Arc::new(Node(2, vec![leaf.clone(), leaf.clone()]));
+///
+/// let mut writer = ArcGraphWriter::new();
+/// writer.write_root(&root);
+/// let bytes = writer.finish();
+///
+/// let mut reader = ArcGraphReader::new(&bytes).unwrap();
+/// let loaded: Arc<Node> = reader.read_root(0).unwrap();
+/// assert_eq!(loaded.0, 2);
+/// assert!(Arc::ptr_eq(&loaded.1[0], &loaded.1[1]));
+/// ```
+#[derive(Default)]
+pub struct ArcGraphWriter {
+    /// The IDs of the nodes written so far, keyed by address
+    ids: BTreeMap<usize, u64>,
+    /// Clones of every written `Arc`, ensuring their addresses are not reused while writing
+    keep_alive: Vec<Box<dyn Any>>,
+    /// The encoded records
+    records: Vec<u8>,
+    /// The IDs of the roots written
+    roots: Vec<u64>,
+}
+
+impl ArcGraphWriter {
+    /// Create a new, empty `ArcGraphWriter`
+    #[inline]
+    pub fn new() -> ArcGraphWriter {
+        ArcGraphWriter::default()
+    }
+    /// Get the number of distinct nodes written so far
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keep_alive.len()
+    }
+    /// Check whether no nodes have been written
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keep_alive.is_empty()
+    }
+    /// Write a node and all its descendants, if not already written, returning its ID
+    pub fn write<T: ArcGraphNode>(&mut self, arc: &Arc<T>) -> u64 {
+        if let Some(id) = self.id(arc) {
+            return id;
+        }
+        let mut stack: Vec<Box<dyn PendingNode>> = Vec::new();
+        stack.push(Box::new(arc.clone()));
+        while let Some(node) = stack.pop() {
+            if self.ids.contains_key(&node.address()) {
+                continue;
+            }
+            let mut encoder = ArcGraphEncoder {
+                writer: self,
+                buf: Vec::new(),
+                missing: Vec::new(),
+            };
+            node.encode(&mut encoder);
+            let (buf, missing) = (encoder.buf, encoder.missing);
+            if !missing.is_empty() {
+                // The node may only be assigned an ID once all its children have been, so encode it again after
+                // them, writing them in the order it refers to them
+                stack.push(node);
+                stack.extend(missing.into_iter().rev());
+                continue;
+            }
+            let id = self.keep_alive.len() as u64;
+            self.ids.insert(node.address(), id);
+            self.keep_alive.push(node.keep_alive());
+            write_varint(&mut self.records, buf.len() as u64);
+            self.records.extend_from_slice(&buf);
+        }
+        self.id(arc).expect("the node has been written")
+    }
+    /// Write a node as a root of the graph, returning its index in the root table
+    pub fn write_root<T: ArcGraphNode>(&mut self, arc: &Arc<T>) -> usize {
+        let id = self.write(arc);
+        self.roots.push(id);
+        self.roots.len() - 1
+    }
+    /// Get the ID of a node, if it has been written
+    #[inline]
+    fn id<T>(&self, arc: &Arc<T>) -> Option<u64> {
+        self.ids.get(&(Arc::as_ptr(arc) as usize)).copied()
+    }
+    /// Finish writing, returning the encoded graph
+    pub fn finish(self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.records.len() + self.roots.len() + 16);
+        out.extend_from_slice(MAGIC);
+        write_varint(&mut out, self.keep_alive.len() as u64);
+        out.extend_from_slice(&self.records);
+        write_varint(&mut out, self.roots.len() as u64);
+        for &root in &self.roots {
+            write_varint(&mut out, root);
+        }
+        out
+    }
+}
+
+impl fmt::Debug for ArcGraphWriter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArcGraphWriter")
+            .field("nodes", &self.len())
+            .field("bytes", &self.records.len())
+            .field("roots", &self.roots)
+            .finish()
+    }
+}
+
+/// A node waiting to be written by an `ArcGraphWriter`, with its type erased
+trait PendingNode {
+    /// Get the address of the node
+    fn address(&self) -> usize;
+    /// Encode the node
+    fn encode(&self, encoder: &mut ArcGraphEncoder);
+    /// Convert the node into a value keeping it alive
+    fn keep_alive(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: ArcGraphNode> PendingNode for Arc<T> {
+    #[inline]
+    fn address(&self) -> usize {
+        Arc::as_ptr(self) as usize
+    }
+    #[inline]
+    fn encode(&self, encoder: &mut ArcGraphEncoder) {
+        T::encode(self, encoder)
+    }
+    #[inline]
+    fn keep_alive(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// An encoder for the contents of a single node of an `ArcGraphWriter`
+pub struct ArcGraphEncoder<'w> {
+    writer: &'w mut ArcGraphWriter,
+    buf: Vec<u8>,
+    /// The children referred to which have not been written yet
+    missing: Vec<Box<dyn PendingNode>>,
+}
+
+impl ArcGraphEncoder<'_> {
+    /// Write a child node, which is written to the graph first if necessary
+    #[inline]
+    pub fn write_arc<T: ArcGraphNode>(&mut self, arc: &Arc<T>) {
+        match self.writer.id(arc) {
+            Some(id) => write_varint(&mut self.buf, id),
+            None => self.missing.push(Box::new(arc.clone())),
+        }
+    }
+    /// Write a `u8`
+    #[inline]
+    pub fn write_u8(&mut self, value: u8) {
+        self.buf.push(value)
+    }
+    /// Write a `bool`
+    #[inline]
+    pub fn write_bool(&mut self, value: bool) {
+        self.buf.push(value as u8)
+    }
+    /// Write a `u64` as a varint
+    #[inline]
+    pub fn write_u64(&mut self, value: u64) {
+        write_varint(&mut self.buf, value)
+    }
+    /// Write an `i64` as a zigzag-encoded varint
+    #[inline]
+    pub fn write_i64(&mut self, value: i64) {
+        write_varint(&mut self.buf, ((value << 1) ^ (value >> 63)) as u64)
+    }
+    /// Write a length as a varint
+    #[inline]
+    pub fn write_len(&mut self, len: usize) {
+        write_varint(&mut self.buf, len as u64)
+    }
+    /// Write a length-prefixed byte slice
+    #[inline]
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_len(bytes.len());
+        self.buf.extend_from_slice(bytes)
+    }
+    /// Write a length-prefixed string
+    #[inline]
+    pub fn write_str(&mut self, s: &str) {
+        self.write_bytes(s.as_bytes())
+    }
+}
+
+/// A reader for a DAG of `Arc`s written by an `ArcGraphWriter`
+///
+/// Nodes are decoded lazily when first requested, and every later request for the same ID returns a clone of the
+/// same `Arc`.
+pub struct ArcGraphReader<'a> {
+    /// The data of each record, indexed by ID
+    records: Vec<&'a [u8]>,
+    /// The IDs of the roots
+    roots: Vec<u64>,
+    /// The nodes decoded so far, indexed by ID
+    loaded: Vec<Option<Box<dyn Any>>>,
+    /// The number of nodes currently being decoded recursively
+    depth: usize,
+    /// The first node deferred to the explicit stack since it was last emptied, and how to decode it
+    deferred: Option<(u64, DecodeFn<'a>)>,
+}
+
+/// Decode the node with a given ID as some type, storing it in the reader
+type DecodeFn<'a> = fn(&mut ArcGraphReader<'a>, u64) -> Result<(), ArcGraphError>;
+
+/// Decode the node with a given ID as a `T`, storing it in the reader
+fn decode_node<T: ArcGraphNode>(reader: &mut ArcGraphReader, id: u64) -> Result<(), ArcGraphError> {
+    reader.read_before::<T>(id, u64::MAX).map(drop)
+}
+
+impl<'a> ArcGraphReader<'a> {
+    /// Index an encoded graph, without decoding any nodes
+    pub fn new(data: &'a [u8]) -> Result<ArcGraphReader<'a>, ArcGraphError> {
+        let mut data = data;
+        if read_slice(&mut data, MAGIC.len() as u64).map_err(|_| ArcGraphError::InvalidMagic)?
+            != MAGIC
+        {
+            return Err(ArcGraphError::InvalidMagic);
+        }
+        let len = read_varint(&mut data)?;
+        // Every record takes at least one byte, so this bounds the allocation below
+        if len > data.len() as u64 {
+            return Err(ArcGraphError::UnexpectedEof);
+        }
+        let mut records = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let record_len = read_varint(&mut data)?;
+            records.push(read_slice(&mut data, record_len)?);
+        }
+        let root_len = read_varint(&mut data)?;
+        if root_len > data.len() as u64 {
+            return Err(ArcGraphError::UnexpectedEof);
+        }
+        let mut roots = Vec::with_capacity(root_len as usize);
+        for _ in 0..root_len {
+            let root = read_varint(&mut data)?;
+            if root >= len {
+                return Err(ArcGraphError::InvalidId(root));
+            }
+            roots.push(root)
+        }
+        if !data.is_empty() {
+            return Err(ArcGraphError::TrailingData);
+        }
+        let mut loaded = Vec::with_capacity(records.len());
+        loaded.resize_with(records.len(), || None);
+        Ok(ArcGraphReader {
+            records,
+            roots,
+            loaded,
+            depth: 0,
+            deferred: None,
+        })
+    }
+    /// Get the number of nodes in the graph
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+    /// Check whether the graph has no nodes
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+    /// Get the IDs of the roots of the graph, in the order they were written
+    #[inline]
+    pub fn roots(&self) -> &[u64] {
+        &self.roots
+    }
+    /// Read the root at a given index of the root table
+    pub fn read_root<T: ArcGraphNode>(&mut self, index: usize) -> Result<Arc<T>, ArcGraphError> {
+        let id = *self
+            .roots
+            .get(index)
+            .ok_or(ArcGraphError::InvalidId(index as u64))?;
+        self.read(id)
+    }
+    /// Read the node with a given ID
+    pub fn read<T: ArcGraphNode>(&mut self, id: u64) -> Result<Arc<T>, ArcGraphError> {
+        loop {
+            match self.read_before(id, u64::MAX) {
+                Err(ArcGraphError::Deferred) => self.decode_deferred()?,
+                result => return result,
+            }
+        }
+    }
+    /// Decode the deferred node, along with every node deferred while decoding it, on an explicit stack
+    fn decode_deferred(&mut self) -> Result<(), ArcGraphError> {
+        let mut stack = Vec::new();
+        loop {
+            // `read_arc` is the only source of deferrals, and always records the node it defers
+            let deferred = self.deferred.take().ok_or(ArcGraphError::Invalid(
+                "node deferred without a deferred child",
+            ))?;
+            stack.push(deferred);
+            loop {
+                let (id, decode) = match stack.last() {
+                    Some(&top) => top,
+                    None => return Ok(()),
+                };
+                match decode(self, id) {
+                    Ok(()) => {
+                        stack.pop();
+                    }
+                    Err(ArcGraphError::Deferred) => break,
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+    }
+    /// Read the node with a given ID, which must be less than `bound`
+    fn read_before<T: ArcGraphNode>(
+        &mut self,
+        id: u64,
+        bound: u64,
+    ) -> Result<Arc<T>, ArcGraphError> {
+        if id >= self.records.len() as u64 {
+            return Err(ArcGraphError::InvalidId(id));
+        }
+        if id >= bound {
+            return Err(ArcGraphError::ForwardReference(id));
+        }
+        if let Some(loaded) = &self.loaded[id as usize] {
+            return loaded
+                .downcast_ref::<Arc<T>>()
+                .cloned()
+                .ok_or(ArcGraphError::TypeMismatch(id));
+        }
+        if self.depth == MAX_DECODE_DEPTH {
+            self.deferred.get_or_insert((id, decode_node::<T>));
+            return Err(ArcGraphError::Deferred);
+        }
+        let data = self.records[id as usize];
+        let mut decoder = ArcGraphDecoder {
+            reader: self,
+            data,
+            id,
+        };
+        decoder.reader.depth += 1;
+        let node = T::decode(&mut decoder);
+        decoder.reader.depth -= 1;
+        let rest = decoder.data;
+        // A deferred child means the node must be decoded again, even if `decode` ignored the error
+        if self.deferred.is_some() {
+            return Err(ArcGraphError::Deferred);
+        }
+        let node = node?;
+        if !rest.is_empty() {
+            return Err(ArcGraphError::TrailingBytes(id));
+        }
+        let arc = Arc::new(node);
+        self.loaded[id as usize] = Some(Box::new(arc.clone()));
+        Ok(arc)
+    }
+}
+
+impl fmt::Debug for ArcGraphReader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArcGraphReader")
+            .field("nodes", &self.len())
+            .field("roots", &self.roots)
+            .finish()
+    }
+}
+
+/// A decoder for the contents of a single node of an `ArcGraphReader`
+pub struct ArcGraphDecoder<'r, 'a> {
+    reader: &'r mut ArcGraphReader<'a>,
+    data: &'a [u8],
+    id: u64,
+}
+
+impl<'a> ArcGraphDecoder<'_, 'a> {
+    /// Get the ID of the node being decoded
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    /// Read a child node
+    #[inline]
+    pub fn read_arc<T: ArcGraphNode>(&mut self) -> Result<Arc<T>, ArcGraphError> {
+        let id = read_varint(&mut self.data)?;
+        self.reader.read_before(id, self.id)
+    }
+    /// Read a `u8`
+    #[inline]
+    pub fn read_u8(&mut self) -> Result<u8, ArcGraphError> {
+        Ok(read_slice(&mut self.data, 1)?[0])
+    }
+    /// Read a `bool`
+    #[inline]
+    pub fn read_bool(&mut self) -> Result<bool, ArcGraphError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ArcGraphError::Invalid("invalid bool")),
+        }
+    }
+    /// Read a varint `u64`
+    #[inline]
+    pub fn read_u64(&mut self) -> Result<u64, ArcGraphError> {
+        read_varint(&mut self.data)
+    }
+    /// Read a zigzag-encoded varint `i64`
+    #[inline]
+    pub fn read_i64(&mut self) -> Result<i64, ArcGraphError> {
+        let value = read_varint(&mut self.data)?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+    /// Read a length
+    ///
+    /// Since elements may take no bytes at all, the length is not checked against the remaining data, so it should
+    /// not be trusted when preallocating: see [`capacity_for`](ArcGraphDecoder::capacity_for).
+    #[inline]
+    pub fn read_len(&mut self) -> Result<usize, ArcGraphError> {
+        let len = read_varint(&mut self.data)?;
+        usize::try_from(len).map_err(|_| ArcGraphError::Invalid("length overflows a usize"))
+    }
+    /// Get a capacity to preallocate for `len` elements read from the rest of this node, which is capped by the
+    /// size of the remaining data so that a corrupt length cannot exhaust memory
+    #[inline]
+    pub fn capacity_for(&self, len: usize) -> usize {
+        len.min(self.data.len())
+    }
+    /// Read a length-prefixed byte slice, borrowed from the underlying data
+    #[inline]
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], ArcGraphError> {
+        let len = read_varint(&mut self.data)?;
+        read_slice(&mut self.data, len)
+    }
+    /// Read a length-prefixed string, borrowed from the underlying data
+    #[inline]
+    pub fn read_str(&mut self) -> Result<&'a str, ArcGraphError> {
+        core::str::from_utf8(self.read_bytes()?).map_err(|_| ArcGraphError::InvalidUtf8)
+    }
+}
@@ -0,0 +1,633 @@
+use crate::*;
+use core::cell::UnsafeCell;
+
+/// The number of the next `ArcIntrusiveList` to be created, as `0` marks an unlinked `ArcLink`
+static NEXT_LIST: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(1);
+
+/// The link fields which let a value stored in an `Arc` be a member of an `ArcIntrusiveList`
+///
+/// A type embeds one `ArcLink` per list it should be able to be in at once, and names each of them with an
+/// [`ArcAdapter`]. A link records which list it is in, so an `Arc` is only ever linked into one list through a
+/// given link, and only that list reads or writes its pointers. Cloning a link yields a new, unlinked one, so that
+/// `Arc::make_mut` and friends never produce a copy which claims to be in a list.
+pub struct ArcLink {
+    /// The list this link is in, or `0`
+    owner: atomic::AtomicUsize,
+    prev: UnsafeCell<*const ()>,
+    next: UnsafeCell<*const ()>,
+}
+
+// Only the list named by `owner` touches `prev` and `next`, and only through `&mut` or `&` access to itself.
+unsafe impl Send for ArcLink {}
+unsafe impl Sync for ArcLink {}
+
+impl ArcLink {
+    /// Create a new, unlinked `ArcLink`
+    #[inline]
+    pub fn new() -> ArcLink {
+        ArcLink {
+            owner: atomic::AtomicUsize::new(0),
+            prev: UnsafeCell::new(ptr::null()),
+            next: UnsafeCell::new(ptr::null()),
+        }
+    }
+    /// Whether this link is currently in a list
+    #[inline]
+    pub fn is_linked(&self) -> bool {
+        self.owner.load(Relaxed) != 0
+    }
+}
+
+impl Default for ArcLink {
+    #[inline]
+    fn default() -> ArcLink {
+        ArcLink::new()
+    }
+}
+
+impl Clone for ArcLink {
+    #[inline]
+    fn clone(&self) -> ArcLink {
+        ArcLink::new()
+    }
+}
+
+impl fmt::Debug for ArcLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArcLink")
+            .field("linked", &self.is_linked())
+            .finish()
+    }
+}
+
+/// Selects the `ArcLink` within a `T` through which an `ArcIntrusiveList<T, Self>` links its members
+///
+/// Adapters are usually zero-sized marker types, one per link field:
+/// ```rust
+/// # use elysees::{ArcAdapter, ArcLink};
+/// struct Task {
+///     id: u32,
+///     queued: ArcLink,
+/// }
+///
+/// struct Queued;
+///
+/// unsafe impl ArcAdapter<Task> for Queued {
+///     fn link(value: &Task) -> &ArcLink {
+///         &value.queued
+///     }
+/// }
+/// ```
+///
+/// # Safety
+/// `link` must not panic, and must return the same `ArcLink` every time it is called on the same value. That link
+/// must be stored within `value` itself, such as in one of its fields, so that it lives exactly as long as `value`
+/// and belongs to no other value: returning a link from a `static`, or one shared between values through a
+/// reference, lets the list overwrite the link of a value it does not hold. No other adapter for `T` may return the
+/// same link.
+pub unsafe trait ArcAdapter<T> {
+    /// Get the link within `value`
+    fn link(value: &T) -> &ArcLink;
+}
+
+/// A doubly linked list whose nodes are the `Arc`s it contains
+///
+/// Instead of allocating nodes of its own, the list threads its pointers through an [`ArcLink`] embedded in each
+/// member and selected by the [`ArcAdapter`] `A`, so a value with several links can be in several lists at once
+/// while sharing a single allocation. The list holds a strong reference to each member, which it takes over on
+/// insertion and hands back on removal. As links know which list they are in, [`remove`](ArcIntrusiveList::remove)
+/// and [`cursor_mut_at`](ArcIntrusiveList::cursor_mut_at) find a member in `O(1)` from any reference to it.
+///
+/// ```rust
+/// # use elysees::{Arc, ArcAdapter, ArcIntrusiveList, ArcLink};
+/// #[derive(Debug, Default)]
+/// struct Page {
+///     number: u32,
+///     lru: ArcLink,
+///     dirty: ArcLink,
+/// }
+///
+/// struct Lru;
+/// unsafe impl ArcAdapter<Page> for Lru {
+///     fn link(page: &Page) -> &ArcLink {
+///         &page.lru
+///     }
+/// }
+///
+/// struct Dirty;
+/// unsafe impl ArcAdapter<Page> for Dirty {
+///     fn link(page: &Page) -> &ArcLink {
+///         &page.dirty
+///     }
+/// }
+///
+/// let mut lru = ArcIntrusiveList::<Page, Lru>::new();
+/// let mut dirty = ArcIntrusiveList::<Page, Dirty>::new();
+/// for number in 0..3 {
+///     let page = Arc::new(Page { number, ..Page::default() });
+///     if number != 1 {
+///         dirty.push(page.clone()).unwrap();
+///     }
+///     lru.push(page).unwrap();
+/// }
+///
+/// // Each link can only be in one list at a time
+/// let first = lru.front().unwrap().clone_arc();
+/// assert!(lru.push(first.clone()).is_err());
+///
+/// // Touching a page moves it to the back of the LRU list, wherever it is
+/// let page = lru.remove(first.borrow_arc()).unwrap();
+/// lru.push(page).unwrap();
+/// assert_eq!(lru.iter().map(|page| page.number).collect::<Vec<_>>(), [1, 2, 0]);
+/// assert_eq!(dirty.iter().map(|page| page.number).collect::<Vec<_>>(), [0, 2]);
+/// ```
+pub struct ArcIntrusiveList<T, A: ArcAdapter<T>> {
+    head: *const T,
+    tail: *const T,
+    len: usize,
+    id: usize,
+    phantom: PhantomData<Arc<T>>,
+    adapter: PhantomData<fn() -> A>,
+}
+
+unsafe impl<T: Send + Sync, A: ArcAdapter<T>> Send for ArcIntrusiveList<T, A> {}
+unsafe impl<T: Send + Sync, A: ArcAdapter<T>> Sync for ArcIntrusiveList<T, A> {}
+
+impl<T, A: ArcAdapter<T>> ArcIntrusiveList<T, A> {
+    /// Create a new, empty list
+    #[inline]
+    pub fn new() -> ArcIntrusiveList<T, A> {
+        let id = NEXT_LIST.fetch_add(1, Relaxed);
+        if id == 0 {
+            // The counter wrapped around, so list numbers are no longer unique
+            abort()
+        }
+        ArcIntrusiveList {
+            head: ptr::null(),
+            tail: ptr::null(),
+            len: 0,
+            id,
+            phantom: PhantomData,
+            adapter: PhantomData,
+        }
+    }
+    /// Get the number of members of this list
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Check whether this list is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Check whether `value` is a member of this list
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool {
+        A::link(value).owner.load(Relaxed) == self.id
+    }
+    /// Borrow the first member of this list
+    #[inline]
+    pub fn front(&self) -> Option<ArcBorrow<'_, T>> {
+        unsafe { Self::borrow(self.head) }
+    }
+    /// Borrow the last member of this list
+    #[inline]
+    pub fn back(&self) -> Option<ArcBorrow<'_, T>> {
+        unsafe { Self::borrow(self.tail) }
+    }
+    /// Append `value` to the back of this list, or give it back if its link is already in a list
+    #[inline]
+    pub fn push(&mut self, value: Arc<T>) -> Result<(), Arc<T>> {
+        let node = self.claim(value)?;
+        unsafe { self.link_between(node, self.tail, ptr::null()) };
+        Ok(())
+    }
+    /// Prepend `value` to the front of this list, or give it back if its link is already in a list
+    #[inline]
+    pub fn push_front(&mut self, value: Arc<T>) -> Result<(), Arc<T>> {
+        let node = self.claim(value)?;
+        unsafe { self.link_between(node, ptr::null(), self.head) };
+        Ok(())
+    }
+    /// Remove the first member of this list
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<Arc<T>> {
+        if self.head.is_null() {
+            None
+        } else {
+            Some(unsafe { self.unlink(self.head) })
+        }
+    }
+    /// Remove the last member of this list
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<Arc<T>> {
+        if self.tail.is_null() {
+            None
+        } else {
+            Some(unsafe { self.unlink(self.tail) })
+        }
+    }
+    /// Remove `value` from this list, returning the list's reference to it, or `None` if it is not a member
+    #[inline]
+    pub fn remove(&mut self, value: ArcBorrow<'_, T>) -> Option<Arc<T>> {
+        if self.contains(&value) {
+            Some(unsafe { self.unlink(ArcBorrow::into_raw(value)) })
+        } else {
+            None
+        }
+    }
+    /// Remove every member of this list
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+    /// Iterate over borrows of the members of this list, from front to back
+    #[inline]
+    pub fn iter(&self) -> ArcIntrusiveIter<'_, T, A> {
+        ArcIntrusiveIter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            phantom: PhantomData,
+        }
+    }
+    /// Get a cursor pointing at the first member of this list, or at the "ghost" position if it is empty
+    #[inline]
+    pub fn cursor_front(&self) -> ArcIntrusiveCursor<'_, T, A> {
+        ArcIntrusiveCursor {
+            list: self,
+            current: self.head,
+        }
+    }
+    /// Get a cursor pointing at the last member of this list, or at the "ghost" position if it is empty
+    #[inline]
+    pub fn cursor_back(&self) -> ArcIntrusiveCursor<'_, T, A> {
+        ArcIntrusiveCursor {
+            list: self,
+            current: self.tail,
+        }
+    }
+    /// Get a cursor which can edit this list, pointing at its first member
+    #[inline]
+    pub fn cursor_front_mut(&mut self) -> ArcIntrusiveCursorMut<'_, T, A> {
+        ArcIntrusiveCursorMut {
+            current: self.head,
+            list: self,
+        }
+    }
+    /// Get a cursor which can edit this list, pointing at its last member
+    #[inline]
+    pub fn cursor_back_mut(&mut self) -> ArcIntrusiveCursorMut<'_, T, A> {
+        ArcIntrusiveCursorMut {
+            current: self.tail,
+            list: self,
+        }
+    }
+    /// Get a cursor which can edit this list, pointing at `value`, or `None` if `value` is not a member
+    #[inline]
+    pub fn cursor_mut_at(
+        &mut self,
+        value: ArcBorrow<'_, T>,
+    ) -> Option<ArcIntrusiveCursorMut<'_, T, A>> {
+        if self.contains(&value) {
+            Some(ArcIntrusiveCursorMut {
+                current: ArcBorrow::into_raw(value),
+                list: self,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Borrow a member, or the "ghost" position as `None`
+    #[inline]
+    unsafe fn borrow<'a>(node: *const T) -> Option<ArcBorrow<'a, T>> {
+        if node.is_null() {
+            None
+        } else {
+            Some(ArcBorrow::from_raw(node))
+        }
+    }
+    #[inline]
+    unsafe fn link<'a>(node: *const T) -> &'a ArcLink
+    where
+        T: 'a,
+    {
+        A::link(&*node)
+    }
+    #[inline]
+    unsafe fn next(node: *const T) -> *const T {
+        *Self::link(node).next.get() as *const T
+    }
+    #[inline]
+    unsafe fn prev(node: *const T) -> *const T {
+        *Self::link(node).prev.get() as *const T
+    }
+    #[inline]
+    unsafe fn set_next(node: *const T, next: *const T) {
+        *Self::link(node).next.get() = next as *const ()
+    }
+    #[inline]
+    unsafe fn set_prev(node: *const T, prev: *const T) {
+        *Self::link(node).prev.get() = prev as *const ()
+    }
+    /// Mark the link of `value` as belonging to this list, and take over its reference
+    #[inline]
+    fn claim(&self, value: Arc<T>) -> Result<*const T, Arc<T>> {
+        // Acquire the pointer writes of the last list this link was in
+        match A::link(&value)
+            .owner
+            .compare_exchange(0, self.id, Acquire, Relaxed)
+        {
+            Ok(_) => Ok(Arc::into_raw(value)),
+            Err(_) => Err(value),
+        }
+    }
+    /// Link a claimed `node` in between `prev` and `next`, where a null pointer stands for the end of the list
+    #[inline]
+    unsafe fn link_between(&mut self, node: *const T, prev: *const T, next: *const T) {
+        Self::set_prev(node, prev);
+        Self::set_next(node, next);
+        if prev.is_null() {
+            self.head = node
+        } else {
+            Self::set_next(prev, node)
+        }
+        if next.is_null() {
+            self.tail = node
+        } else {
+            Self::set_prev(next, node)
+        }
+        self.len += 1;
+    }
+    /// Unlink a member of this list, releasing its link and returning the list's reference to it
+    #[inline]
+    unsafe fn unlink(&mut self, node: *const T) -> Arc<T> {
+        let (prev, next) = (Self::prev(node), Self::next(node));
+        if prev.is_null() {
+            self.head = next
+        } else {
+            Self::set_next(prev, next)
+        }
+        if next.is_null() {
+            self.tail = prev
+        } else {
+            Self::set_prev(next, prev)
+        }
+        self.len -= 1;
+        Self::link(node).owner.store(0, Release);
+        Arc::from_raw(node)
+    }
+}
+
+impl<T, A: ArcAdapter<T>> Drop for ArcIntrusiveList<T, A> {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
+
+impl<T, A: ArcAdapter<T>> Default for ArcIntrusiveList<T, A> {
+    #[inline]
+    fn default() -> ArcIntrusiveList<T, A> {
+        ArcIntrusiveList::new()
+    }
+}
+
+impl<T: fmt::Debug, A: ArcAdapter<T>> fmt::Debug for ArcIntrusiveList<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|member| member.get()))
+            .finish()
+    }
+}
+
+impl<'a, T, A: ArcAdapter<T>> IntoIterator for &'a ArcIntrusiveList<T, A> {
+    type Item = ArcBorrow<'a, T>;
+    type IntoIter = ArcIntrusiveIter<'a, T, A>;
+
+    #[inline]
+    fn into_iter(self) -> ArcIntrusiveIter<'a, T, A> {
+        self.iter()
+    }
+}
+
+/// An iterator over borrows of the members of an `ArcIntrusiveList`
+pub struct ArcIntrusiveIter<'a, T, A: ArcAdapter<T>> {
+    head: *const T,
+    tail: *const T,
+    len: usize,
+    phantom: PhantomData<&'a ArcIntrusiveList<T, A>>,
+}
+
+unsafe impl<'a, T: Send + Sync, A: ArcAdapter<T>> Send for ArcIntrusiveIter<'a, T, A> {}
+unsafe impl<'a, T: Send + Sync, A: ArcAdapter<T>> Sync for ArcIntrusiveIter<'a, T, A> {}
+
+impl<'a, T, A: ArcAdapter<T>> Clone for ArcIntrusiveIter<'a, T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArcIntrusiveIter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, A: ArcAdapter<T>> Iterator for ArcIntrusiveIter<'a, T, A> {
+    type Item = ArcBorrow<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<ArcBorrow<'a, T>> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let node = self.head;
+        unsafe {
+            self.head = ArcIntrusiveList::<T, A>::next(node);
+            Some(ArcBorrow::from_raw(node))
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, A: ArcAdapter<T>> DoubleEndedIterator for ArcIntrusiveIter<'a, T, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<ArcBorrow<'a, T>> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let node = self.tail;
+        unsafe {
+            self.tail = ArcIntrusiveList::<T, A>::prev(node);
+            Some(ArcBorrow::from_raw(node))
+        }
+    }
+}
+
+impl<'a, T, A: ArcAdapter<T>> ExactSizeIterator for ArcIntrusiveIter<'a, T, A> {}
+
+impl<'a, T, A: ArcAdapter<T>> FusedIterator for ArcIntrusiveIter<'a, T, A> {}
+
+/// A cursor over an `ArcIntrusiveList`
+///
+/// A cursor points either at a member of the list or at a "ghost" position between its back and its front, and can
+/// move in both directions, wrapping around through the ghost.
+pub struct ArcIntrusiveCursor<'a, T, A: ArcAdapter<T>> {
+    list: &'a ArcIntrusiveList<T, A>,
+    current: *const T,
+}
+
+unsafe impl<'a, T: Send + Sync, A: ArcAdapter<T>> Send for ArcIntrusiveCursor<'a, T, A> {}
+unsafe impl<'a, T: Send + Sync, A: ArcAdapter<T>> Sync for ArcIntrusiveCursor<'a, T, A> {}
+
+impl<'a, T, A: ArcAdapter<T>> Clone for ArcIntrusiveCursor<'a, T, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArcIntrusiveCursor {
+            list: self.list,
+            current: self.current,
+        }
+    }
+}
+
+impl<'a, T, A: ArcAdapter<T>> ArcIntrusiveCursor<'a, T, A> {
+    /// Borrow the member this cursor points at, or `None` at the ghost position
+    #[inline]
+    pub fn current(&self) -> Option<ArcBorrow<'a, T>> {
+        unsafe { ArcIntrusiveList::<T, A>::borrow(self.current) }
+    }
+    /// Borrow the member after the one this cursor points at, which is the front of the list at the ghost position
+    #[inline]
+    pub fn peek_next(&self) -> Option<ArcBorrow<'a, T>> {
+        unsafe { ArcIntrusiveList::<T, A>::borrow(next_of(self.list, self.current)) }
+    }
+    /// Borrow the member before the one this cursor points at, which is the back of the list at the ghost position
+    #[inline]
+    pub fn peek_prev(&self) -> Option<ArcBorrow<'a, T>> {
+        unsafe { ArcIntrusiveList::<T, A>::borrow(prev_of(self.list, self.current)) }
+    }
+    /// Move to the next member, or from the back of the list to the ghost position
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.current = unsafe { next_of(self.list, self.current) }
+    }
+    /// Move to the previous member, or from the front of the list to the ghost position
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.current = unsafe { prev_of(self.list, self.current) }
+    }
+}
+
+/// A cursor over an `ArcIntrusiveList` which can insert and remove members
+///
+/// Like an [`ArcIntrusiveCursor`], this points either at a member or at the ghost position.
+pub struct ArcIntrusiveCursorMut<'a, T, A: ArcAdapter<T>> {
+    list: &'a mut ArcIntrusiveList<T, A>,
+    current: *const T,
+}
+
+unsafe impl<'a, T: Send + Sync, A: ArcAdapter<T>> Send for ArcIntrusiveCursorMut<'a, T, A> {}
+unsafe impl<'a, T: Send + Sync, A: ArcAdapter<T>> Sync for ArcIntrusiveCursorMut<'a, T, A> {}
+
+impl<'a, T, A: ArcAdapter<T>> ArcIntrusiveCursorMut<'a, T, A> {
+    /// Borrow the member this cursor points at, or `None` at the ghost position
+    #[inline]
+    pub fn current(&self) -> Option<ArcBorrow<'_, T>> {
+        self.as_cursor().current()
+    }
+    /// Borrow the member after the one this cursor points at, which is the front of the list at the ghost position
+    #[inline]
+    pub fn peek_next(&self) -> Option<ArcBorrow<'_, T>> {
+        self.as_cursor().peek_next()
+    }
+    /// Borrow the member before the one this cursor points at, which is the back of the list at the ghost position
+    #[inline]
+    pub fn peek_prev(&self) -> Option<ArcBorrow<'_, T>> {
+        self.as_cursor().peek_prev()
+    }
+    /// Move to the next member, or from the back of the list to the ghost position
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.current = unsafe { next_of(self.list, self.current) }
+    }
+    /// Move to the previous member, or from the front of the list to the ghost position
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.current = unsafe { prev_of(self.list, self.current) }
+    }
+    /// Get a read-only cursor pointing at the same position
+    #[inline]
+    pub fn as_cursor(&self) -> ArcIntrusiveCursor<'_, T, A> {
+        ArcIntrusiveCursor {
+            list: self.list,
+            current: self.current,
+        }
+    }
+    /// Remove the member this cursor points at and move to the next one, or do nothing at the ghost position
+    #[inline]
+    pub fn remove_current(&mut self) -> Option<Arc<T>> {
+        if self.current.is_null() {
+            return None;
+        }
+        let node = self.current;
+        unsafe {
+            self.current = ArcIntrusiveList::<T, A>::next(node);
+            Some(self.list.unlink(node))
+        }
+    }
+    /// Insert `value` after the member this cursor points at, or at the front of the list at the ghost position.
+    /// Gives `value` back if its link is already in a list.
+    #[inline]
+    pub fn insert_after(&mut self, value: Arc<T>) -> Result<(), Arc<T>> {
+        let node = self.list.claim(value)?;
+        unsafe {
+            let next = next_of(self.list, self.current);
+            self.list.link_between(node, self.current, next);
+        }
+        Ok(())
+    }
+    /// Insert `value` before the member this cursor points at, or at the back of the list at the ghost position.
+    /// Gives `value` back if its link is already in a list.
+    #[inline]
+    pub fn insert_before(&mut self, value: Arc<T>) -> Result<(), Arc<T>> {
+        let node = self.list.claim(value)?;
+        unsafe {
+            let prev = prev_of(self.list, self.current);
+            self.list.link_between(node, prev, self.current);
+        }
+        Ok(())
+    }
+}
+
+/// Get the position after `current` in `list`, where null stands for the ghost position
+#[inline]
+unsafe fn next_of<T, A: ArcAdapter<T>>(
+    list: &ArcIntrusiveList<T, A>,
+    current: *const T,
+) -> *const T {
+    if current.is_null() {
+        list.head
+    } else {
+        ArcIntrusiveList::<T, A>::next(current)
+    }
+}
+
+/// Get the position before `current` in `list`, where null stands for the ghost position
+#[inline]
+unsafe fn prev_of<T, A: ArcAdapter<T>>(
+    list: &ArcIntrusiveList<T, A>,
+    current: *const T,
+) -> *const T {
+    if current.is_null() {
+        list.tail
+    } else {
+        ArcIntrusiveList::<T, A>::prev(current)
+    }
+}
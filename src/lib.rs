@@ -24,24 +24,31 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate core;
 
+#[cfg(feature = "rkyv")]
+extern crate rkyv;
 #[cfg(feature = "serde")]
 extern crate serde;
 #[cfg(feature = "stable_deref_trait")]
 extern crate stable_deref_trait;
 
-use alloc::alloc::{alloc, dealloc, Layout};
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::borrow::{Borrow, BorrowMut};
 use core::cmp::Ordering;
 use core::convert::From;
 use core::fmt;
 use core::hash::{Hash, Hasher};
+use core::iter::{FromIterator, FusedIterator};
 use core::marker::PhantomData;
 use core::mem;
 use core::ops::{Deref, DerefMut};
 use core::ptr;
+#[cfg(not(loom))]
 use core::sync::atomic;
 use core::sync::atomic::Ordering::{self as LoadOrdering, Acquire, Relaxed, Release};
-use core::{isize, usize};
+#[cfg(loom)]
+use loom::sync::atomic;
 
 #[cfg(feature = "erasable")]
 use erasable::{Erasable, ErasablePtr, ErasedPtr};
@@ -51,16 +58,68 @@ use serde::{Deserialize, Serialize};
 use stable_deref_trait::{CloneStableDeref, StableDeref};
 
 mod arc;
+mod arc32;
+#[cfg(all(feature = "rkyv", feature = "slice-dst"))]
+mod archive;
 mod borrow;
+#[cfg(feature = "std")]
+mod cache;
+mod clone;
+mod graph;
+mod intrusive;
+mod list;
+mod lockfree;
+#[cfg(all(feature = "std", feature = "slice-dst"))]
+mod map;
+mod nullable;
+mod overflow;
+mod pointer;
+mod pvec;
+mod radix;
+mod rope;
+#[cfg(all(feature = "erasable", feature = "slice-dst"))]
+mod slice;
+mod slice_ref;
 #[cfg(feature = "ptr-union")]
 mod union;
 mod unique;
+mod vec;
+
+#[cfg(feature = "debug-tracking")]
+pub mod debug;
+#[cfg(feature = "serde_shared")]
+pub mod serde_shared;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategy;
 
 pub use arc::*;
+pub use arc32::*;
+#[cfg(all(feature = "rkyv", feature = "slice-dst"))]
+pub use archive::*;
 pub use borrow::*;
+#[cfg(feature = "std")]
+pub use cache::*;
+pub use clone::*;
+pub use graph::*;
+pub use intrusive::*;
+pub use list::*;
+pub use lockfree::*;
+#[cfg(all(feature = "std", feature = "slice-dst"))]
+pub use map::*;
+pub use nullable::*;
+pub use overflow::*;
+pub use pointer::*;
+pub use pvec::*;
+pub use rope::*;
+#[cfg(all(feature = "erasable", feature = "slice-dst"))]
+pub use slice::*;
+pub use slice_ref::*;
 #[cfg(feature = "ptr-union")]
 pub use union::*;
 pub use unique::*;
+pub use vec::*;
 
 #[cfg(feature = "std")]
 use std::process::abort;
@@ -0,0 +1,360 @@
+use crate::*;
+
+/// A node of an `ArcList`
+#[derive(Clone)]
+struct ListNode<T> {
+    value: T,
+    next: Option<Arc<ListNode<T>>>,
+}
+
+impl<T> Drop for ListNode<T> {
+    fn drop(&mut self) {
+        // Unlink uniquely owned successors one at a time, so that dropping a long list does not recurse
+        let mut next = self.next.take();
+        while let Some(node) = next {
+            match Arc::try_unique(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T> ListNode<T> {
+    /// Move the value out of a uniquely owned node, returning it along with the rest of the list
+    #[inline]
+    fn into_parts(node: ArcBox<ListNode<T>>) -> (T, Option<Arc<ListNode<T>>>) {
+        let node = mem::ManuallyDrop::new(ArcBox::into_inner(node));
+        // `ListNode` implements `Drop`, so we move its fields out by hand; `node` is never used again
+        unsafe { (ptr::read(&node.value), ptr::read(&node.next)) }
+    }
+}
+
+/// A persistent, singly linked list built out of `Arc`s
+///
+/// Lists share structure: [`cons`](ArcList::cons) and [`tail`](ArcList::tail) are `O(1)` and never copy
+/// elements, and cloning a list just clones the `Arc` pointing to its first node. Mutating methods such as
+/// [`head_mut`](ArcList::head_mut) and [`iter_mut`](ArcList::iter_mut) work in place on nodes which are not shared,
+/// and copy only the shared nodes they touch otherwise.
+///
+/// ```rust
+/// # use elysees::ArcList;
+/// let tail: ArcList<i32> = vec![2, 3].into_iter().collect();
+/// let mut list = tail.cons(1);
+/// assert_eq!(list.len(), 3);
+/// assert_eq!(list.head(), Some(&1));
+/// assert_eq!(list.tail(), Some(tail.clone()));
+///
+/// // Mutating a shared node copies it, leaving `tail` untouched
+/// for x in list.iter_mut() {
+///     *x *= 10;
+/// }
+/// assert_eq!(list.iter().copied().collect::<Vec<_>>(), [10, 20, 30]);
+/// assert_eq!(tail.iter().copied().collect::<Vec<_>>(), [2, 3]);
+/// ```
+pub struct ArcList<T> {
+    head: Option<Arc<ListNode<T>>>,
+    len: usize,
+}
+
+impl<T> ArcList<T> {
+    /// Create a new, empty list
+    #[inline]
+    pub const fn new() -> ArcList<T> {
+        ArcList { head: None, len: 0 }
+    }
+    /// Get the number of elements in this list
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether this list is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+    /// Create a new list with `value` in front of the elements of this one, which are shared
+    #[inline]
+    pub fn cons(&self, value: T) -> ArcList<T> {
+        ArcList {
+            head: Some(Arc::new(ListNode {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+    /// Add `value` to the front of this list
+    #[inline]
+    pub fn push_front(&mut self, value: T) {
+        let next = self.head.take();
+        self.head = Some(Arc::new(ListNode { value, next }));
+        self.len += 1;
+    }
+    /// Get the first element of this list, if any
+    #[inline]
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+    /// Get the list of all elements but the first, or `None` if this list is empty
+    #[inline]
+    pub fn tail(&self) -> Option<ArcList<T>> {
+        self.head.as_ref().map(|node| ArcList {
+            head: node.next.clone(),
+            len: self.len - 1,
+        })
+    }
+    /// Iterate over the elements of this list
+    #[inline]
+    pub fn iter(&self) -> ArcListIter<'_, T> {
+        ArcListIter {
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+    /// Whether two lists share the same first node, and hence are equal
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        match (&this.head, &other.head) {
+            (Some(this), Some(other)) => Arc::ptr_eq(this, other),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Clone> ArcList<T> {
+    /// Remove and return the first element of this list, if any.
+    ///
+    /// The element is moved out if the first node is not shared, and cloned otherwise.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+        self.len -= 1;
+        match Arc::try_unique(node) {
+            Ok(node) => {
+                let (value, next) = ListNode::into_parts(node);
+                self.head = next;
+                Some(value)
+            }
+            Err(node) => {
+                self.head = node.next.clone();
+                Some(node.value.clone())
+            }
+        }
+    }
+    /// Get a mutable reference to the first element of this list, if any, copying the first node if it is shared
+    #[inline]
+    pub fn head_mut(&mut self) -> Option<&mut T> {
+        self.head
+            .as_mut()
+            .map(|node| &mut Arc::make_mut(node).value)
+    }
+    /// Iterate mutably over the elements of this list, copying each shared node as it is reached.
+    ///
+    /// Copying a shared node shares its successor with the original, so once one shared node is reached every later
+    /// node is copied, but only as far as the iterator is advanced.
+    #[inline]
+    pub fn iter_mut(&mut self) -> ArcListIterMut<'_, T> {
+        ArcListIterMut {
+            next: self.head.as_mut(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Clone for ArcList<T> {
+    #[inline]
+    fn clone(&self) -> ArcList<T> {
+        ArcList {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for ArcList<T> {
+    #[inline]
+    fn default() -> ArcList<T> {
+        ArcList::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcList<T> {
+    fn eq(&self, other: &ArcList<T>) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+        let mut left = self.head.as_ref();
+        let mut right = other.head.as_ref();
+        while let (Some(l), Some(r)) = (left, right) {
+            // Shared suffixes are equal
+            if Arc::ptr_eq(l, r) {
+                return true;
+            }
+            if l.value != r.value {
+                return false;
+            }
+            left = l.next.as_ref();
+            right = r.next.as_ref();
+        }
+        true
+    }
+}
+
+impl<T: Eq> Eq for ArcList<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArcList<T> {
+    fn partial_cmp(&self, other: &ArcList<T>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for ArcList<T> {
+    fn cmp(&self, other: &ArcList<T>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for ArcList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for value in self {
+            value.hash(state)
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T> FromIterator<T> for ArcList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        let mut list = ArcList::new();
+        for value in values.into_iter().rev() {
+            list.push_front(value)
+        }
+        list
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArcList<T> {
+    type Item = &'a T;
+    type IntoIter = ArcListIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> ArcListIter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a mut ArcList<T> {
+    type Item = &'a mut T;
+    type IntoIter = ArcListIterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> ArcListIterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: Clone> IntoIterator for ArcList<T> {
+    type Item = T;
+    type IntoIter = ArcListIntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> ArcListIntoIter<T> {
+        ArcListIntoIter(self)
+    }
+}
+
+/// An iterator over the elements of an `ArcList`
+pub struct ArcListIter<'a, T> {
+    next: Option<&'a ListNode<T>>,
+    len: usize,
+}
+
+impl<'a, T> Clone for ArcListIter<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArcListIter {
+            next: self.next,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for ArcListIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ArcListIter<'a, T> {}
+
+impl<'a, T> FusedIterator for ArcListIter<'a, T> {}
+
+/// A mutable iterator over the elements of an `ArcList`, which copies shared nodes as they are reached
+pub struct ArcListIterMut<'a, T> {
+    next: Option<&'a mut Arc<ListNode<T>>>,
+    len: usize,
+}
+
+impl<'a, T: Clone> Iterator for ArcListIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut T> {
+        let node = Arc::make_mut(self.next.take()?);
+        self.next = node.next.as_mut();
+        self.len -= 1;
+        Some(&mut node.value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T: Clone> ExactSizeIterator for ArcListIterMut<'a, T> {}
+
+impl<'a, T: Clone> FusedIterator for ArcListIterMut<'a, T> {}
+
+/// An owning iterator over the elements of an `ArcList`, which moves elements out of unshared nodes and clones
+/// them out of shared ones
+#[derive(Clone)]
+pub struct ArcListIntoIter<T>(ArcList<T>);
+
+impl<T: Clone> Iterator for ArcListIntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for ArcListIntoIter<T> {}
+
+impl<T: Clone> FusedIterator for ArcListIntoIter<T> {}
@@ -0,0 +1,430 @@
+use crate::*;
+use alloc::boxed::Box;
+use core::sync::atomic::Ordering::AcqRel;
+
+/// The intrusive link at the start of every node in an `ArcStack` or `ArcQueue`
+#[repr(C)]
+struct Link {
+    next: atomic::AtomicPtr<Link>,
+}
+
+impl Link {
+    #[inline]
+    fn new() -> Link {
+        Link {
+            next: atomic::AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+/// Back off while waiting for another thread to make progress
+#[inline]
+fn spin() {
+    #[cfg(loom)]
+    loom::thread::yield_now();
+    #[cfg(not(loom))]
+    core::hint::spin_loop();
+}
+
+/// A value which can be linked into an `ArcStack` or `ArcQueue`
+///
+/// The link lives in the same `Arc` allocation as the value, so pushing and popping an `ArcNode` never allocates:
+/// a node is created with [`ArcNode::new`], moved into a container, and handed back out by a pop as the same
+/// uniquely owned `ArcBox`, which can be turned into a shared `Arc<ArcNode<T>>` or back into its value.
+#[repr(C)]
+pub struct ArcNode<T> {
+    link: Link,
+    value: T,
+}
+
+impl<T> ArcNode<T> {
+    /// Allocate a new node holding `value`
+    #[inline]
+    pub fn new(value: T) -> ArcBox<ArcNode<T>> {
+        ArcBox::new(ArcNode {
+            link: Link::new(),
+            value,
+        })
+    }
+    /// Take the value out of a node, freeing it
+    #[inline]
+    pub fn into_inner(this: ArcBox<ArcNode<T>>) -> T {
+        ArcBox::into_inner(this).value
+    }
+    /// Move ownership of a node into a raw pointer to its link
+    #[inline]
+    fn into_link(this: ArcBox<ArcNode<T>>) -> *mut Link {
+        let node = Arc::into_raw(this.shareable()) as *mut ArcNode<T>;
+        // The link is the first field of the node
+        node as *mut Link
+    }
+    /// Take back ownership of a node from a raw pointer to its link, as returned by `into_link`
+    #[inline]
+    unsafe fn from_link(link: *mut Link) -> ArcBox<ArcNode<T>> {
+        // Nodes are only ever linked while uniquely owned, and the container gave up its reference
+        ArcBox(Arc::from_raw(link as *const ArcNode<T>))
+    }
+}
+
+impl<T> Deref for ArcNode<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for ArcNode<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcNode<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ArcNode").field(&self.value).finish()
+    }
+}
+
+/// A hazard pointer, through which a thread popping from an `ArcStack` announces which node it is reading
+struct Hazard {
+    /// The node being read, or null
+    node: atomic::AtomicPtr<Link>,
+    /// Whether a thread is using this hazard pointer
+    active: atomic::AtomicBool,
+    /// The next hazard pointer in the stack's registry, which never changes once published
+    next: *mut Hazard,
+}
+
+/// A concurrent stack of `ArcNode`s, with lock-free pushes
+///
+/// This is a Treiber stack: nodes form a singly linked list through their intrusive links, with ownership of each
+/// node's `Arc` moving in and out of the stack's head pointer by `Arc::into_raw` and `Arc::from_raw`.
+///
+/// A popping thread has to read the `next` pointer of the node at the top of the stack, which another thread might
+/// pop and hand out concurrently. To make this safe, each stack keeps a registry of hazard pointers: a thread
+/// announces the node it is about to read before reading it, and a node is only handed out once no other thread
+/// has announced it. This also rules out the ABA problem, since a node cannot be pushed again before it is handed
+/// out.
+///
+/// Since a popped node is handed out itself, rather than a copy of its value, it cannot be handed out while other
+/// threads are still reading it, so [`pop`](ArcStack::pop) is *not* lock-free: it blocks until concurrent pops
+/// have moved on from the node it took. Pushing never waits.
+///
+/// ```rust
+/// # use elysees::{ArcNode, ArcStack};
+/// let stack = ArcStack::new();
+/// stack.push(ArcNode::new(1));
+/// stack.push(ArcNode::new(2));
+/// let top = stack.pop().unwrap();
+/// assert_eq!(**top, 2);
+/// assert_eq!(ArcNode::into_inner(stack.pop().unwrap()), 1);
+/// assert!(stack.pop().is_none());
+/// ```
+pub struct ArcStack<T> {
+    head: atomic::AtomicPtr<Link>,
+    hazards: atomic::AtomicPtr<Hazard>,
+    phantom: PhantomData<ArcBox<ArcNode<T>>>,
+}
+
+unsafe impl<T: Send> Send for ArcStack<T> {}
+unsafe impl<T: Send> Sync for ArcStack<T> {}
+
+impl<T> ArcStack<T> {
+    /// Create a new, empty stack
+    #[inline]
+    pub fn new() -> ArcStack<T> {
+        ArcStack {
+            head: atomic::AtomicPtr::new(ptr::null_mut()),
+            hazards: atomic::AtomicPtr::new(ptr::null_mut()),
+            phantom: PhantomData,
+        }
+    }
+    /// Whether this stack is empty. This may of course change immediately if other threads are using the stack.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Relaxed).is_null()
+    }
+    /// Push a node onto the top of this stack
+    pub fn push(&self, node: ArcBox<ArcNode<T>>) {
+        let node = ArcNode::into_link(node);
+        let mut head = self.head.load(Relaxed);
+        loop {
+            // The node is not yet published, so nothing else can be reading its link
+            unsafe { (*node).next.store(head, Relaxed) };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Release, Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+    /// Pop the node at the top of this stack, if any
+    ///
+    /// # Blocking
+    /// This spins until no concurrent `pop` is still reading the popped node. Each of them only reads it for a few
+    /// instructions, but if one is descheduled in the meantime, this waits until it runs again.
+    pub fn pop(&self) -> Option<ArcBox<ArcNode<T>>> {
+        let hazard = self.acquire_hazard();
+        let node = loop {
+            let head = self.head.load(Acquire);
+            if head.is_null() {
+                break head;
+            }
+            hazard.node.store(head, Relaxed);
+            // Check that `head` is still at the top of the stack with a read-modify-write, so that our announcement
+            // is visible to whichever thread pops it
+            if self
+                .head
+                .compare_exchange(head, head, AcqRel, Relaxed)
+                .is_err()
+            {
+                continue;
+            }
+            // `head` cannot be handed out while we have announced it, so it is safe to read
+            let next = unsafe { (*head).next.load(Relaxed) };
+            if self
+                .head
+                .compare_exchange(head, next, AcqRel, Relaxed)
+                .is_ok()
+            {
+                break head;
+            }
+        };
+        hazard.node.store(ptr::null_mut(), Release);
+        hazard.active.store(false, Release);
+        if node.is_null() {
+            return None;
+        }
+        self.wait_for_readers(node);
+        Some(unsafe { ArcNode::from_link(node) })
+    }
+    /// Claim an unused hazard pointer, registering a new one if they are all in use
+    fn acquire_hazard(&self) -> &Hazard {
+        let mut current = self.hazards.load(Acquire);
+        while !current.is_null() {
+            // Registered hazard pointers are only freed when the stack is dropped
+            let hazard = unsafe { &*current };
+            if hazard
+                .active
+                .compare_exchange(false, true, Acquire, Relaxed)
+                .is_ok()
+            {
+                return hazard;
+            }
+            current = hazard.next;
+        }
+        let hazard = Box::into_raw(Box::new(Hazard {
+            node: atomic::AtomicPtr::new(ptr::null_mut()),
+            active: atomic::AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.hazards.load(Relaxed);
+        loop {
+            unsafe { (*hazard).next = head };
+            match self
+                .hazards
+                .compare_exchange_weak(head, hazard, Release, Relaxed)
+            {
+                Ok(_) => return unsafe { &*hazard },
+                Err(current) => head = current,
+            }
+        }
+    }
+    /// Wait until no other thread has announced that it is reading `node`, which has been removed from the stack
+    fn wait_for_readers(&self, node: *mut Link) {
+        let mut current = self.hazards.load(Acquire);
+        while !current.is_null() {
+            let hazard = unsafe { &*current };
+            while hazard.node.load(Acquire) == node {
+                spin()
+            }
+            current = hazard.next;
+        }
+    }
+}
+
+impl<T> Default for ArcStack<T> {
+    #[inline]
+    fn default() -> ArcStack<T> {
+        ArcStack::new()
+    }
+}
+
+impl<T> Drop for ArcStack<T> {
+    fn drop(&mut self) {
+        let mut node = self.head.load(Relaxed);
+        while !node.is_null() {
+            let next = unsafe { (*node).next.load(Relaxed) };
+            drop(unsafe { ArcNode::<T>::from_link(node) });
+            node = next;
+        }
+        let mut hazard = self.hazards.load(Relaxed);
+        while !hazard.is_null() {
+            let next = unsafe { (*hazard).next };
+            drop(unsafe { Box::from_raw(hazard) });
+            hazard = next;
+        }
+    }
+}
+
+impl<T> fmt::Debug for ArcStack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArcStack").finish_non_exhaustive()
+    }
+}
+
+/// A lock-free, multi-producer single-consumer queue of `ArcNode`s
+///
+/// This is Dmitry Vyukov's intrusive MPSC queue: any number of threads may [`push`](ArcQueue::push) concurrently
+/// without waiting, while nodes are popped by the single holder of the queue's [`consumer`](ArcQueue::consumer).
+/// Since only the consumer ever frees nodes, and only once every producer is done with them, no further memory
+/// reclamation scheme is needed.
+///
+/// If a producer is interrupted in the middle of a push, the nodes it is linking in and any pushed after it are not
+/// visible to the consumer until it finishes, so [`pop`](ArcQueueConsumer::pop) may return `None` even though
+/// pushes have completed.
+///
+/// ```rust
+/// # use elysees::{ArcNode, ArcQueue};
+/// let queue = ArcQueue::new();
+/// queue.push(ArcNode::new("first"));
+/// queue.push(ArcNode::new("second"));
+/// let mut consumer = queue.consumer().unwrap();
+/// assert!(queue.consumer().is_none());
+/// assert_eq!(**consumer.pop().unwrap(), "first");
+/// assert_eq!(**consumer.pop().unwrap(), "second");
+/// assert!(consumer.pop().is_none());
+/// ```
+pub struct ArcQueue<T> {
+    /// The most recently pushed node
+    head: atomic::AtomicPtr<Link>,
+    /// The next node to pop, which is only accessed by the consumer
+    tail: atomic::AtomicPtr<Link>,
+    /// A placeholder node, which keeps the queue nonempty so that producers always have a node to link onto. It is
+    /// owned by the queue, but kept as a raw pointer since other threads link onto it.
+    stub: *mut Link,
+    /// Whether the consumer has been claimed
+    consuming: atomic::AtomicBool,
+    phantom: PhantomData<ArcBox<ArcNode<T>>>,
+}
+
+unsafe impl<T: Send> Send for ArcQueue<T> {}
+unsafe impl<T: Send> Sync for ArcQueue<T> {}
+
+impl<T> ArcQueue<T> {
+    /// Create a new, empty queue
+    #[inline]
+    pub fn new() -> ArcQueue<T> {
+        let stub = Box::into_raw(Box::new(Link::new()));
+        ArcQueue {
+            head: atomic::AtomicPtr::new(stub),
+            tail: atomic::AtomicPtr::new(stub),
+            stub,
+            consuming: atomic::AtomicBool::new(false),
+            phantom: PhantomData,
+        }
+    }
+    /// Push a node onto the back of this queue
+    #[inline]
+    pub fn push(&self, node: ArcBox<ArcNode<T>>) {
+        self.push_link(ArcNode::into_link(node))
+    }
+    /// Push an unpublished node onto the back of this queue
+    #[inline]
+    fn push_link(&self, node: *mut Link) {
+        unsafe { (*node).next.store(ptr::null_mut(), Relaxed) };
+        let previous = self.head.swap(node, AcqRel);
+        // Until this store, the consumer cannot move past `previous`, so it is still live
+        unsafe { (*previous).next.store(node, Release) };
+    }
+    /// Claim the right to pop from this queue, returning `None` if another consumer already exists
+    #[inline]
+    pub fn consumer(&self) -> Option<ArcQueueConsumer<'_, T>> {
+        self.consuming
+            .compare_exchange(false, true, Acquire, Relaxed)
+            .ok()
+            .map(|_| ArcQueueConsumer { queue: self })
+    }
+    /// Pop the node at the front of this queue, if any. Must only be called by the consumer.
+    fn pop(&self) -> Option<ArcBox<ArcNode<T>>> {
+        let stub = self.stub;
+        let mut tail = self.tail.load(Relaxed);
+        let mut next = unsafe { (*tail).next.load(Acquire) };
+        if tail == stub {
+            if next.is_null() {
+                return None;
+            }
+            self.tail.store(next, Relaxed);
+            tail = next;
+            next = unsafe { (*next).next.load(Acquire) };
+        }
+        if !next.is_null() {
+            self.tail.store(next, Relaxed);
+            return Some(unsafe { ArcNode::from_link(tail) });
+        }
+        if tail != self.head.load(Acquire) {
+            // A producer has claimed the spot after `tail` but not yet linked its node
+            return None;
+        }
+        // `tail` is the last node, so put the stub behind it to be able to pop it
+        self.push_link(stub);
+        next = unsafe { (*tail).next.load(Acquire) };
+        if !next.is_null() {
+            self.tail.store(next, Relaxed);
+            return Some(unsafe { ArcNode::from_link(tail) });
+        }
+        None
+    }
+}
+
+impl<T> Default for ArcQueue<T> {
+    #[inline]
+    fn default() -> ArcQueue<T> {
+        ArcQueue::new()
+    }
+}
+
+impl<T> Drop for ArcQueue<T> {
+    fn drop(&mut self) {
+        // With no producers left, every pushed node is reachable
+        while self.pop().is_some() {}
+        drop(unsafe { Box::from_raw(self.stub) })
+    }
+}
+
+impl<T> fmt::Debug for ArcQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArcQueue").finish_non_exhaustive()
+    }
+}
+
+/// The unique consumer of an `ArcQueue`, as returned by [`ArcQueue::consumer`]
+pub struct ArcQueueConsumer<'a, T> {
+    queue: &'a ArcQueue<T>,
+}
+
+impl<'a, T> ArcQueueConsumer<'a, T> {
+    /// Pop the node at the front of the queue, if any
+    #[inline]
+    pub fn pop(&mut self) -> Option<ArcBox<ArcNode<T>>> {
+        self.queue.pop()
+    }
+}
+
+impl<'a, T> Drop for ArcQueueConsumer<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.queue.consuming.store(false, Release)
+    }
+}
+
+impl<'a, T> fmt::Debug for ArcQueueConsumer<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArcQueueConsumer").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,920 @@
+use crate::*;
+use core::hash::BuildHasher;
+use core::iter;
+use core::ops::Index;
+use core::slice;
+use slice_dst::SliceWithHeader;
+use std::collections::hash_map::RandomState;
+
+/// The number of hash bits consumed at each level of an `ArcHashMap`
+const BITS: u32 = 5;
+
+/// The mask selecting the hash bits for one level of an `ArcHashMap`
+const MASK: u64 = (1 << BITS) - 1;
+
+/// The maximum number of slots in a branch of an `ArcHashMap`
+const WIDTH: usize = 1 << BITS;
+
+/// A slot in a branch of an `ArcHashMap`
+enum Slot<K, V> {
+    /// A single entry
+    Entry { hash: u64, key: K, value: V },
+    /// Two or more entries whose keys have the same hash
+    Collision { hash: u64, entries: Arc<[(K, V)]> },
+    /// A subtree
+    Branch(Branch<K, V>),
+    /// Spare room at the end of a branch, or a slot whose contents have been moved out, which is never left among the
+    /// occupied slots of a map
+    Vacant,
+}
+
+/// The slots of a branch of an `ArcHashMap`, after a bitmap of which hash values they hold
+type Slots<K, V> = SliceWithHeader<u32, Slot<K, V>>;
+
+/// A branch of an `ArcHashMap`, holding a slot for each bit set in its bitmap, followed by any spare room as vacant
+/// slots
+struct Branch<K, V>(Arc<Slots<K, V>>);
+
+impl<K: Clone, V: Clone> Clone for Slot<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Slot::Entry { hash, key, value } => Slot::Entry {
+                hash: *hash,
+                key: key.clone(),
+                value: value.clone(),
+            },
+            Slot::Collision { hash, entries } => Slot::Collision {
+                hash: *hash,
+                entries: entries.clone(),
+            },
+            Slot::Branch(branch) => Slot::Branch(branch.clone()),
+            Slot::Vacant => Slot::Vacant,
+        }
+    }
+}
+
+impl<K, V> Clone for Branch<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Branch(self.0.clone())
+    }
+}
+
+impl<K: Clone, V: Clone> Slot<K, V> {
+    /// Take the entries out of a collision as a growable buffer, leaving this slot vacant
+    ///
+    /// Shared entries are cloned before this slot is touched, so that a panicking `Clone` leaves it intact.
+    fn take_entries(&mut self) -> ArcVec<(K, V)> {
+        let cloned = match self {
+            Slot::Collision { entries, .. } if !entries.is_unique() => {
+                Some(ArcVec::from(&**entries))
+            }
+            _ => None,
+        };
+        match mem::replace(self, Slot::Vacant) {
+            Slot::Collision { entries, .. } => cloned.unwrap_or_else(|| Arc::into_vec(entries)),
+            _ => unreachable!("slot holds a collision"),
+        }
+    }
+}
+
+impl<K, V> Slot<K, V> {
+    /// Get the hash of the keys in an entry or collision
+    #[inline]
+    fn hash(&self) -> u64 {
+        match self {
+            Slot::Entry { hash, .. } | Slot::Collision { hash, .. } => *hash,
+            Slot::Branch(_) => unreachable!("branches hold keys with many hashes"),
+            Slot::Vacant => unreachable!("occupied slots are never vacant"),
+        }
+    }
+}
+
+/// Get the bit for the given hash at a level
+#[inline]
+fn hash_bit(hash: u64, shift: u32) -> u32 {
+    1 << ((hash >> shift) & MASK)
+}
+
+impl<K, V> Branch<K, V> {
+    /// Create a branch holding the given slots, which must be in order, one for each bit set in `bitmap`
+    #[inline]
+    fn new<I>(bitmap: u32, slots: I) -> Branch<K, V>
+    where
+        I: IntoIterator<Item = Slot<K, V>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Branch(SliceWithHeader::new(bitmap, slots))
+    }
+    /// Get the bitmap of which slots of this branch are occupied
+    #[inline]
+    fn bitmap(&self) -> u32 {
+        self.0.header
+    }
+    /// Get the number of occupied slots in this branch
+    #[inline]
+    fn len(&self) -> usize {
+        self.bitmap().count_ones() as usize
+    }
+    /// Get the occupied slots of this branch
+    #[inline]
+    fn slots(&self) -> &[Slot<K, V>] {
+        &self.0.slice[..self.len()]
+    }
+    /// Get the index of the slot for `bit`, whether or not it is occupied
+    #[inline]
+    fn index(&self, bit: u32) -> usize {
+        (self.bitmap() & (bit - 1)).count_ones() as usize
+    }
+    /// Create a branch holding a single slot
+    #[inline]
+    fn single(bit: u32, slot: Slot<K, V>) -> Branch<K, V> {
+        Branch::new(bit, iter::once(slot))
+    }
+    /// Create a subtree at `shift` holding two slots with different hashes
+    fn pair(
+        shift: u32,
+        left_hash: u64,
+        left: Slot<K, V>,
+        right_hash: u64,
+        right: Slot<K, V>,
+    ) -> Branch<K, V> {
+        let left_bit = hash_bit(left_hash, shift);
+        let right_bit = hash_bit(right_hash, shift);
+        if left_bit == right_bit {
+            let child = Branch::pair(shift + BITS, left_hash, left, right_hash, right);
+            return Branch::single(left_bit, Slot::Branch(child));
+        }
+        let slots = if left_bit < right_bit {
+            [left, right]
+        } else {
+            [right, left]
+        };
+        Branch::new(left_bit | right_bit, slots)
+    }
+    /// Look up the value for `key`, which has the given hash
+    fn get<Q>(&self, hash: u64, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let mut branch = self;
+        let mut shift = 0;
+        loop {
+            let bit = hash_bit(hash, shift);
+            if branch.bitmap() & bit == 0 {
+                return None;
+            }
+            match &branch.slots()[branch.index(bit)] {
+                Slot::Entry {
+                    hash: entry_hash,
+                    key: entry_key,
+                    value,
+                } => return (*entry_hash == hash && entry_key.borrow() == key).then_some(value),
+                Slot::Collision {
+                    hash: entry_hash,
+                    entries,
+                } if *entry_hash == hash => {
+                    return entries
+                        .iter()
+                        .find(|(entry_key, _)| entry_key.borrow() == key)
+                        .map(|(_, value)| value)
+                }
+                Slot::Collision { .. } => return None,
+                Slot::Branch(child) => {
+                    branch = child;
+                    shift += BITS;
+                }
+                Slot::Vacant => unreachable!("occupied slots are never vacant"),
+            }
+        }
+    }
+}
+
+impl<K: Eq + Clone, V: Clone> Branch<K, V> {
+    /// Get mutable access to the occupied slots of this branch, copying it first if it is shared
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [Slot<K, V>] {
+        let len = self.len();
+        &mut Arc::make_mut(&mut self.0).slice[..len]
+    }
+    /// Take the slots out of this branch, moving them if it is not shared and cloning them otherwise, in which case a
+    /// panicking `Clone` leaves it intact
+    fn take_slots(&mut self) -> Vec<Slot<K, V>> {
+        if self.0.is_unique() {
+            self.slots_mut()
+                .iter_mut()
+                .map(|slot| mem::replace(slot, Slot::Vacant))
+                .collect()
+        } else {
+            self.slots().to_vec()
+        }
+    }
+    /// Replace this branch with a new one holding `slots`, one for each bit set in `bitmap`
+    ///
+    /// A transient map's branch is given room to grow, while a persistent map's branch is allocated at its exact size.
+    fn rebuild(&mut self, bitmap: u32, mut slots: Vec<Slot<K, V>>, transient: bool) {
+        if transient {
+            let capacity = (2 * slots.len()).min(WIDTH);
+            slots.resize_with(capacity, || Slot::Vacant);
+        }
+        *self = Branch::new(bitmap, slots);
+    }
+    /// Remove the slot for `bit`, which is at `index`, shrinking this branch in place if it is unique and `transient`
+    fn remove_slot(&mut self, bit: u32, index: usize, transient: bool) {
+        let len = self.len();
+        if transient && self.0.is_unique() {
+            let branch = Arc::make_mut(&mut self.0);
+            branch.header &= !bit;
+            branch.slice[index..len].rotate_left(1);
+            branch.slice[len - 1] = Slot::Vacant;
+            return;
+        }
+        let bitmap = self.bitmap() & !bit;
+        let mut slots = self.take_slots();
+        slots.remove(index);
+        self.rebuild(bitmap, slots, transient);
+    }
+    /// Insert an entry into the subtree at `shift`, returning the previous value for its key, if any
+    ///
+    /// If `transient`, a new slot is added in place when the branch holding it is unique and has room to spare.
+    fn insert(&mut self, shift: u32, hash: u64, key: K, value: V, transient: bool) -> Option<V> {
+        let bit = hash_bit(hash, shift);
+        let index = self.index(bit);
+        if self.bitmap() & bit == 0 {
+            let len = self.len();
+            if transient && len < self.0.slice.len() && self.0.is_unique() {
+                let branch = Arc::make_mut(&mut self.0);
+                branch.header |= bit;
+                branch.slice[len] = Slot::Entry { hash, key, value };
+                branch.slice[index..=len].rotate_right(1);
+                return None;
+            }
+            let bitmap = self.bitmap() | bit;
+            let mut slots = self.take_slots();
+            slots.insert(index, Slot::Entry { hash, key, value });
+            self.rebuild(bitmap, slots, transient);
+            return None;
+        }
+        let slot = &mut self.slots_mut()[index];
+        match slot {
+            Slot::Entry {
+                hash: entry_hash,
+                key: entry_key,
+                value: entry_value,
+            } if *entry_hash == hash && *entry_key == key => {
+                return Some(mem::replace(entry_value, value))
+            }
+            Slot::Collision {
+                hash: entry_hash,
+                entries,
+            } if *entry_hash == hash => {
+                if let Some(position) = entries.iter().position(|(entry_key, _)| *entry_key == key)
+                {
+                    let entry_value = &mut Arc::make_mut(entries)[position].1;
+                    return Some(mem::replace(entry_value, value));
+                }
+                let mut entries = slot.take_entries();
+                entries.push((key, value));
+                let entries = entries.freeze();
+                *slot = Slot::Collision { hash, entries };
+                return None;
+            }
+            Slot::Branch(child) => return child.insert(shift + BITS, hash, key, value, transient),
+            _ => {}
+        }
+        *slot = match mem::replace(slot, Slot::Vacant) {
+            Slot::Entry {
+                hash: entry_hash,
+                key: entry_key,
+                value: entry_value,
+            } if entry_hash == hash => {
+                let mut entries = ArcVec::with_capacity(2);
+                entries.push((entry_key, entry_value));
+                entries.push((key, value));
+                let entries = entries.freeze();
+                Slot::Collision { hash, entries }
+            }
+            other => {
+                let child = Branch::pair(
+                    shift + BITS,
+                    other.hash(),
+                    other,
+                    hash,
+                    Slot::Entry { hash, key, value },
+                );
+                Slot::Branch(child)
+            }
+        };
+        None
+    }
+    /// Get a mutable reference to the value for `key`, which must be in the subtree at `shift`
+    fn get_mut<Q>(&mut self, shift: u32, hash: u64, key: &Q) -> &mut V
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let index = self.index(hash_bit(hash, shift));
+        match &mut self.slots_mut()[index] {
+            Slot::Entry { value, .. } => value,
+            Slot::Collision { entries, .. } => {
+                let (_, value) = Arc::make_mut(entries)
+                    .iter_mut()
+                    .find(|(entry_key, _)| entry_key.borrow() == key)
+                    .expect("key is in the map");
+                value
+            }
+            Slot::Branch(child) => child.get_mut(shift + BITS, hash, key),
+            Slot::Vacant => unreachable!("occupied slots are never vacant"),
+        }
+    }
+    /// Remove the entry for `key`, which must be in the subtree at `shift`, returning it
+    ///
+    /// This may leave the subtree empty, or holding a lone entry or collision, which its parent should then take. If
+    /// `transient`, slots are removed in place from unique branches.
+    fn remove<Q>(&mut self, shift: u32, hash: u64, key: &Q, transient: bool) -> (K, V)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let bit = hash_bit(hash, shift);
+        let index = self.index(bit);
+        let slot = &mut self.slots_mut()[index];
+        if let Slot::Branch(child) = slot {
+            let removed = child.remove(shift + BITS, hash, key, transient);
+            match child.slots() {
+                [] => self.remove_slot(bit, index, transient),
+                // A lone entry or collision does not need a branch of its own, so move it up a level
+                [lone] if !matches!(lone, Slot::Branch(_)) => {
+                    *slot = child.take_slots().pop().expect("one slot is left")
+                }
+                _ => {}
+            }
+            return removed;
+        }
+        if let Slot::Collision { entries, .. } = slot {
+            // Find the key before vacating the slot, so that a panicking `Eq` leaves it intact
+            let position = entries
+                .iter()
+                .position(|(entry_key, _)| entry_key.borrow() == key)
+                .expect("key is in the map");
+            let mut entries = slot.take_entries();
+            let removed = entries.remove(position);
+            *slot = if entries.len() == 1 {
+                let (key, value) = entries.pop().expect("one entry is left");
+                Slot::Entry { hash, key, value }
+            } else {
+                Slot::Collision {
+                    hash,
+                    entries: entries.freeze(),
+                }
+            };
+            return removed;
+        }
+        match mem::replace(slot, Slot::Vacant) {
+            Slot::Entry { key, value, .. } => {
+                self.remove_slot(bit, index, transient);
+                (key, value)
+            }
+            Slot::Collision { .. } | Slot::Branch(_) | Slot::Vacant => {
+                unreachable!("slot holds the key")
+            }
+        }
+    }
+}
+
+/// A persistent hash map built out of `Arc`s
+///
+/// An `ArcHashMap` is a hash array mapped trie: each branch holds up to 32 slots, one for each value of the next
+/// five bits of the hash, along with a bitmap of which slots are occupied, so it stores only the slots it uses in
+/// a single `Arc<SliceWithHeader<u32, _>>` allocation, with the bitmap as its header. Cloning a map is `O(1)`,
+/// since it just clones the `Arc` pointing to its root, and clones share all their branches until they are
+/// modified. Lookups, insertions and removals take `O(log n)` time.
+///
+/// Modifying a map copies the branches on the path to the change which are shared with another map, and updates
+/// the rest in place, except that adding or removing a slot always reallocates the branch holding it, so that
+/// branches stay at their exact size.
+///
+/// ```rust
+/// # use elysees::ArcHashMap;
+/// let mut scope: ArcHashMap<&str, u32> = vec![("x", 1), ("y", 2)].into_iter().collect();
+/// let outer = scope.clone();
+/// scope.insert("x", 3);
+/// scope.insert("z", 4);
+/// assert_eq!(scope.get("x"), Some(&3));
+/// assert_eq!(outer.get("x"), Some(&1));
+/// assert_eq!(outer.get("z"), None);
+/// assert_eq!(scope.remove("y"), Some(2));
+/// assert_eq!(scope.len(), 2);
+/// assert_eq!(outer.len(), 2);
+/// ```
+///
+/// # Batch updates
+///
+/// To apply many updates at once, turn the map into an [`ArcHashMapMut`] with [`transient`](ArcHashMap::transient),
+/// which adds and removes slots in place, and back with [`persistent`](ArcHashMapMut::persistent) when done.
+pub struct ArcHashMap<K, V, S = RandomState> {
+    root: Option<Branch<K, V>>,
+    len: usize,
+    hasher: S,
+}
+
+impl<K, V> ArcHashMap<K, V> {
+    /// Create a new, empty map
+    #[inline]
+    pub fn new() -> ArcHashMap<K, V> {
+        ArcHashMap::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S> ArcHashMap<K, V, S> {
+    /// Create a new, empty map which will use the given hasher to hash keys
+    #[inline]
+    pub const fn with_hasher(hasher: S) -> ArcHashMap<K, V, S> {
+        ArcHashMap {
+            root: None,
+            len: 0,
+            hasher,
+        }
+    }
+    /// Get the number of entries in this map
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether this map is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Get a reference to this map's hasher
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+    /// Iterate over the entries of this map, in an arbitrary order
+    #[inline]
+    pub fn iter(&self) -> ArcHashMapIter<'_, K, V> {
+        let mut stack = Vec::new();
+        if let Some(root) = &self.root {
+            stack.push(root.slots().iter())
+        }
+        ArcHashMapIter {
+            stack,
+            collision: [].iter(),
+            len: self.len,
+        }
+    }
+    /// Whether two maps share the same root, and hence have the same entries
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        match (&this.root, &other.root) {
+            (Some(this), Some(other)) => Arc::ptr_eq(&this.0, &other.0),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+    /// Remove all entries from this map
+    #[inline]
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+    /// Turn this map into a transient map, to apply a batch of updates to it
+    #[inline]
+    pub fn transient(self) -> ArcHashMapMut<K, V, S> {
+        ArcHashMapMut { map: self }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> ArcHashMap<K, V, S> {
+    /// Hash a key with this map's hasher
+    #[inline]
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> u64 {
+        self.hasher.hash_one(key)
+    }
+    /// Get a reference to the value for `key`, if any
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.root.as_ref()?.get(self.hash(key), key)
+    }
+    /// Whether this map contains an entry for `key`
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> ArcHashMap<K, V, S> {
+    /// Insert a value for `key`, returning the previous value, if any
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_with(key, value, false)
+    }
+    /// Insert a value for `key`, returning the previous value, if any, updating unique branches in place if `transient`
+    fn insert_with(&mut self, key: K, value: V, transient: bool) -> Option<V> {
+        let hash = self.hash(&key);
+        let previous = match &mut self.root {
+            Some(root) => root.insert(0, hash, key, value, transient),
+            None => {
+                let slot = Slot::Entry { hash, key, value };
+                self.root = Some(Branch::single(hash_bit(hash, 0), slot));
+                None
+            }
+        };
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+    /// Remove the entry for `key`, returning its value, if any
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+    /// Remove the entry for `key`, returning it, if any
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.remove_entry_with(key, false)
+    }
+    /// Remove the entry for `key`, returning it, if any, updating unique branches in place if `transient`
+    fn remove_entry_with<Q>(&mut self, key: &Q, transient: bool) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        // Check that the key is present first, to avoid copying shared branches when there is nothing to remove
+        if !self.contains_key(key) {
+            return None;
+        }
+        let hash = self.hash(key);
+        let root = self.root.as_mut().expect("key is in the map");
+        let removed = root.remove(0, hash, key, transient);
+        if root.slots().is_empty() {
+            self.root = None;
+        }
+        self.len -= 1;
+        Some(removed)
+    }
+}
+
+impl<K, V, S: Clone> Clone for ArcHashMap<K, V, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArcHashMap {
+            root: self.root.clone(),
+            len: self.len,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<K, V, S: Default> Default for ArcHashMap<K, V, S> {
+    #[inline]
+    fn default() -> Self {
+        ArcHashMap::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for ArcHashMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        if ArcHashMap::ptr_eq(self, other) {
+            return true;
+        }
+        self.len == other.len
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for ArcHashMap<K, V, S> {}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for ArcHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for ArcHashMap<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: ?Sized + Hash + Eq,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> Extend<(K, V)> for ArcHashMap<K, V, S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Default> FromIterator<(K, V)>
+    for ArcHashMap<K, V, S>
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut result = ArcHashMap::default();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a ArcHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = ArcHashMapIter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> ArcHashMapIter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an `ArcHashMap`
+pub struct ArcHashMapIter<'a, K, V> {
+    /// The unvisited slots at each level above the current one
+    stack: Vec<slice::Iter<'a, Slot<K, V>>>,
+    /// The unvisited entries of the current collision
+    collision: slice::Iter<'a, (K, V)>,
+    len: usize,
+}
+
+impl<'a, K, V> Clone for ArcHashMapIter<'a, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArcHashMapIter {
+            stack: self.stack.clone(),
+            collision: self.collision.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for ArcHashMapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            if let Some((key, value)) = self.collision.next() {
+                self.len -= 1;
+                return Some((key, value));
+            }
+            match self.stack.last_mut()?.next() {
+                Some(Slot::Entry { key, value, .. }) => {
+                    self.len -= 1;
+                    return Some((key, value));
+                }
+                Some(Slot::Collision { entries, .. }) => self.collision = entries.iter(),
+                Some(Slot::Branch(branch)) => self.stack.push(branch.slots().iter()),
+                Some(Slot::Vacant) => unreachable!("occupied slots are never vacant"),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for ArcHashMapIter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for ArcHashMapIter<'a, K, V> {}
+
+/// A transient `ArcHashMap`, for applying a batch of updates
+///
+/// A transient map cannot be cloned, so every branch it copies or creates stays unique until it is made
+/// [`persistent`](ArcHashMapMut::persistent) again. Each branch it shares with the maps it was created from is
+/// hence copied at most once, by the first update which touches it, and all later updates to that branch check that
+/// it is still unique and modify it in place. Values may also be modified in place through
+/// [`get_mut`](ArcHashMapMut::get_mut).
+///
+/// Unlike a persistent map, whose branches are allocated at their exact size, a transient map gives each branch it
+/// copies or grows room for up to twice as many slots, so that later insertions into it do not reallocate it, and
+/// removes slots from unique branches without shrinking them. Branches keep this spare room once the map is made
+/// persistent again.
+///
+/// ```rust
+/// # use elysees::ArcHashMap;
+/// let counts: ArcHashMap<&str, u32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+/// let mut batch = counts.clone().transient();
+/// for &word in ["a", "b", "a", "c"].iter() {
+///     match batch.get_mut(word) {
+///         Some(count) => *count += 1,
+///         None => {
+///             batch.insert(word, 1);
+///         }
+///     }
+/// }
+/// let updated = batch.persistent();
+/// assert_eq!(updated.get("a"), Some(&3));
+/// assert_eq!(updated.get("c"), Some(&1));
+/// assert_eq!(counts.get("a"), Some(&1));
+/// assert_eq!(counts.get("c"), None);
+/// ```
+pub struct ArcHashMapMut<K, V, S = RandomState> {
+    map: ArcHashMap<K, V, S>,
+}
+
+impl<K, V, S> ArcHashMapMut<K, V, S> {
+    /// Turn this map back into a persistent map, which may be cloned
+    #[inline]
+    pub fn persistent(self) -> ArcHashMap<K, V, S> {
+        self.map
+    }
+    /// Get the number of entries in this map
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+    /// Whether this map is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+    /// Iterate over the entries of this map, in an arbitrary order
+    #[inline]
+    pub fn iter(&self) -> ArcHashMapIter<'_, K, V> {
+        self.map.iter()
+    }
+    /// Remove all entries from this map
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> ArcHashMapMut<K, V, S> {
+    /// Get a reference to the value for `key`, if any
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(key)
+    }
+    /// Whether this map contains an entry for `key`
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.contains_key(key)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> ArcHashMapMut<K, V, S> {
+    /// Get a mutable reference to the value for `key`, if any, copying the branches on the path to it if they are
+    /// shared
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        // Check that the key is present first, to avoid copying shared branches when there is nothing to modify
+        if !self.contains_key(key) {
+            return None;
+        }
+        let hash = self.map.hash(key);
+        let root = self.map.root.as_mut().expect("key is in the map");
+        Some(root.get_mut(0, hash, key))
+    }
+    /// Insert a value for `key`, returning the previous value, if any
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.map.insert_with(key, value, true)
+    }
+    /// Remove the entry for `key`, returning its value, if any
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+    /// Remove the entry for `key`, returning it, if any
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.remove_entry_with(key, true)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for ArcHashMapMut<K, V, S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> Extend<(K, V)> for ArcHashMapMut<K, V, S> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.map.extend(iter)
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a ArcHashMapMut<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = ArcHashMapIter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> ArcHashMapIter<'a, K, V> {
+        self.iter()
+    }
+}
+
+#[cfg(all(test, feature = "stats"))]
+mod tests {
+    use super::*;
+    use core::hash::{BuildHasherDefault, Hasher};
+
+    /// A hasher which hashes integers to themselves, to choose which slot of the root each key goes in
+    #[derive(Default)]
+    struct IdentityHasher(u64);
+
+    impl Hasher for IdentityHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, _bytes: &[u8]) {
+            unimplemented!("only integers are hashed")
+        }
+        fn write_u64(&mut self, n: u64) {
+            self.0 = n
+        }
+    }
+
+    #[test]
+    fn transient_updates_in_place() {
+        stats::register::<Slots<u64, u64>>();
+        let allocations = || stats::type_stats::<Slots<u64, u64>>().unwrap().allocations;
+        let map: ArcHashMap<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            (0..4).map(|i| (i, i)).collect();
+        let snapshot = map.clone();
+        let mut batch = map.transient();
+
+        // The first insertion copies the shared root, with room for ten slots
+        let before = allocations();
+        batch.insert(4, 4);
+        assert_eq!(allocations(), before + 1);
+
+        // Which later insertions and removals use without reallocating it
+        for i in 5..10 {
+            assert_eq!(batch.insert(i, i), None);
+        }
+        for i in (0..10).step_by(2) {
+            assert_eq!(batch.remove(&i), Some(i));
+        }
+        for i in (0..10).step_by(2) {
+            assert_eq!(batch.insert(i, i + 1), None);
+        }
+        assert_eq!(allocations(), before + 1);
+
+        // Until it is full
+        batch.insert(10, 10);
+        assert_eq!(allocations(), before + 2);
+
+        let map = batch.persistent();
+        assert_eq!(map.len(), 11);
+        assert!((0..11).all(|i| map[&i] == if i < 10 && i % 2 == 0 { i + 1 } else { i }));
+        assert_eq!(snapshot.len(), 4);
+        assert!((0..4).all(|i| snapshot[&i] == i));
+    }
+}
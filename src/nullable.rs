@@ -0,0 +1,163 @@
+use crate::*;
+
+/// An optional `Arc<T>` with the same representation as a nullable C `T*`
+///
+/// `NullableArc<T>` is `#[repr(transparent)]` over `Option<Arc<T>>`, which is in turn guaranteed to be a single
+/// pointer, null for `None`, since `Arc<T>` is a non-null pointer to the data of its allocation. It can hence be
+/// passed to and from C in place of a `T*`, and embedded in `#[repr(C)]` structs shared with C, with the null pointer
+/// standing for no value. Unlike a raw pointer, it still owns a reference to the value it points to.
+///
+/// ```rust
+/// # use elysees::{Arc, NullableArc};
+/// let mut nullable = NullableArc::new(5);
+/// assert_eq!(nullable.as_deref(), Some(&5));
+/// let ptr = NullableArc::as_ptr(&nullable);
+/// assert_eq!(nullable.as_ref(), &ptr);
+///
+/// let arc = nullable.take().unwrap();
+/// assert!(nullable.is_null());
+/// assert!(NullableArc::as_ptr(&nullable).is_null());
+/// assert_eq!(std::mem::size_of::<NullableArc<u64>>(), std::mem::size_of::<*const u64>());
+///
+/// // A nullable pointer can be handed over to C and back
+/// let raw = NullableArc::into_raw(NullableArc::from(arc));
+/// let nullable = unsafe { NullableArc::from_raw(raw) };
+/// assert_eq!(*nullable.as_arc().unwrap(), Arc::new(5));
+/// ```
+#[repr(transparent)]
+pub struct NullableArc<T>(Option<Arc<T>>);
+
+impl<T> NullableArc<T> {
+    /// Construct a null `NullableArc<T>`
+    #[inline]
+    pub const fn null() -> Self {
+        NullableArc(None)
+    }
+    /// Construct a `NullableArc<T>` pointing to a new `Arc<T>`
+    #[inline]
+    pub fn new(data: T) -> Self {
+        NullableArc(Some(Arc::new(data)))
+    }
+    /// Check whether this is null
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.0.is_none()
+    }
+    /// Borrow the value pointed to, if any
+    #[inline]
+    pub fn as_deref(&self) -> Option<&T> {
+        self.0.as_deref()
+    }
+    /// Borrow the `Arc<T>` this holds, if any
+    #[inline]
+    pub fn as_arc(&self) -> Option<&Arc<T>> {
+        self.0.as_ref()
+    }
+    /// Borrow the `Arc<T>` this holds as an `ArcBorrow<T>`, if any
+    #[inline]
+    pub fn borrow_arc(&self) -> Option<ArcBorrow<'_, T>> {
+        self.0.as_ref().map(Arc::borrow_arc)
+    }
+    /// Take the `Arc<T>` out of this, leaving it null
+    #[inline]
+    pub fn take(&mut self) -> Option<Arc<T>> {
+        self.0.take()
+    }
+    /// Replace the `Arc<T>` this holds, returning the old one
+    #[inline]
+    pub fn replace(&mut self, arc: Arc<T>) -> Option<Arc<T>> {
+        self.0.replace(arc)
+    }
+    /// Convert this into an `Option<Arc<T>>`
+    #[inline]
+    pub fn into_option(this: Self) -> Option<Arc<T>> {
+        this.0
+    }
+    /// Get the raw pointer underlying this `NullableArc<T>`, which is null if it is
+    #[inline]
+    pub fn as_ptr(this: &Self) -> *const T {
+        *this.as_ref()
+    }
+    /// Convert this to a raw pointer, which is null if this is, suitable for use across FFI as a nullable C `T*`
+    #[inline]
+    pub fn into_raw(this: Self) -> *const T {
+        Arc::option_into_raw(this.0)
+    }
+    /// Convert a raw pointer obtained from `into_raw()` back into a `NullableArc<T>`
+    ///
+    /// # Safety
+    /// This function must be called with a null pointer or a pointer obtained from `into_raw()`,
+    /// `Arc::into_raw()` or `Arc::option_into_raw()`, which is then invalidated.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        NullableArc(Arc::option_from_raw(ptr))
+    }
+}
+
+impl<T> AsRef<*const T> for NullableArc<T> {
+    #[inline]
+    fn as_ref(&self) -> &*const T {
+        // `Option<Arc<T>>` is guaranteed to have the same layout as `*const T`, as documented on `NullableArc`
+        unsafe { &*(self as *const NullableArc<T> as *const *const T) }
+    }
+}
+
+impl<T> Clone for NullableArc<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        NullableArc(self.0.clone())
+    }
+}
+
+impl<T> Default for NullableArc<T> {
+    #[inline]
+    fn default() -> Self {
+        NullableArc::null()
+    }
+}
+
+impl<T: PartialEq> PartialEq for NullableArc<T> {
+    #[inline]
+    fn eq(&self, other: &NullableArc<T>) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq> Eq for NullableArc<T> {}
+
+impl<T: Hash> Hash for NullableArc<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for NullableArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            Some(arc) => f.debug_tuple("NullableArc").field(arc).finish(),
+            None => f.write_str("NullableArc(null)"),
+        }
+    }
+}
+
+impl<T> From<Arc<T>> for NullableArc<T> {
+    #[inline]
+    fn from(arc: Arc<T>) -> Self {
+        NullableArc(Some(arc))
+    }
+}
+
+impl<T> From<Option<Arc<T>>> for NullableArc<T> {
+    #[inline]
+    fn from(arc: Option<Arc<T>>) -> Self {
+        NullableArc(arc)
+    }
+}
+
+impl<T> From<NullableArc<T>> for Option<Arc<T>> {
+    #[inline]
+    fn from(nullable: NullableArc<T>) -> Self {
+        nullable.0
+    }
+}
@@ -0,0 +1,194 @@
+use crate::*;
+use core::sync::atomic::AtomicPtr;
+
+/// The value saturated `usize` counts are pinned at
+const SATURATED: usize = usize::MAX - usize::MAX / 4;
+/// The value above which a `usize` count is saturated, halfway between the soft limit on references and `SATURATED`
+const SATURATION_FLOOR: usize = SATURATED - usize::MAX / 8;
+/// The value saturated `u32` counts are pinned at
+const SATURATED32: u32 = u32::MAX - u32::MAX / 4;
+/// The value above which a `u32` count is saturated, halfway between the soft limit on references and `SATURATED32`
+const SATURATION_FLOOR32: u32 = SATURATED32 - u32::MAX / 8;
+
+/// A reference count which can handle overflow
+///
+/// Saturated counts are pinned three quarters of the way to their maximum value, far enough from the soft limit on
+/// references that no genuine count can reach them. Releasing references never lowers a saturated count, so the
+/// allocation is leaked forever, however many references are dropped afterwards.
+pub(crate) trait RefCount {
+    /// Get the current value of this count
+    fn current(&self) -> usize;
+    /// Undo adding `n` references to this count
+    fn undo(&self, n: usize);
+    /// Pin this count at its saturated value
+    fn saturate(&self);
+    /// Remove `n` references from this count with `Release` ordering, returning its previous value, unless it is
+    /// saturated, in which case it is left as is and `None` is returned
+    fn release(&self, n: usize) -> Option<usize>;
+}
+
+impl RefCount for atomic::AtomicUsize {
+    #[inline]
+    fn current(&self) -> usize {
+        self.load(Relaxed)
+    }
+    #[inline]
+    fn undo(&self, n: usize) {
+        self.fetch_sub(n, Relaxed);
+    }
+    #[inline]
+    fn saturate(&self) {
+        self.store(SATURATED, Relaxed)
+    }
+    #[inline]
+    fn release(&self, n: usize) -> Option<usize> {
+        if n == 1 {
+            // Putting the reference back afterwards lowers a saturated count by at most one per racing thread,
+            // which keeps it saturated, while dropping a single reference stays a single atomic operation
+            let old_size = self.fetch_sub(1, Release);
+            if old_size > SATURATION_FLOOR {
+                self.fetch_add(1, Relaxed);
+                return None;
+            }
+            return Some(old_size);
+        }
+        // Releasing many references at once could bring a saturated count back into range, so it is only done if
+        // the count is not saturated
+        let mut old_size = self.load(Relaxed);
+        loop {
+            if old_size > SATURATION_FLOOR {
+                return None;
+            }
+            match self.compare_exchange_weak(old_size, old_size.wrapping_sub(n), Release, Relaxed) {
+                Ok(_) => return Some(old_size),
+                Err(current) => old_size = current,
+            }
+        }
+    }
+}
+
+impl RefCount for atomic::AtomicU32 {
+    #[inline]
+    fn current(&self) -> usize {
+        self.load(Relaxed) as usize
+    }
+    #[inline]
+    fn undo(&self, n: usize) {
+        self.fetch_sub(n as u32, Relaxed);
+    }
+    #[inline]
+    fn saturate(&self) {
+        self.store(SATURATED32, Relaxed)
+    }
+    #[inline]
+    fn release(&self, n: usize) -> Option<usize> {
+        // See the implementation for `AtomicUsize`
+        let n = n as u32;
+        if n == 1 {
+            let old_size = self.fetch_sub(1, Release);
+            if old_size > SATURATION_FLOOR32 {
+                self.fetch_add(1, Relaxed);
+                return None;
+            }
+            return Some(old_size as usize);
+        }
+        let mut old_size = self.load(Relaxed);
+        loop {
+            if old_size > SATURATION_FLOOR32 {
+                return None;
+            }
+            match self.compare_exchange_weak(old_size, old_size.wrapping_sub(n), Release, Relaxed) {
+                Ok(_) => return Some(old_size as usize),
+                Err(current) => old_size = current,
+            }
+        }
+    }
+}
+
+/// What to do when incrementing a reference count would exceed the maximum number of references
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OverflowAction {
+    /// Abort the process. In `no_std` builds, this is implemented as a panic while panicking.
+    Abort,
+    /// Undo the increment and panic
+    Panic,
+    /// Pin the reference count at a saturated value, leaking the allocation forever. Dropping references to a
+    /// saturated allocation leaves its count as it is.
+    Saturate,
+}
+
+impl OverflowAction {
+    /// The action taken when no overflow hook is set, chosen at compile time.
+    ///
+    /// This is `Saturate` if the `overflow-saturate` feature is enabled, otherwise `Panic` if the
+    /// `overflow-panic` feature is enabled, and otherwise `Abort`.
+    pub const DEFAULT: OverflowAction = if cfg!(feature = "overflow-saturate") {
+        OverflowAction::Saturate
+    } else if cfg!(feature = "overflow-panic") {
+        OverflowAction::Panic
+    } else {
+        OverflowAction::Abort
+    };
+}
+
+impl Default for OverflowAction {
+    #[inline]
+    fn default() -> OverflowAction {
+        OverflowAction::DEFAULT
+    }
+}
+
+/// A hook called with the current reference count whenever a reference count overflows, returning the action to
+/// take
+pub type OverflowHook = fn(usize) -> OverflowAction;
+
+/// The current overflow hook, or null to use `OverflowAction::DEFAULT`
+static OVERFLOW_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Set the hook called whenever a reference count overflows, returning the previous hook.
+///
+/// The hook applies to every `Arc` in the program. Passing `None` restores the default action for this build,
+/// [`OverflowAction::DEFAULT`].
+///
+/// ```rust
+/// # use elysees::*;
+/// fn panic_on_overflow(_count: usize) -> OverflowAction {
+///     OverflowAction::Panic
+/// }
+/// let previous = set_overflow_hook(Some(panic_on_overflow));
+/// let arc = Arc::new(5);
+/// let overflow = std::panic::catch_unwind(|| Arc::increment_by(&arc, usize::MAX));
+/// assert!(overflow.is_err());
+/// assert!(arc.is_unique());
+/// set_overflow_hook(previous);
+/// ```
+pub fn set_overflow_hook(hook: Option<OverflowHook>) -> Option<OverflowHook> {
+    let hook = hook.map_or(ptr::null_mut(), |hook| hook as *mut ());
+    let previous = OVERFLOW_HOOK.swap(hook, Relaxed);
+    if previous.is_null() {
+        None
+    } else {
+        Some(unsafe { mem::transmute::<*mut (), OverflowHook>(previous) })
+    }
+}
+
+/// Handle a reference count overflow, given the number of references which were already added to `count`
+#[cold]
+#[inline(never)]
+pub(crate) fn refcount_overflow<C: RefCount>(count: &C, added: usize) {
+    let hook = OVERFLOW_HOOK.load(Relaxed);
+    let action = if hook.is_null() {
+        OverflowAction::DEFAULT
+    } else {
+        let hook = unsafe { mem::transmute::<*mut (), OverflowHook>(hook) };
+        hook(count.current())
+    };
+    match action {
+        OverflowAction::Abort => abort(),
+        OverflowAction::Panic => {
+            count.undo(added);
+            panic!("elysees: reference count overflow")
+        }
+        OverflowAction::Saturate => count.saturate(),
+    }
+}
@@ -0,0 +1,129 @@
+use crate::*;
+#[cfg(feature = "erasable")]
+use erasable::Thin;
+
+mod sealed {
+    /// Prevents `ArcPtr` from being implemented outside of this crate
+    pub trait Sealed {}
+}
+
+use sealed::Sealed;
+
+/// A pointer to a value stored in an `Arc`, abstracting over `Arc<T>`, `ArcBox<T>`, `ArcBorrow<'_, T>` and their
+/// `Thin` versions
+///
+/// This trait is sealed, as its provided methods rely on every implementation pointing into an `Arc` allocation.
+/// Like `Arc::count` and `Arc::as_ptr`, its methods are associated functions, so that they never shadow methods of
+/// `T` reached through `Deref`. Pointers which may share their `Arc` also implement [`SharedArcPtr`], which an
+/// `ArcBox<T>` cannot, as it must stay the only owner of its value.
+///
+/// ```rust
+/// # use elysees::{Arc, ArcBox, ArcPtr};
+/// fn describe<P: ArcPtr<str>>(pointer: &P) -> String {
+///     let count = P::count(pointer, core::sync::atomic::Ordering::Acquire);
+///     format!("{}: {}", P::get(pointer), count)
+/// }
+///
+/// let shared: Arc<str> = Arc::from("shared");
+/// let other = shared.clone();
+/// assert_eq!(describe(&shared), "shared: 2");
+/// assert_eq!(describe(&other.borrow_arc()), "shared: 2");
+/// let unique: ArcBox<str> = Arc::unique(Arc::from("unique"));
+/// assert_eq!(describe(&unique), "unique: 1");
+/// assert!(ArcPtr::is_unique(&unique));
+/// ```
+pub trait ArcPtr<T: ?Sized>: Sealed {
+    /// Get a raw pointer to the value
+    fn as_ptr(this: &Self) -> *const T;
+    /// Borrow the value
+    #[inline]
+    fn get(this: &Self) -> &T {
+        unsafe { &*Self::as_ptr(this) }
+    }
+    /// Get the reference count of the `Arc` this points into, using the given memory ordering
+    #[inline]
+    fn count(this: &Self, ordering: LoadOrdering) -> usize {
+        ArcBorrow::count(unsafe { ArcBorrow::from_raw(Self::as_ptr(this)) }, ordering)
+    }
+    /// Check whether the `Arc` this points into is uniquely owned
+    #[inline]
+    fn is_unique(this: &Self) -> bool {
+        // See `Arc::is_unique` for why this needs to be `Acquire`
+        Self::count(this, Acquire) == 1
+    }
+}
+
+/// A pointer to a value stored in an `Arc` which may be shared, i.e. any [`ArcPtr`] but an `ArcBox<T>`
+///
+/// ```rust
+/// # use elysees::{Arc, SharedArcPtr};
+/// fn keep<P: SharedArcPtr<[u8]>>(pointer: &P, kept: &mut Vec<Arc<[u8]>>) {
+///     if !P::borrow_arc(pointer).is_empty() {
+///         kept.push(P::clone_arc(pointer));
+///     }
+/// }
+///
+/// let mut kept = Vec::new();
+/// let bytes: Arc<[u8]> = Arc::from(&b"bytes"[..]);
+/// keep(&bytes, &mut kept);
+/// keep(&bytes.borrow_arc(), &mut kept);
+/// assert_eq!(Arc::count(&bytes, core::sync::atomic::Ordering::Relaxed), 3);
+/// ```
+pub trait SharedArcPtr<T: ?Sized>: ArcPtr<T> {
+    /// Borrow the `Arc` this points into
+    #[inline]
+    fn borrow_arc(this: &Self) -> ArcBorrow<'_, T> {
+        unsafe { ArcBorrow::from_raw(Self::as_ptr(this)) }
+    }
+    /// Clone the `Arc` this points into, bumping its reference count
+    #[inline]
+    fn clone_arc(this: &Self) -> Arc<T> {
+        Self::borrow_arc(this).clone_arc()
+    }
+}
+
+impl<T: ?Sized> Sealed for Arc<T> {}
+
+impl<T: ?Sized> ArcPtr<T> for Arc<T> {
+    #[inline]
+    fn as_ptr(this: &Self) -> *const T {
+        Arc::as_ptr(this)
+    }
+}
+
+impl<T: ?Sized> SharedArcPtr<T> for Arc<T> {}
+
+impl<T: ?Sized> Sealed for ArcBox<T> {}
+
+impl<T: ?Sized> ArcPtr<T> for ArcBox<T> {
+    #[inline]
+    fn as_ptr(this: &Self) -> *const T {
+        Arc::as_ptr(&this.0)
+    }
+}
+
+impl<'a, T: ?Sized> Sealed for ArcBorrow<'a, T> {}
+
+impl<'a, T: ?Sized> ArcPtr<T> for ArcBorrow<'a, T> {
+    #[inline]
+    fn as_ptr(this: &Self) -> *const T {
+        ArcBorrow::into_raw(*this)
+    }
+}
+
+impl<'a, T: ?Sized> SharedArcPtr<T> for ArcBorrow<'a, T> {}
+
+#[cfg(feature = "erasable")]
+impl<P: Sealed + ErasablePtr> Sealed for Thin<P> {}
+
+#[cfg(feature = "erasable")]
+impl<T: ?Sized, P: ArcPtr<T> + ErasablePtr> ArcPtr<T> for Thin<P> {
+    #[inline]
+    fn as_ptr(this: &Self) -> *const T {
+        // The pointer stays valid for as long as `this` keeps the allocation alive
+        Thin::with(this, |pointer| P::as_ptr(pointer))
+    }
+}
+
+#[cfg(feature = "erasable")]
+impl<T: ?Sized, P: SharedArcPtr<T> + ErasablePtr> SharedArcPtr<T> for Thin<P> {}
@@ -0,0 +1,498 @@
+use crate::radix::*;
+use crate::*;
+use core::ops::Index;
+use core::slice;
+
+/// The maximum number of elements in a leaf of an `ArcPVec`
+const CHUNK: usize = 32;
+
+/// The maximum number of children of a branch of an `ArcPVec`
+const BRANCH: usize = 32;
+
+/// A node of an `ArcPVec`
+enum Node<T> {
+    /// A chunk of between one and `CHUNK` elements
+    Leaf(Arc<[T]>),
+    /// Between one and `BRANCH` subtrees, all of the same height, along with their total number of elements
+    Branch {
+        len: usize,
+        children: Arc<[Node<T>]>,
+    },
+}
+
+impl<T> Clone for Node<T> {
+    #[inline]
+    fn clone(&self) -> Node<T> {
+        match self {
+            Node::Leaf(leaf) => Node::Leaf(leaf.clone()),
+            Node::Branch { len, children } => Node::Branch {
+                len: *len,
+                children: children.clone(),
+            },
+        }
+    }
+}
+
+impl<T> Node<T> {
+    /// Get the number of elements in this subtree
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(leaf) => leaf.len(),
+            Node::Branch { len, .. } => *len,
+        }
+    }
+}
+
+impl<T> Measured for Node<T> {
+    #[inline]
+    fn measure(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Clone> RadixNode for Node<T> {
+    const BRANCH: usize = BRANCH;
+
+    #[inline]
+    fn branch(children: ArcVec<Node<T>>) -> Node<T> {
+        debug_assert!(!children.is_empty() && children.len() <= BRANCH);
+        Node::Branch {
+            len: children.iter().map(Node::len).sum(),
+            children: children.freeze(),
+        }
+    }
+    #[inline]
+    fn children(&self) -> Option<&Arc<[Node<T>]>> {
+        match self {
+            Node::Branch { children, .. } => Some(children),
+            Node::Leaf(_) => None,
+        }
+    }
+    #[inline]
+    fn into_children(self) -> Arc<[Node<T>]> {
+        match self {
+            Node::Branch { children, .. } => children,
+            Node::Leaf(_) => unreachable!("leaves only appear at height zero"),
+        }
+    }
+    fn split_leaf(self, at: usize) -> (Node<T>, Node<T>) {
+        let mut left = match self {
+            Node::Leaf(leaf) => Arc::into_vec(leaf),
+            Node::Branch { .. } => unreachable!("only leaves are split here"),
+        };
+        let right = ArcVec::from(&left[at..]);
+        left.truncate(at);
+        (Node::Leaf(left.freeze()), Node::Leaf(right.freeze()))
+    }
+    fn concat_leaves(left: Node<T>, right: Node<T>) -> (Node<T>, Option<Node<T>>) {
+        let (left, right) = match (left, right) {
+            (Node::Leaf(left), Node::Leaf(right)) => (left, right),
+            _ => unreachable!("nodes at height zero are leaves"),
+        };
+        if left.len() + right.len() > CHUNK {
+            return (Node::Leaf(left), Some(Node::Leaf(right)));
+        }
+        let mut leaf = Arc::into_vec(left);
+        leaf.extend_from_slice(&right);
+        (Node::Leaf(leaf.freeze()), None)
+    }
+}
+
+impl<T: Clone> Node<T> {
+    /// Push `value` onto the end of this subtree, returning a new sibling of the same height if it is full
+    fn push(self, value: T) -> (Node<T>, Option<Node<T>>) {
+        match self {
+            Node::Leaf(leaf) if leaf.len() < CHUNK => {
+                let mut leaf = Arc::into_vec(leaf);
+                leaf.push(value);
+                (Node::Leaf(leaf.freeze()), None)
+            }
+            Node::Leaf(leaf) => (
+                Node::Leaf(leaf),
+                Some(Node::Leaf(Arc::new_clone(&[value][..]))),
+            ),
+            Node::Branch { len, children } => {
+                let mut children = Arc::into_vec(children);
+                let last = children.pop().expect("branches are nonempty");
+                let (last, sibling) = last.push(value);
+                children.push(last);
+                match sibling {
+                    None => (
+                        Node::Branch {
+                            len: len + 1,
+                            children: children.freeze(),
+                        },
+                        None,
+                    ),
+                    Some(sibling) if children.len() < BRANCH => {
+                        children.push(sibling);
+                        (
+                            Node::Branch {
+                                len: len + 1,
+                                children: children.freeze(),
+                            },
+                            None,
+                        )
+                    }
+                    Some(sibling) => {
+                        let mut parent = ArcVec::with_capacity(1);
+                        parent.push(sibling);
+                        (
+                            Node::Branch {
+                                len,
+                                children: children.freeze(),
+                            },
+                            Some(Node::branch(parent)),
+                        )
+                    }
+                }
+            }
+        }
+    }
+    /// Get a mutable reference to the element at `index`, which must be in bounds, copying shared nodes on the way
+    fn get_mut(&mut self, mut index: usize) -> &mut T {
+        let mut node = self;
+        loop {
+            match node {
+                Node::Leaf(leaf) => return &mut Arc::make_mut(leaf)[index],
+                Node::Branch { children, .. } => {
+                    let children = Arc::make_mut(children);
+                    let i = child_index(children, &mut index);
+                    node = &mut children[i];
+                }
+            }
+        }
+    }
+}
+
+/// A persistent vector built out of `Arc<[T]>` chunks
+///
+/// An `ArcPVec<T>` is a relaxed radix tree: elements are stored in leaves of up to 32 elements, which are gathered
+/// into branches of up to 32 subtrees. Cloning a vector is `O(1)`, since it just clones the `Arc` pointing to its
+/// root, and clones share all their nodes until they are modified. Indexing, [`set`](ArcPVec::set) and
+/// [`push_back`](ArcPVec::push_back) take `O(log n)` time, as do [`split_off`](ArcPVec::split_off) and
+/// [`append`](ArcPVec::append), which only rebuild the nodes along the seam.
+///
+/// Modifying a vector copies only the nodes on the path to the change which are shared with another vector; nodes
+/// which are uniquely owned are updated in place.
+///
+/// ```rust
+/// # use elysees::ArcPVec;
+/// let mut v: ArcPVec<u32> = (0..1000).collect();
+/// let snapshot = v.clone();
+/// v.set(500, 0);
+/// v.push_back(1000);
+/// assert_eq!(v[500], 0);
+/// assert_eq!(snapshot[500], 500);
+/// assert_eq!(snapshot.len(), 1000);
+///
+/// let mut tail = v.split_off(10);
+/// assert_eq!(v.len(), 10);
+/// assert_eq!(tail[0], 10);
+/// v.append(&mut tail);
+/// assert_eq!(v.len(), 1001);
+/// assert!(tail.is_empty());
+/// ```
+pub struct ArcPVec<T> {
+    root: Option<Node<T>>,
+    height: usize,
+}
+
+impl<T> ArcPVec<T> {
+    /// Create a new, empty vector
+    #[inline]
+    pub const fn new() -> ArcPVec<T> {
+        ArcPVec {
+            root: None,
+            height: 0,
+        }
+    }
+    /// Get the number of elements in this vector
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, Node::len)
+    }
+    /// Whether this vector is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+    /// Get a reference to the element at `index`, or `None` if it is out of bounds
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        if index >= node.len() {
+            return None;
+        }
+        loop {
+            match node {
+                Node::Leaf(leaf) => return Some(&leaf[index]),
+                Node::Branch { children, .. } => {
+                    node = &children[child_index(children, &mut index)]
+                }
+            }
+        }
+    }
+    /// Iterate over the elements of this vector
+    #[inline]
+    pub fn iter(&self) -> ArcPVecIter<'_, T> {
+        let mut stack = Vec::with_capacity(self.height + 1);
+        if let Some(root) = &self.root {
+            stack.push(slice::from_ref(root).iter())
+        }
+        ArcPVecIter {
+            stack,
+            leaf: [].iter(),
+            len: self.len(),
+        }
+    }
+    /// Whether two vectors share the same root, and hence are equal
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        match (&this.root, &other.root) {
+            (Some(Node::Leaf(this)), Some(Node::Leaf(other))) => Arc::ptr_eq(this, other),
+            (
+                Some(Node::Branch { children: this, .. }),
+                Some(Node::Branch {
+                    children: other, ..
+                }),
+            ) => Arc::ptr_eq(this, other),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Clone> ArcPVec<T> {
+    /// Append an element to the end of this vector
+    pub fn push_back(&mut self, value: T) {
+        let root = match self.root.take() {
+            Some(root) => root,
+            None => {
+                self.root = Some(Node::Leaf(Arc::new_clone(&[value][..])));
+                return;
+            }
+        };
+        let (root, sibling) = root.push(value);
+        self.root = Some(join_root(root, sibling, &mut self.height));
+    }
+    /// Get a mutable reference to the element at `index`, or `None` if it is out of bounds.
+    ///
+    /// Any nodes on the path to the element which are shared with another vector are copied.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match &mut self.root {
+            Some(root) if index < root.len() => Some(root.get_mut(index)),
+            _ => None,
+        }
+    }
+    /// Replace the element at `index` with `value`, returning the old element.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        let len = self.len();
+        match self.get_mut(index) {
+            Some(element) => mem::replace(element, value),
+            None => panic!(
+                "index {} out of bounds for ArcPVec of length {}",
+                index, len
+            ),
+        }
+    }
+    /// Split this vector in two at `at`, leaving the elements before `at` in `self` and returning the rest.
+    ///
+    /// # Panics
+    /// Panics if `at` is greater than the length of this vector.
+    pub fn split_off(&mut self, at: usize) -> ArcPVec<T> {
+        let len = self.len();
+        if at > len {
+            panic!(
+                "split index {} out of bounds for ArcPVec of length {}",
+                at, len
+            )
+        }
+        let (left, right) = match self.root.take() {
+            Some(root) => split(root, at),
+            None => (None, None),
+        };
+        let height = self.height;
+        let make = |root: Option<Node<T>>| match root {
+            Some(root) => {
+                let (root, height) = trim(root, height);
+                ArcPVec {
+                    root: Some(root),
+                    height,
+                }
+            }
+            None => ArcPVec::new(),
+        };
+        *self = make(left);
+        make(right)
+    }
+    /// Move all the elements of `other` onto the end of this vector, leaving `other` empty
+    pub fn append(&mut self, other: &mut ArcPVec<T>) {
+        let other = mem::take(other);
+        let (left, right) = match (self.root.take(), other.root) {
+            (Some(left), Some(right)) => (left, right),
+            (None, right) => {
+                self.root = right;
+                self.height = other.height;
+                return;
+            }
+            (left, None) => {
+                self.root = left;
+                return;
+            }
+        };
+        let (root, sibling) = concat(left, self.height, right, other.height);
+        self.height = self.height.max(other.height);
+        self.root = Some(join_root(root, sibling, &mut self.height));
+    }
+}
+
+impl<T> Clone for ArcPVec<T> {
+    #[inline]
+    fn clone(&self) -> ArcPVec<T> {
+        ArcPVec {
+            root: self.root.clone(),
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Default for ArcPVec<T> {
+    #[inline]
+    fn default() -> ArcPVec<T> {
+        ArcPVec::new()
+    }
+}
+
+impl<T> Index<usize> for ArcPVec<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(element) => element,
+            None => panic!(
+                "index {} out of bounds for ArcPVec of length {}",
+                index,
+                self.len()
+            ),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcPVec<T> {
+    fn eq(&self, other: &ArcPVec<T>) -> bool {
+        ArcPVec::ptr_eq(self, other) || (self.len() == other.len() && self.iter().eq(other.iter()))
+    }
+}
+
+impl<T: Eq> Eq for ArcPVec<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArcPVec<T> {
+    fn partial_cmp(&self, other: &ArcPVec<T>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for ArcPVec<T> {
+    fn cmp(&self, other: &ArcPVec<T>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for ArcPVec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for value in self {
+            value.hash(state)
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcPVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: Clone> Extend<T> for ArcPVec<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value)
+        }
+    }
+}
+
+impl<T: Clone> FromIterator<T> for ArcPVec<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut result = ArcPVec::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArcPVec<T> {
+    type Item = &'a T;
+    type IntoIter = ArcPVecIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> ArcPVecIter<'a, T> {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of an `ArcPVec`
+pub struct ArcPVecIter<'a, T> {
+    /// The unvisited nodes at each level above the current leaf
+    stack: Vec<slice::Iter<'a, Node<T>>>,
+    /// The unvisited elements of the current leaf
+    leaf: slice::Iter<'a, T>,
+    len: usize,
+}
+
+impl<'a, T> Clone for ArcPVecIter<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ArcPVecIter {
+            stack: self.stack.clone(),
+            leaf: self.leaf.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for ArcPVecIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(value) = self.leaf.next() {
+                self.len -= 1;
+                return Some(value);
+            }
+            match self.stack.last_mut()?.next() {
+                Some(Node::Leaf(leaf)) => self.leaf = leaf.iter(),
+                Some(Node::Branch { children, .. }) => self.stack.push(children.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ArcPVecIter<'a, T> {}
+
+impl<'a, T> FusedIterator for ArcPVecIter<'a, T> {}
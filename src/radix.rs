@@ -0,0 +1,138 @@
+use crate::*;
+
+/// A subtree of a relaxed radix tree, whose length is measured in the units it is indexed by
+pub(crate) trait Measured {
+    /// Get the length of this subtree
+    fn measure(&self) -> usize;
+}
+
+/// A node of a relaxed radix tree: either a nonempty leaf, or a branch of between one and `BRANCH` subtrees, all of
+/// the same height
+pub(crate) trait RadixNode: Measured + Clone {
+    /// The maximum number of children of a branch
+    const BRANCH: usize;
+    /// Create a branch from a nonempty list of children
+    fn branch(children: ArcVec<Self>) -> Self;
+    /// Get the children of a branch, or `None` for a leaf
+    fn children(&self) -> Option<&Arc<[Self]>>;
+    /// Get the children of a branch
+    fn into_children(self) -> Arc<[Self]>;
+    /// Split a leaf in two at `at`, which is strictly between its start and end
+    fn split_leaf(self, at: usize) -> (Self, Self);
+    /// Concatenate two leaves, returning the second as a sibling if they do not fit into one
+    fn concat_leaves(left: Self, right: Self) -> (Self, Option<Self>);
+}
+
+/// Find the child containing unit `index`, making `index` relative to that child
+#[inline]
+pub(crate) fn child_index<N: Measured>(children: &[N], index: &mut usize) -> usize {
+    for (i, child) in children.iter().enumerate() {
+        let len = child.measure();
+        if *index < len {
+            return i;
+        }
+        *index -= len;
+    }
+    unreachable!("index checked against the length of the subtree")
+}
+
+/// Split a subtree into the units before and after `at`, either of which may be empty
+pub(crate) fn split<N: RadixNode>(node: N, at: usize) -> (Option<N>, Option<N>) {
+    if at == 0 {
+        return (None, Some(node));
+    }
+    if at >= node.measure() {
+        return (Some(node), None);
+    }
+    if node.children().is_none() {
+        let (left, right) = node.split_leaf(at);
+        return (Some(left), Some(right));
+    }
+    let mut left = Arc::into_vec(node.into_children());
+    let mut index = at;
+    let i = child_index(&left, &mut index);
+    let mut right = ArcVec::with_capacity(left.len() - i);
+    let rest = left[i + 1..].to_vec();
+    left.truncate(i + 1);
+    let (child_left, child_right) = split(left.pop().expect("child was found"), index);
+    left.extend(child_left);
+    right.extend(child_right);
+    right.extend(rest);
+    (
+        (!left.is_empty()).then(|| N::branch(left)),
+        (!right.is_empty()).then(|| N::branch(right)),
+    )
+}
+
+/// Concatenate two subtrees of the given heights, returning a subtree of the greater height along with a sibling of
+/// the same height if everything does not fit into one node.
+///
+/// Leaves along the seam are merged whenever they fit, so that repeated small appends and edits do not build up
+/// sparse nodes.
+pub(crate) fn concat<N: RadixNode>(
+    left: N,
+    left_height: usize,
+    right: N,
+    right_height: usize,
+) -> (N, Option<N>) {
+    if left_height == 0 && right_height == 0 {
+        return N::concat_leaves(left, right);
+    }
+    // Descend along the right edge of the left subtree and the left edge of the right subtree until both are at
+    // the same height, then merge the nodes on either side of the seam
+    let (mut children, left, left_height) = if left_height >= right_height {
+        let mut children = Arc::into_vec(left.into_children());
+        let last = children.pop().expect("branches are nonempty");
+        (children, last, left_height - 1)
+    } else {
+        (ArcVec::with_capacity(N::BRANCH), left, left_height)
+    };
+    let (right, rest, right_height) = if right_height > left_height {
+        let rest = right.into_children();
+        (rest[0].clone(), Some(rest), right_height - 1)
+    } else {
+        (right, None, right_height)
+    };
+    let (merged, sibling) = concat(left, left_height, right, right_height);
+    children.push(merged);
+    children.extend(sibling);
+    if let Some(rest) = rest {
+        children.extend_from_slice(&rest[1..]);
+    }
+    if children.len() <= N::BRANCH {
+        return (N::branch(children), None);
+    }
+    let half = children.len() / 2;
+    let right = ArcVec::from(&children[half..]);
+    children.truncate(half);
+    (N::branch(children), Some(N::branch(right)))
+}
+
+/// Make the root of a tree of height `height` out of a subtree and the sibling it overflowed into, if any, adding a
+/// level above them if needed
+#[inline]
+pub(crate) fn join_root<N: RadixNode>(root: N, sibling: Option<N>, height: &mut usize) -> N {
+    match sibling {
+        None => root,
+        Some(sibling) => {
+            let mut children = ArcVec::with_capacity(2);
+            children.push(root);
+            children.push(sibling);
+            *height += 1;
+            N::branch(children)
+        }
+    }
+}
+
+/// Remove branches with a single child from the top of a tree of the given height
+#[inline]
+pub(crate) fn trim<N: RadixNode>(mut root: N, mut height: usize) -> (N, usize) {
+    while let Some(children) = root.children() {
+        if children.len() != 1 {
+            break;
+        }
+        root = children[0].clone();
+        height -= 1;
+    }
+    (root, height)
+}
@@ -0,0 +1,674 @@
+use crate::radix::*;
+use crate::*;
+use core::iter::FlatMap;
+use core::ops::{AddAssign, Bound, RangeBounds};
+use core::slice;
+use core::str::Chars;
+
+/// The maximum number of bytes in a leaf of an `ArcRope`
+const MAX_LEAF: usize = 512;
+
+/// The maximum number of children of a branch of an `ArcRope`
+const BRANCH: usize = 16;
+
+/// The lengths of a piece of text, in each of the units an `ArcRope` can be indexed by
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+struct Metrics {
+    bytes: usize,
+    chars: usize,
+    newlines: usize,
+}
+
+impl Metrics {
+    /// Measure a string
+    #[inline]
+    fn of(text: &str) -> Metrics {
+        Metrics {
+            bytes: text.len(),
+            chars: text.chars().count(),
+            newlines: text.bytes().filter(|&byte| byte == b'\n').count(),
+        }
+    }
+}
+
+impl AddAssign for Metrics {
+    #[inline]
+    fn add_assign(&mut self, other: Metrics) {
+        self.bytes += other.bytes;
+        self.chars += other.chars;
+        self.newlines += other.newlines;
+    }
+}
+
+/// A node of an `ArcRope`
+#[derive(Clone)]
+enum Node {
+    /// Between one and `MAX_LEAF` bytes of text
+    Leaf { metrics: Metrics, text: Arc<str> },
+    /// Between one and `BRANCH` subtrees, all of the same height
+    Branch {
+        metrics: Metrics,
+        children: Arc<[Node]>,
+    },
+}
+
+impl Node {
+    /// Create a leaf holding `text`
+    #[inline]
+    fn leaf(text: &str) -> Node {
+        Node::Leaf {
+            metrics: Metrics::of(text),
+            text: Arc::from(text),
+        }
+    }
+    /// Get the lengths of the text in this subtree
+    #[inline]
+    fn metrics(&self) -> Metrics {
+        match self {
+            Node::Leaf { metrics, .. } | Node::Branch { metrics, .. } => *metrics,
+        }
+    }
+    /// Build a tree holding `text`, returning its root and height
+    fn build(text: &str) -> Option<(Node, usize)> {
+        let mut level = Vec::with_capacity(text.len() / MAX_LEAF + 1);
+        let mut rest = text;
+        while !rest.is_empty() {
+            let mut end = rest.len().min(MAX_LEAF);
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            level.push(Node::leaf(&rest[..end]));
+            rest = &rest[end..];
+        }
+        let mut height = 0;
+        while level.len() > 1 {
+            level = level
+                .chunks(BRANCH)
+                .map(|children| Node::branch(ArcVec::from(children)))
+                .collect();
+            height += 1;
+        }
+        level.pop().map(|root| (root, height))
+    }
+}
+
+impl Measured for Node {
+    #[inline]
+    fn measure(&self) -> usize {
+        self.metrics().bytes
+    }
+}
+
+impl RadixNode for Node {
+    const BRANCH: usize = BRANCH;
+
+    #[inline]
+    fn branch(children: ArcVec<Node>) -> Node {
+        debug_assert!(!children.is_empty() && children.len() <= BRANCH);
+        let mut metrics = Metrics::default();
+        for child in children.iter() {
+            metrics += child.metrics()
+        }
+        Node::Branch {
+            metrics,
+            children: children.freeze(),
+        }
+    }
+    #[inline]
+    fn children(&self) -> Option<&Arc<[Node]>> {
+        match self {
+            Node::Branch { children, .. } => Some(children),
+            Node::Leaf { .. } => None,
+        }
+    }
+    #[inline]
+    fn into_children(self) -> Arc<[Node]> {
+        match self {
+            Node::Branch { children, .. } => children,
+            Node::Leaf { .. } => unreachable!("leaves only appear at height zero"),
+        }
+    }
+    #[inline]
+    fn split_leaf(self, at: usize) -> (Node, Node) {
+        match self {
+            Node::Leaf { text, .. } => (Node::leaf(&text[..at]), Node::leaf(&text[at..])),
+            Node::Branch { .. } => unreachable!("only leaves are split here"),
+        }
+    }
+    fn concat_leaves(left: Node, right: Node) -> (Node, Option<Node>) {
+        let (left_text, right_text) = match (&left, &right) {
+            (Node::Leaf { text: left, .. }, Node::Leaf { text: right, .. }) => (left, right),
+            _ => unreachable!("nodes at height zero are leaves"),
+        };
+        if left_text.len() + right_text.len() > MAX_LEAF {
+            return (left, Some(right));
+        }
+        let mut text = String::with_capacity(left_text.len() + right_text.len());
+        text.push_str(left_text);
+        text.push_str(right_text);
+        let mut metrics = left.metrics();
+        metrics += right.metrics();
+        let leaf = Node::Leaf {
+            metrics,
+            text: Arc::from(text),
+        };
+        (leaf, None)
+    }
+}
+
+/// A persistent string built out of `Arc<str>` chunks, for cheaply sharing and editing large texts
+///
+/// An `ArcRope` is a balanced tree whose leaves hold up to 512 bytes of text each, and whose branches cache the
+/// number of bytes, `char`s and lines below them. Cloning a rope is `O(1)`, so snapshots can be taken freely and
+/// sent to other threads, and clones share all their nodes until they are edited.
+/// [`insert`](ArcRope::insert), [`remove`](ArcRope::remove) and [`slice`](ArcRope::slice) take `O(log n)` time,
+/// as does converting between byte, `char` and line indices.
+///
+/// Like `String`, edits are addressed by byte offset, and panic if an offset is not on a `char` boundary. Lines are
+/// separated by `'\n'`, so a rope with `n` newlines has `n + 1` lines.
+///
+/// ```rust
+/// # use elysees::ArcRope;
+/// let mut rope = ArcRope::from("fn main() {\n}\n");
+/// let snapshot = rope.clone();
+/// rope.insert(12, "    println!(\"héllo\");\n");
+/// assert_eq!(rope.to_string(), "fn main() {\n    println!(\"héllo\");\n}\n");
+/// assert_eq!(snapshot.to_string(), "fn main() {\n}\n");
+///
+/// assert_eq!(rope.len_lines(), 4);
+/// assert_eq!(rope.line(1), "    println!(\"héllo\");\n");
+/// assert_eq!(rope.line_to_byte(2), 36);
+/// assert_eq!(rope.byte_to_char(36), 35);
+/// assert_eq!(rope.char(27), 'é');
+///
+/// rope.remove(12..36);
+/// assert_eq!(rope, snapshot);
+/// ```
+#[derive(Clone)]
+pub struct ArcRope {
+    root: Option<Node>,
+    height: usize,
+}
+
+impl ArcRope {
+    /// Create a new, empty rope
+    #[inline]
+    pub const fn new() -> ArcRope {
+        ArcRope {
+            root: None,
+            height: 0,
+        }
+    }
+    /// Get the lengths of the text in this rope
+    #[inline]
+    fn metrics(&self) -> Metrics {
+        self.root.as_ref().map(Node::metrics).unwrap_or_default()
+    }
+    /// Get the length of this rope in bytes
+    #[inline]
+    pub fn len_bytes(&self) -> usize {
+        self.metrics().bytes
+    }
+    /// Get the length of this rope in `char`s
+    #[inline]
+    pub fn len_chars(&self) -> usize {
+        self.metrics().chars
+    }
+    /// Get the number of lines in this rope, which is one more than the number of newlines
+    #[inline]
+    pub fn len_lines(&self) -> usize {
+        self.metrics().newlines + 1
+    }
+    /// Whether this rope is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+    /// Find the leaf containing unit `index`, as measured by `metric`, returning its text along with the lengths of
+    /// all the text before it
+    fn locate(&self, mut index: usize, metric: fn(&Metrics) -> usize) -> Option<(&str, Metrics)> {
+        let mut node = self.root.as_ref()?;
+        if index >= metric(&node.metrics()) {
+            return None;
+        }
+        let mut before = Metrics::default();
+        loop {
+            match node {
+                Node::Leaf { text, .. } => return Some((text, before)),
+                Node::Branch { children, .. } => {
+                    for child in children.iter() {
+                        let metrics = child.metrics();
+                        if index < metric(&metrics) {
+                            node = child;
+                            break;
+                        }
+                        index -= metric(&metrics);
+                        before += metrics;
+                    }
+                }
+            }
+        }
+    }
+    /// Get the byte at byte offset `byte`.
+    ///
+    /// # Panics
+    /// Panics if `byte` is out of bounds.
+    #[inline]
+    pub fn byte(&self, byte: usize) -> u8 {
+        match self.locate(byte, |metrics| metrics.bytes) {
+            Some((text, before)) => text.as_bytes()[byte - before.bytes],
+            None => panic!(
+                "byte index {} out of bounds for ArcRope of length {}",
+                byte,
+                self.len_bytes()
+            ),
+        }
+    }
+    /// Get the `char` at `char` index `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn char(&self, index: usize) -> char {
+        match self.locate(index, |metrics| metrics.chars) {
+            Some((text, before)) => text
+                .chars()
+                .nth(index - before.chars)
+                .expect("char is in this leaf"),
+            None => panic!(
+                "char index {} out of bounds for ArcRope of length {}",
+                index,
+                self.len_chars()
+            ),
+        }
+    }
+    /// Convert a byte offset into the index of the `char` starting there.
+    ///
+    /// # Panics
+    /// Panics if `byte` is greater than the length of this rope or not on a `char` boundary.
+    pub fn byte_to_char(&self, byte: usize) -> usize {
+        match self.locate(byte, |metrics| metrics.bytes) {
+            Some((text, before)) => before.chars + text[..byte - before.bytes].chars().count(),
+            None if byte == self.len_bytes() => self.len_chars(),
+            None => panic!(
+                "byte index {} out of bounds for ArcRope of length {}",
+                byte,
+                self.len_bytes()
+            ),
+        }
+    }
+    /// Convert a `char` index into the byte offset at which that `char` starts.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the length of this rope in `char`s.
+    pub fn char_to_byte(&self, index: usize) -> usize {
+        match self.locate(index, |metrics| metrics.chars) {
+            Some((text, before)) => {
+                let (offset, _) = text
+                    .char_indices()
+                    .nth(index - before.chars)
+                    .expect("char is in this leaf");
+                before.bytes + offset
+            }
+            None if index == self.len_chars() => self.len_bytes(),
+            None => panic!(
+                "char index {} out of bounds for ArcRope of length {}",
+                index,
+                self.len_chars()
+            ),
+        }
+    }
+    /// Convert a byte offset into the index of the line containing it.
+    ///
+    /// # Panics
+    /// Panics if `byte` is greater than the length of this rope.
+    pub fn byte_to_line(&self, byte: usize) -> usize {
+        match self.locate(byte, |metrics| metrics.bytes) {
+            Some((text, before)) => {
+                let newlines = text.as_bytes()[..byte - before.bytes]
+                    .iter()
+                    .filter(|&&byte| byte == b'\n')
+                    .count();
+                before.newlines + newlines
+            }
+            None if byte == self.len_bytes() => self.len_lines() - 1,
+            None => panic!(
+                "byte index {} out of bounds for ArcRope of length {}",
+                byte,
+                self.len_bytes()
+            ),
+        }
+    }
+    /// Convert a line index into the byte offset at which that line starts.
+    ///
+    /// # Panics
+    /// Panics if `line` is not less than the number of lines in this rope.
+    pub fn line_to_byte(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+        // Line `n` starts just after the `n`th newline
+        match self.locate(line - 1, |metrics| metrics.newlines) {
+            Some((text, before)) => {
+                let (offset, _) = text
+                    .bytes()
+                    .enumerate()
+                    .filter(|&(_, byte)| byte == b'\n')
+                    .nth(line - 1 - before.newlines)
+                    .expect("newline is in this leaf");
+                before.bytes + offset + 1
+            }
+            None => panic!(
+                "line index {} out of bounds for ArcRope with {} lines",
+                line,
+                self.len_lines()
+            ),
+        }
+    }
+    /// Convert a `char` index into the index of the line containing it.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the length of this rope in `char`s.
+    #[inline]
+    pub fn char_to_line(&self, index: usize) -> usize {
+        self.byte_to_line(self.char_to_byte(index))
+    }
+    /// Convert a line index into the index of the `char` at which that line starts.
+    ///
+    /// # Panics
+    /// Panics if `line` is not less than the number of lines in this rope.
+    #[inline]
+    pub fn line_to_char(&self, line: usize) -> usize {
+        self.byte_to_char(self.line_to_byte(line))
+    }
+    /// Whether byte offset `byte` is at the start or end of a `char`, as for `str::is_char_boundary`
+    pub fn is_char_boundary(&self, byte: usize) -> bool {
+        match self.locate(byte, |metrics| metrics.bytes) {
+            Some((text, before)) => text.is_char_boundary(byte - before.bytes),
+            None => byte == self.len_bytes(),
+        }
+    }
+    /// Panic if `byte` is out of bounds or not on a `char` boundary
+    #[inline]
+    fn check_boundary(&self, byte: usize) {
+        if !self.is_char_boundary(byte) {
+            panic!(
+                "byte index {} is out of bounds or not a char boundary in ArcRope of length {}",
+                byte,
+                self.len_bytes()
+            )
+        }
+    }
+    /// Split this rope in two at byte offset `at`, leaving the text before `at` in `self` and returning the rest.
+    ///
+    /// # Panics
+    /// Panics if `at` is greater than the length of this rope or not on a `char` boundary.
+    pub fn split_off(&mut self, at: usize) -> ArcRope {
+        self.check_boundary(at);
+        let (left, right) = match self.root.take() {
+            Some(root) => split(root, at),
+            None => (None, None),
+        };
+        let height = self.height;
+        let make = |root: Option<Node>| match root {
+            Some(root) => {
+                let (root, height) = trim(root, height);
+                ArcRope {
+                    root: Some(root),
+                    height,
+                }
+            }
+            None => ArcRope::new(),
+        };
+        *self = make(left);
+        make(right)
+    }
+    /// Move all the text of `other` onto the end of this rope, leaving `other` empty
+    pub fn append(&mut self, other: &mut ArcRope) {
+        let other = mem::take(other);
+        let (left, right) = match (self.root.take(), other.root) {
+            (Some(left), Some(right)) => (left, right),
+            (None, right) => {
+                self.root = right;
+                self.height = other.height;
+                return;
+            }
+            (left, None) => {
+                self.root = left;
+                return;
+            }
+        };
+        let (root, sibling) = concat(left, self.height, right, other.height);
+        self.height = self.height.max(other.height);
+        self.root = Some(join_root(root, sibling, &mut self.height));
+    }
+    /// Append `text` to the end of this rope
+    #[inline]
+    pub fn push_str(&mut self, text: &str) {
+        self.append(&mut ArcRope::from(text))
+    }
+    /// Insert `text` at byte offset `at`.
+    ///
+    /// # Panics
+    /// Panics if `at` is greater than the length of this rope or not on a `char` boundary.
+    pub fn insert(&mut self, at: usize, text: &str) {
+        let mut right = self.split_off(at);
+        self.push_str(text);
+        self.append(&mut right);
+    }
+    /// Remove the text in the byte range `range`.
+    ///
+    /// # Panics
+    /// Panics if either end of `range` is out of bounds or not on a `char` boundary, or if its start is after its
+    /// end.
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = self.range(range);
+        let mut right = self.split_off(end);
+        self.split_off(start);
+        self.append(&mut right);
+    }
+    /// Get the text in the byte range `range`, sharing it with this rope.
+    ///
+    /// # Panics
+    /// Panics if either end of `range` is out of bounds or not on a `char` boundary, or if its start is after its
+    /// end.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> ArcRope {
+        let (start, end) = self.range(range);
+        let mut result = self.clone();
+        result.split_off(end);
+        result.split_off(start)
+    }
+    /// Get the line at index `line`, including its trailing newline, if any.
+    ///
+    /// # Panics
+    /// Panics if `line` is not less than the number of lines in this rope.
+    pub fn line(&self, line: usize) -> ArcRope {
+        let start = self.line_to_byte(line);
+        let end = if line + 1 < self.len_lines() {
+            self.line_to_byte(line + 1)
+        } else {
+            self.len_bytes()
+        };
+        self.slice(start..end)
+    }
+    /// Resolve a byte range into its start and end, which are checked to be in order and on `char` boundaries
+    fn range<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1).expect("byte range out of bounds"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).expect("byte range out of bounds"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len_bytes(),
+        };
+        if start > end {
+            panic!("byte range starts at {} but ends at {}", start, end)
+        }
+        self.check_boundary(start);
+        self.check_boundary(end);
+        (start, end)
+    }
+    /// Iterate over the chunks of text making up this rope, in order
+    #[inline]
+    pub fn chunks(&self) -> ArcRopeChunks<'_> {
+        let mut stack = Vec::with_capacity(self.height + 1);
+        if let Some(root) = &self.root {
+            stack.push(slice::from_ref(root).iter())
+        }
+        ArcRopeChunks { stack }
+    }
+    /// Iterate over the `char`s of this rope
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn chars(&self) -> FlatMap<ArcRopeChunks<'_>, Chars<'_>, fn(&str) -> Chars<'_>> {
+        self.chunks().flat_map(str::chars)
+    }
+    /// Whether two ropes share the same root, and hence are equal
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        match (&this.root, &other.root) {
+            (Some(Node::Leaf { text: this, .. }), Some(Node::Leaf { text: other, .. })) => {
+                Arc::ptr_eq(this, other)
+            }
+            (
+                Some(Node::Branch { children: this, .. }),
+                Some(Node::Branch {
+                    children: other, ..
+                }),
+            ) => Arc::ptr_eq(this, other),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+    /// Compare the bytes of this rope with those of a sequence of chunks
+    fn eq_chunks<'a, I: Iterator<Item = &'a str>>(&self, len: usize, chunks: I) -> bool {
+        self.len_bytes() == len
+            && self
+                .chunks()
+                .flat_map(str::bytes)
+                .eq(chunks.flat_map(str::bytes))
+    }
+}
+
+impl Default for ArcRope {
+    #[inline]
+    fn default() -> ArcRope {
+        ArcRope::new()
+    }
+}
+
+impl From<&str> for ArcRope {
+    #[inline]
+    fn from(text: &str) -> ArcRope {
+        match Node::build(text) {
+            Some((root, height)) => ArcRope {
+                root: Some(root),
+                height,
+            },
+            None => ArcRope::new(),
+        }
+    }
+}
+
+impl From<String> for ArcRope {
+    #[inline]
+    fn from(text: String) -> ArcRope {
+        ArcRope::from(&*text)
+    }
+}
+
+impl From<&ArcRope> for String {
+    #[inline]
+    fn from(rope: &ArcRope) -> String {
+        let mut result = String::with_capacity(rope.len_bytes());
+        result.extend(rope.chunks());
+        result
+    }
+}
+
+impl From<ArcRope> for String {
+    #[inline]
+    fn from(rope: ArcRope) -> String {
+        String::from(&rope)
+    }
+}
+
+impl PartialEq for ArcRope {
+    fn eq(&self, other: &ArcRope) -> bool {
+        ArcRope::ptr_eq(self, other) || self.eq_chunks(other.len_bytes(), other.chunks())
+    }
+}
+
+impl Eq for ArcRope {}
+
+impl PartialEq<str> for ArcRope {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.eq_chunks(other.len(), core::iter::once(other))
+    }
+}
+
+impl PartialEq<&str> for ArcRope {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialOrd for ArcRope {
+    #[inline]
+    fn partial_cmp(&self, other: &ArcRope) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ArcRope {
+    fn cmp(&self, other: &ArcRope) -> Ordering {
+        // Byte order is the same as `char` order for UTF-8
+        self.chunks()
+            .flat_map(str::bytes)
+            .cmp(other.chunks().flat_map(str::bytes))
+    }
+}
+
+impl fmt::Display for ArcRope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ArcRope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&String::from(self), f)
+    }
+}
+
+/// An iterator over the chunks of text making up an `ArcRope`
+#[derive(Clone)]
+pub struct ArcRopeChunks<'a> {
+    /// The unvisited nodes at each level of the tree
+    stack: Vec<slice::Iter<'a, Node>>,
+}
+
+impl<'a> Iterator for ArcRopeChunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(Node::Leaf { text, .. }) => return Some(text),
+                Some(Node::Branch { children, .. }) => self.stack.push(children.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for ArcRopeChunks<'a> {}
@@ -0,0 +1,179 @@
+/*!
+Serialization of `Arc`s which preserves sharing.
+
+By default, serializing an `Arc<T>` writes out a full copy of the `T` each time it is encountered, so that two
+clones of the same `Arc` are deserialized into two separate allocations. The helpers in this module instead write
+each allocation only once, tagged with an ID, and write just the ID for every later occurrence, restoring the
+original sharing on deserialization.
+
+Both serialization and deserialization must be performed within a [`scope`], which holds the table of allocations
+seen so far. Fields can opt in using `#[serde(with = "elysees::serde_shared")]`, or by wrapping the `Arc` in a
+[`Shared`]:
+
+```rust
+# use elysees::Arc;
+# use elysees::serde_shared::{self, Shared};
+let value = Arc::new(String::from("shared"));
+let values = vec![Shared(value.clone()), Shared(value.clone())];
+let json = serde_shared::scope(|| serde_json::to_string(&values)).unwrap();
+assert_eq!(json, r#"[[0,"shared"],[0,null]]"#);
+let loaded: Vec<Shared<String>> = serde_shared::scope(|| serde_json::from_str(&json)).unwrap();
+assert!(Arc::ptr_eq(&loaded[0].0, &loaded[1].0));
+```
+*/
+use crate::*;
+use serde::de::{Deserializer, Error as _};
+use serde::ser::{Error as _, Serializer};
+use std::any::Any;
+use std::boxed::Box;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+/// The table of allocations seen within a `scope`
+#[derive(Default)]
+struct Context {
+    /// The IDs assigned to serialized allocations, keyed by address
+    ids: HashMap<usize, u64>,
+    /// Clones of every serialized `Arc`, ensuring their addresses are not reused within the scope
+    keep_alive: Vec<Box<dyn Any>>,
+    /// The `Arc`s deserialized so far, keyed by ID
+    loaded: HashMap<u64, Box<dyn Any>>,
+}
+
+/// Run `f` with a shared serialization context, which is cleared once the outermost `scope` returns.
+///
+/// Nested calls reuse the enclosing context.
+pub fn scope<R>(f: impl FnOnce() -> R) -> R {
+    struct ClearOnExit;
+
+    impl Drop for ClearOnExit {
+        fn drop(&mut self) {
+            // Take the context out first, so that dropping its contents cannot observe a borrowed `CONTEXT`
+            let context = CONTEXT.with(|context| context.borrow_mut().take());
+            mem::drop(context)
+        }
+    }
+
+    let outermost = CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        if context.is_some() {
+            false
+        } else {
+            *context = Some(Context::default());
+            true
+        }
+    });
+    let _guard = if outermost { Some(ClearOnExit) } else { None };
+    f()
+}
+
+/// Serialize an `Arc<T>`, writing its contents only the first time its allocation is encountered in this `scope`.
+///
+/// # Errors
+/// Returns an error if called outside of a `scope`
+pub fn serialize<T, S>(arc: &Arc<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + Serialize + 'static,
+    S: Serializer,
+{
+    let address = Arc::as_ptr(arc) as *const u8 as usize;
+    let seen = CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let context = context.as_mut()?;
+        if let Some(&id) = context.ids.get(&address) {
+            return Some((id, true));
+        }
+        let id = context.ids.len() as u64;
+        context.ids.insert(address, id);
+        context.keep_alive.push(Box::new(arc.clone()));
+        Some((id, false))
+    });
+    match seen {
+        None => Err(S::Error::custom(
+            "shared Arc serialized outside of a serde_shared::scope",
+        )),
+        Some((id, true)) => (id, None::<&T>).serialize(serializer),
+        Some((id, false)) => (id, Some(&**arc)).serialize(serializer),
+    }
+}
+
+/// Deserialize an `Arc<T>` written by [`serialize`], sharing allocations with the same ID in this `scope`.
+///
+/// # Errors
+/// Returns an error if called outside of a `scope`, or if an ID is referenced before its contents or with a
+/// different type.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Arc<T>, D::Error>
+where
+    T: ?Sized + 'static,
+    Arc<T>: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let (id, value): (u64, Option<Arc<T>>) = Deserialize::deserialize(deserializer)?;
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        let context = context.as_mut().ok_or_else(|| {
+            D::Error::custom("shared Arc deserialized outside of a serde_shared::scope")
+        })?;
+        match value {
+            Some(arc) => {
+                context.loaded.insert(id, Box::new(arc.clone()));
+                Ok(arc)
+            }
+            None => match context.loaded.get(&id) {
+                Some(arc) => arc.downcast_ref::<Arc<T>>().cloned().ok_or_else(|| {
+                    D::Error::custom(format_args!("shared Arc {} has a different type", id))
+                }),
+                None => Err(D::Error::custom(format_args!(
+                    "shared Arc {} referenced before its definition",
+                    id
+                ))),
+            },
+        }
+    })
+}
+
+/// A wrapper around an `Arc<T>` which is (de)serialized with [`serialize`] and [`deserialize`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Shared<T: ?Sized>(pub Arc<T>);
+
+impl<T: ?Sized> From<Arc<T>> for Shared<T> {
+    #[inline]
+    fn from(arc: Arc<T>) -> Shared<T> {
+        Shared(arc)
+    }
+}
+
+impl<T: ?Sized> Deref for Shared<T> {
+    type Target = Arc<T>;
+
+    #[inline]
+    fn deref(&self) -> &Arc<T> {
+        &self.0
+    }
+}
+
+impl<T: ?Sized + Serialize + 'static> Serialize for Shared<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T: ?Sized + 'static> Deserialize<'de> for Shared<T>
+where
+    Arc<T>: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Shared<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer).map(Shared)
+    }
+}
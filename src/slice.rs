@@ -0,0 +1,468 @@
+use crate::*;
+use erasable::Thin;
+use slice_dst::{SliceWithHeader, StrWithHeader};
+
+/// A thin, atomically reference counted slice
+///
+/// Unlike an `Arc<[T]>`, which is a fat pointer, the length of an `ArcSlice<T>` is stored in the
+/// allocation itself, right after the reference count. This makes an `ArcSlice<T>` a single pointer wide
+/// and [`Erasable`], so it can be stored in pointer unions via [`UnionAlign`].
+///
+/// ```text
+///   elysees::ArcSlice<T>
+///   |
+///   v
+///  ------------------------------------------
+/// | RefCount            | Length | [T] (data) | [ArcInner<SliceWithHeader<(), T>>]
+///  ------------------------------------------
+/// ```
+#[repr(transparent)]
+pub struct ArcSlice<T> {
+    inner: Thin<Arc<SliceWithHeader<(), T>>>,
+}
+
+impl<T> ArcSlice<T> {
+    /// Construct a new `ArcSlice<T>` from an iterator of known length
+    #[inline]
+    pub fn new<I>(items: I) -> ArcSlice<T>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let arc: Arc<_> = SliceWithHeader::new((), items);
+        arc.into()
+    }
+    /// Get the slice underlying this `ArcSlice<T>`
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.inner.slice
+    }
+    /// Convert this `ArcSlice<T>` into the underlying length-prefixed `Arc`
+    #[inline]
+    pub fn into_arc(this: ArcSlice<T>) -> Arc<SliceWithHeader<(), T>> {
+        Thin::into_inner(this.inner)
+    }
+    /// Whether or not this `ArcSlice` is uniquely owned (is the refcount 1?).
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        Thin::with(&self.inner, |arc| arc.is_unique())
+    }
+    /// Get the reference count of this `ArcSlice` with a given ordering
+    #[inline]
+    pub fn count(this: &ArcSlice<T>, ordering: LoadOrdering) -> usize {
+        Thin::with(&this.inner, |arc| Arc::count(arc, ordering))
+    }
+    /// Compare two `ArcSlice`s via pointer equality. Will only return
+    /// true if they come from the same allocation
+    #[inline]
+    pub fn ptr_eq(this: &ArcSlice<T>, other: &ArcSlice<T>) -> bool {
+        Thin::ptr_eq(&this.inner, &other.inner)
+    }
+}
+
+impl<T> From<Arc<SliceWithHeader<(), T>>> for ArcSlice<T> {
+    #[inline]
+    fn from(arc: Arc<SliceWithHeader<(), T>>) -> ArcSlice<T> {
+        ArcSlice { inner: arc.into() }
+    }
+}
+
+impl<T> From<ArcSlice<T>> for Arc<SliceWithHeader<(), T>> {
+    #[inline]
+    fn from(slice: ArcSlice<T>) -> Arc<SliceWithHeader<(), T>> {
+        ArcSlice::into_arc(slice)
+    }
+}
+
+impl<T: Clone> From<&[T]> for ArcSlice<T> {
+    #[inline]
+    fn from(slice: &[T]) -> ArcSlice<T> {
+        ArcSlice::new(slice.iter().cloned())
+    }
+}
+
+impl<T> From<Vec<T>> for ArcSlice<T> {
+    #[inline]
+    fn from(vec: Vec<T>) -> ArcSlice<T> {
+        ArcSlice::new(vec)
+    }
+}
+
+impl<T> FromIterator<T> for ArcSlice<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> ArcSlice<T> {
+        ArcSlice::new(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T> Clone for ArcSlice<T> {
+    #[inline]
+    fn clone(&self) -> ArcSlice<T> {
+        ArcSlice {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Deref for ArcSlice<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> Borrow<[T]> for ArcSlice<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsRef<[T]> for ArcSlice<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcSlice<T> {
+    #[inline]
+    fn eq(&self, other: &ArcSlice<T>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: PartialEq> PartialEq<[T]> for ArcSlice<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Eq> Eq for ArcSlice<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArcSlice<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &ArcSlice<T>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord> Ord for ArcSlice<T> {
+    #[inline]
+    fn cmp(&self, other: &ArcSlice<T>) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Hash> Hash for ArcSlice<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T> fmt::Pointer for ArcSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Pointer::fmt(&self.inner, f)
+    }
+}
+
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T> StableDeref for ArcSlice<T> {}
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T> CloneStableDeref for ArcSlice<T> {}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for ArcSlice<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for ArcSlice<T> {
+    fn deserialize<D>(deserializer: D) -> Result<ArcSlice<T>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        Vec::<T>::deserialize(deserializer).map(ArcSlice::from)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for ArcSlice<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.collect()
+    }
+}
+
+unsafe impl<T> ErasablePtr for ArcSlice<T> {
+    #[inline]
+    fn erase(this: Self) -> ErasedPtr {
+        ErasablePtr::erase(this.inner)
+    }
+
+    #[inline]
+    unsafe fn unerase(this: ErasedPtr) -> Self {
+        ArcSlice {
+            inner: ErasablePtr::unerase(this),
+        }
+    }
+}
+
+/// A thin, atomically reference counted string
+///
+/// This is the `str` equivalent of [`ArcSlice`]: the length of the string is stored in the allocation,
+/// right after the reference count, making an `ArcStr` a single pointer wide and [`Erasable`].
+///
+/// `ArcStr` hashes and compares exactly like the `str` it contains, so it can be used as a key in maps
+/// queried by `&str`.
+#[repr(transparent)]
+pub struct ArcStr {
+    inner: Thin<Arc<StrWithHeader<()>>>,
+}
+
+impl ArcStr {
+    /// Construct a new `ArcStr` by copying a string
+    #[inline]
+    pub fn new(s: &str) -> ArcStr {
+        let arc: Arc<_> = StrWithHeader::new((), s);
+        arc.into()
+    }
+    /// Get the string underlying this `ArcStr`
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.inner.str
+    }
+    /// Convert this `ArcStr` into the underlying length-prefixed `Arc`
+    #[inline]
+    pub fn into_arc(this: ArcStr) -> Arc<StrWithHeader<()>> {
+        Thin::into_inner(this.inner)
+    }
+    /// Whether or not this `ArcStr` is uniquely owned (is the refcount 1?).
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        Thin::with(&self.inner, |arc| arc.is_unique())
+    }
+    /// Get the reference count of this `ArcStr` with a given ordering
+    #[inline]
+    pub fn count(this: &ArcStr, ordering: LoadOrdering) -> usize {
+        Thin::with(&this.inner, |arc| Arc::count(arc, ordering))
+    }
+    /// Compare two `ArcStr`s via pointer equality. Will only return
+    /// true if they come from the same allocation
+    #[inline]
+    pub fn ptr_eq(this: &ArcStr, other: &ArcStr) -> bool {
+        Thin::ptr_eq(&this.inner, &other.inner)
+    }
+}
+
+impl From<Arc<StrWithHeader<()>>> for ArcStr {
+    #[inline]
+    fn from(arc: Arc<StrWithHeader<()>>) -> ArcStr {
+        ArcStr { inner: arc.into() }
+    }
+}
+
+impl From<ArcStr> for Arc<StrWithHeader<()>> {
+    #[inline]
+    fn from(s: ArcStr) -> Arc<StrWithHeader<()>> {
+        ArcStr::into_arc(s)
+    }
+}
+
+impl From<&str> for ArcStr {
+    #[inline]
+    fn from(s: &str) -> ArcStr {
+        ArcStr::new(s)
+    }
+}
+
+impl From<String> for ArcStr {
+    #[inline]
+    fn from(s: String) -> ArcStr {
+        ArcStr::new(&s)
+    }
+}
+
+impl Clone for ArcStr {
+    #[inline]
+    fn clone(&self) -> ArcStr {
+        ArcStr {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Deref for ArcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for ArcStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for ArcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for ArcStr {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PartialEq for ArcStr {
+    #[inline]
+    fn eq(&self, other: &ArcStr) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl PartialEq<str> for ArcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ArcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<ArcStr> for str {
+    #[inline]
+    fn eq(&self, other: &ArcStr) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<ArcStr> for &str {
+    #[inline]
+    fn eq(&self, other: &ArcStr) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl Eq for ArcStr {}
+
+impl PartialOrd for ArcStr {
+    #[inline]
+    fn partial_cmp(&self, other: &ArcStr) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ArcStr {
+    #[inline]
+    fn cmp(&self, other: &ArcStr) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for ArcStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Debug for ArcStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for ArcStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Pointer for ArcStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Pointer::fmt(&self.inner, f)
+    }
+}
+
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl StableDeref for ArcStr {}
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl CloneStableDeref for ArcStr {}
+
+#[cfg(feature = "serde")]
+impl Serialize for ArcStr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        self.as_str().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ArcStr {
+    fn deserialize<D>(deserializer: D) -> Result<ArcStr, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(ArcStr::from)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ArcStr {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary(u).map(ArcStr::from)
+    }
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary_take_rest(u).map(ArcStr::from)
+    }
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <&str>::size_hint(depth)
+    }
+}
+
+unsafe impl ErasablePtr for ArcStr {
+    #[inline]
+    fn erase(this: Self) -> ErasedPtr {
+        ErasablePtr::erase(this.inner)
+    }
+
+    #[inline]
+    unsafe fn unerase(this: ErasedPtr) -> Self {
+        ArcStr {
+            inner: ErasablePtr::unerase(this),
+        }
+    }
+}
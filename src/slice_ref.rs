@@ -0,0 +1,293 @@
+use crate::*;
+use core::mem::ManuallyDrop;
+use core::ops::{Bound, Range, RangeBounds};
+use core::slice;
+
+/// A zero-copy view into a range of a shared `Arc<[T]>`
+///
+/// An `ArcSliceRef<T>` holds a strong reference to its parent `Arc<[T]>`, keeping the whole allocation alive,
+/// along with the range of the parent it refers to. Cloning and sub-slicing an `ArcSliceRef` never copies the
+/// underlying data, only bumping the reference count of the parent, similarly to `bytes::Bytes`.
+///
+/// Like a `&[T]`, it points directly at the elements it views, so dereferencing it does not go through the parent.
+/// The parent's allocation is instead recovered from that pointer, the view's offset into the parent and the
+/// parent's length, using [`ArcInner::data_offset`] as for any `Arc`, when the view is cloned or dropped.
+///
+/// ```rust
+/// # use elysees::{Arc, ArcSliceRef};
+/// let buffer: Arc<[u8]> = Arc::from(&b"Hello, world!"[..]);
+/// let mut hello = ArcSliceRef::from(buffer.clone());
+/// let world = hello.split_off(7);
+/// assert_eq!(&hello[..], b"Hello, ");
+/// assert_eq!(&world[..], b"world!");
+/// assert_eq!(&world.slice(..5)[..], b"world");
+/// assert!(Arc::ptr_eq(ArcSliceRef::parent(&hello).as_arc(), &buffer));
+/// ```
+pub struct ArcSliceRef<T> {
+    /// The first element of this view
+    ptr: ptr::NonNull<T>,
+    /// The number of elements in this view
+    len: usize,
+    /// The index of the first element of this view in the parent slice
+    start: usize,
+    /// The length of the parent slice
+    parent_len: usize,
+    phantom: PhantomData<Arc<[T]>>,
+}
+
+unsafe impl<T: Sync + Send> Send for ArcSliceRef<T> {}
+unsafe impl<T: Sync + Send> Sync for ArcSliceRef<T> {}
+
+impl<T> ArcSliceRef<T> {
+    /// Create a new `ArcSliceRef<T>` viewing the entirety of an `Arc<[T]>`
+    #[inline]
+    pub fn new(parent: Arc<[T]>) -> ArcSliceRef<T> {
+        let parent_len = parent.len();
+        let ptr = Arc::into_raw(parent) as *mut T;
+        ArcSliceRef {
+            ptr: unsafe { ptr::NonNull::new_unchecked(ptr) },
+            len: parent_len,
+            start: 0,
+            parent_len,
+            phantom: PhantomData,
+        }
+    }
+    /// Create a new `ArcSliceRef<T>` viewing a range of an `Arc<[T]>`
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds for the parent slice
+    #[inline]
+    pub fn with_range(parent: Arc<[T]>, range: impl RangeBounds<usize>) -> ArcSliceRef<T> {
+        ArcSliceRef::new(parent).slice(range)
+    }
+    /// Get the underlying slice
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // The range is checked against the parent's length on construction
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+    /// Get a pointer to the parent slice
+    #[inline]
+    fn parent_ptr(&self) -> *const [T] {
+        let data = unsafe { self.ptr.as_ptr().sub(self.start) };
+        ptr::slice_from_raw_parts(data, self.parent_len)
+    }
+    /// Borrow the parent `Arc<[T]>` of this `ArcSliceRef<T>`
+    #[inline]
+    pub fn parent(this: &ArcSliceRef<T>) -> ArcBorrow<'_, [T]> {
+        unsafe { ArcBorrow::from_raw(this.parent_ptr()) }
+    }
+    /// Convert this `ArcSliceRef<T>` into its parent `Arc<[T]>`
+    #[inline]
+    pub fn into_parent(this: ArcSliceRef<T>) -> Arc<[T]> {
+        let this = ManuallyDrop::new(this);
+        unsafe { Arc::from_raw(this.parent_ptr()) }
+    }
+    /// Get the range of the parent `Arc<[T]>` this `ArcSliceRef<T>` views
+    #[inline]
+    pub fn range(this: &ArcSliceRef<T>) -> Range<usize> {
+        this.start..this.start + this.len
+    }
+    /// View the `len` elements starting at `start` in this `ArcSliceRef<T>`, which must be in bounds, taking a new
+    /// reference to the parent
+    #[inline]
+    fn view(&self, start: usize, len: usize) -> ArcSliceRef<T> {
+        // The new view owns this reference, and releases it when dropped
+        mem::forget(ArcSliceRef::parent(self).clone_arc());
+        ArcSliceRef {
+            ptr: unsafe { ptr::NonNull::new_unchecked(self.ptr.as_ptr().add(start)) },
+            len,
+            start: self.start + start,
+            parent_len: self.parent_len,
+            phantom: PhantomData,
+        }
+    }
+    /// Get a sub-slice of this `ArcSliceRef<T>`, sharing the same parent. The range is relative to this slice.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds
+    #[inline]
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> ArcSliceRef<T> {
+        let len = self.len();
+        let begin = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("out of range"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("out of range"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            begin <= end,
+            "range start must not be greater than end: {:?} <= {:?}",
+            begin,
+            end
+        );
+        assert!(
+            end <= len,
+            "range end out of bounds: {:?} <= {:?}",
+            end,
+            len
+        );
+        self.view(begin, end - begin)
+    }
+    /// Split this `ArcSliceRef<T>` into two at an index, sharing the same parent.
+    ///
+    /// The first slice will contain `[0, mid)` and the second `[mid, len)`
+    ///
+    /// # Panics
+    /// Panics if `mid > len`
+    #[inline]
+    pub fn split_at(self, mid: usize) -> (ArcSliceRef<T>, ArcSliceRef<T>) {
+        let mut left = self;
+        let right = left.split_off(mid);
+        (left, right)
+    }
+    /// Split off `[at, len)` from this `ArcSliceRef<T>`, leaving `[0, at)`
+    ///
+    /// # Panics
+    /// Panics if `at > len`
+    #[inline]
+    pub fn split_off(&mut self, at: usize) -> ArcSliceRef<T> {
+        assert!(at <= self.len(), "split_off out of bounds");
+        let right = self.view(at, self.len - at);
+        self.len = at;
+        right
+    }
+    /// Split off `[0, at)` from this `ArcSliceRef<T>`, leaving `[at, len)`
+    ///
+    /// # Panics
+    /// Panics if `at > len`
+    #[inline]
+    pub fn split_to(&mut self, at: usize) -> ArcSliceRef<T> {
+        assert!(at <= self.len(), "split_to out of bounds");
+        let left = self.view(0, at);
+        self.ptr = unsafe { ptr::NonNull::new_unchecked(self.ptr.as_ptr().add(at)) };
+        self.start += at;
+        self.len -= at;
+        left
+    }
+    /// Shorten this `ArcSliceRef<T>`, keeping the first `len` elements. Does nothing if `len` is greater than
+    /// the current length
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len
+        }
+    }
+}
+
+impl<T> Clone for ArcSliceRef<T> {
+    #[inline]
+    fn clone(&self) -> ArcSliceRef<T> {
+        self.view(0, self.len)
+    }
+}
+
+impl<T> Drop for ArcSliceRef<T> {
+    #[inline]
+    fn drop(&mut self) {
+        drop(unsafe { Arc::from_raw(self.parent_ptr()) })
+    }
+}
+
+impl<T> From<Arc<[T]>> for ArcSliceRef<T> {
+    #[inline]
+    fn from(parent: Arc<[T]>) -> ArcSliceRef<T> {
+        ArcSliceRef::new(parent)
+    }
+}
+
+impl<T> Deref for ArcSliceRef<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> Borrow<[T]> for ArcSliceRef<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsRef<[T]> for ArcSliceRef<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcSliceRef<T> {
+    #[inline]
+    fn eq(&self, other: &ArcSliceRef<T>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: PartialEq> PartialEq<[T]> for ArcSliceRef<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Eq> Eq for ArcSliceRef<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArcSliceRef<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &ArcSliceRef<T>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord> Ord for ArcSliceRef<T> {
+    #[inline]
+    fn cmp(&self, other: &ArcSliceRef<T>) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Hash> Hash for ArcSliceRef<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcSliceRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T> StableDeref for ArcSliceRef<T> {}
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T> CloneStableDeref for ArcSliceRef<T> {}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for ArcSliceRef<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+/// Generates an arbitrary sub-slice of an arbitrary parent
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for ArcSliceRef<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let parent: Arc<[T]> = arbitrary::Arbitrary::arbitrary(u)?;
+        let start = u.int_in_range(0..=parent.len())?;
+        let end = u.int_in_range(start..=parent.len())?;
+        Ok(ArcSliceRef::with_range(parent, start..end))
+    }
+}
@@ -0,0 +1,223 @@
+/*!
+Allocation statistics for `Arc`s and `Arc32`s.
+
+With the `stats` feature enabled, global counters track the number and total size of live `Arc` and `Arc32`
+allocations (including those backing `ArcBox`es, `ArcSlice`s and the like), as well as the number of allocations,
+deallocations, clones and drops over time, which can be read with [`global`]. Individual types can also be
+[`register`]ed to be counted separately, and read with [`type_stats`].
+
+Counters are updated with relaxed atomic operations, so a snapshot taken while other threads are working is not
+necessarily consistent.
+
+```rust
+# use elysees::{stats, Arc};
+stats::register::<[u8]>();
+let before = stats::type_stats::<[u8]>().unwrap();
+let bytes: Arc<[u8]> = Arc::from(&[1, 2, 3][..]);
+let clone = bytes.clone();
+let after = stats::type_stats::<[u8]>().unwrap();
+assert_eq!(after.live, before.live + 1);
+assert_eq!(after.clones, before.clones + 1);
+assert!(stats::global().live_bytes >= after.live_bytes);
+```
+*/
+use crate::*;
+use core::sync::atomic::{AtomicPtr, AtomicUsize};
+use std::boxed::Box;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+
+/// A snapshot of `Arc` allocation statistics
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ArcStats {
+    /// The number of live allocations
+    pub live: usize,
+    /// The total size, in bytes, of live allocations, including their reference counts
+    pub live_bytes: usize,
+    /// The number of allocations made since the last [`reset`]
+    pub allocations: usize,
+    /// The total size, in bytes, of allocations made since the last [`reset`]
+    pub allocated_bytes: usize,
+    /// The number of deallocations since the last [`reset`]
+    pub deallocations: usize,
+    /// The number of references created by cloning since the last [`reset`]
+    pub clones: usize,
+    /// The number of references released since the last [`reset`]
+    pub drops: usize,
+}
+
+/// The live counters behind an `ArcStats`
+struct Counters {
+    live: AtomicUsize,
+    live_bytes: AtomicUsize,
+    allocations: AtomicUsize,
+    allocated_bytes: AtomicUsize,
+    deallocations: AtomicUsize,
+    clones: AtomicUsize,
+    drops: AtomicUsize,
+}
+
+impl Counters {
+    const fn new() -> Counters {
+        Counters {
+            live: AtomicUsize::new(0),
+            live_bytes: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            allocated_bytes: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            clones: AtomicUsize::new(0),
+            drops: AtomicUsize::new(0),
+        }
+    }
+
+    fn snapshot(&self) -> ArcStats {
+        ArcStats {
+            live: self.live.load(Relaxed),
+            live_bytes: self.live_bytes.load(Relaxed),
+            allocations: self.allocations.load(Relaxed),
+            allocated_bytes: self.allocated_bytes.load(Relaxed),
+            deallocations: self.deallocations.load(Relaxed),
+            clones: self.clones.load(Relaxed),
+            drops: self.drops.load(Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        // `live` and `live_bytes` describe the current state rather than a history, so they are kept
+        self.allocations.store(0, Relaxed);
+        self.allocated_bytes.store(0, Relaxed);
+        self.deallocations.store(0, Relaxed);
+        self.clones.store(0, Relaxed);
+        self.drops.store(0, Relaxed);
+    }
+
+    #[inline]
+    fn alloc(&self, bytes: usize) {
+        self.live.fetch_add(1, Relaxed);
+        self.live_bytes.fetch_add(bytes, Relaxed);
+        self.allocations.fetch_add(1, Relaxed);
+        self.allocated_bytes.fetch_add(bytes, Relaxed);
+    }
+
+    #[inline]
+    fn dealloc(&self, bytes: usize) {
+        // Allocations made before their type was registered are freed without having been counted, so the live
+        // counters stop at zero rather than wrapping around
+        let _ = self
+            .live
+            .fetch_update(Relaxed, Relaxed, |live| live.checked_sub(1));
+        let _ = self
+            .live_bytes
+            .fetch_update(Relaxed, Relaxed, |live_bytes| live_bytes.checked_sub(bytes));
+        self.deallocations.fetch_add(1, Relaxed);
+    }
+}
+
+/// The counters for all `Arc`s
+static GLOBAL: Counters = Counters::new();
+
+/// The registered types, keyed by type name
+type Registry = BTreeMap<&'static str, &'static Counters>;
+
+/// The counters for registered types, or null if no types have been registered.
+///
+/// `Arc`s look their type up here on every allocation, deallocation, clone and drop, so rather than being locked,
+/// the registry is never modified once published: each registration publishes a new copy. Registered counters
+/// and every published copy of the registry are leaked, as registration is permanent.
+static TYPES: AtomicPtr<Registry> = AtomicPtr::new(ptr::null_mut());
+
+/// Held while registering a type, so that concurrent registrations do not lose each other's updates
+static REGISTERING: Mutex<()> = Mutex::new(());
+
+/// Get the current registry, if any types have been registered
+#[inline]
+fn registry() -> Option<&'static Registry> {
+    // Published registries are leaked, and never modified or freed
+    unsafe { TYPES.load(Acquire).as_ref() }
+}
+
+/// Get the counters for the type `T`, if it is registered
+#[inline]
+fn type_counters<T: ?Sized>() -> Option<&'static Counters> {
+    registry()?.get(core::any::type_name::<T>()).copied()
+}
+
+/// Get a snapshot of the statistics for all `Arc`s
+pub fn global() -> ArcStats {
+    GLOBAL.snapshot()
+}
+
+/// Start counting `Arc<T>`s and `Arc32<T>`s separately. Registering a type more than once has no further effect.
+///
+/// Only allocations made after registration are counted, but deallocations are counted regardless, so for a type
+/// registered while some allocations of it already exist, the number and size of live allocations are too low until
+/// those are freed, though they never drop below zero.
+///
+/// Types are identified by their [`type_name`](core::any::type_name), since `Arc`s of non-`'static` types have no
+/// `TypeId`. Type names are not guaranteed to be unique, so distinct types with the same name, such as types which
+/// differ only in their lifetimes, or the same type from two versions of a crate, are counted together.
+///
+/// Once any type is registered, every `Arc` allocation, deallocation, clone and drop looks up its type among the
+/// registered types, though without taking a lock.
+pub fn register<T: ?Sized>() {
+    let _registering = REGISTERING.lock().unwrap_or_else(PoisonError::into_inner);
+    let name = core::any::type_name::<T>();
+    let registry = registry();
+    if registry.is_some_and(|registry| registry.contains_key(name)) {
+        return;
+    }
+    let mut registry = registry.cloned().unwrap_or_default();
+    registry.insert(name, Box::leak(Box::new(Counters::new())));
+    TYPES.store(Box::into_raw(Box::new(registry)), Release);
+}
+
+/// Get a snapshot of the statistics for `Arc<T>`s, if `T` has been [`register`]ed
+pub fn type_stats<T: ?Sized>() -> Option<ArcStats> {
+    type_counters::<T>().map(Counters::snapshot)
+}
+
+/// Reset the cumulative counters, both global and for each registered type, to zero.
+///
+/// The number and size of live allocations are kept, since they describe the current state of the program.
+pub fn reset() {
+    GLOBAL.reset();
+    for counters in registry().into_iter().flat_map(Registry::values) {
+        counters.reset()
+    }
+}
+
+/// Record a new allocation of `bytes` bytes
+#[inline]
+pub(crate) fn record_alloc<T: ?Sized>(bytes: usize) {
+    GLOBAL.alloc(bytes);
+    if let Some(counters) = type_counters::<T>() {
+        counters.alloc(bytes)
+    }
+}
+
+/// Record the deallocation of an allocation of `bytes` bytes
+#[inline]
+pub(crate) fn record_dealloc<T: ?Sized>(bytes: usize) {
+    GLOBAL.dealloc(bytes);
+    if let Some(counters) = type_counters::<T>() {
+        counters.dealloc(bytes)
+    }
+}
+
+/// Record the creation of `n` references by cloning
+#[inline]
+pub(crate) fn record_clones<T: ?Sized>(n: usize) {
+    GLOBAL.clones.fetch_add(n, Relaxed);
+    if let Some(counters) = type_counters::<T>() {
+        counters.clones.fetch_add(n, Relaxed);
+    }
+}
+
+/// Record the release of `n` references
+#[inline]
+pub(crate) fn record_drops<T: ?Sized>(n: usize) {
+    GLOBAL.drops.fetch_add(n, Relaxed);
+    if let Some(counters) = type_counters::<T>() {
+        counters.drops.fetch_add(n, Relaxed);
+    }
+}
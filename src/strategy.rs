@@ -0,0 +1,128 @@
+/*!
+`proptest` strategies for `Arc`s with controlled sharing.
+
+Plain `any::<Arc<T>>()` always generates unique `Arc`s. The strategies in this module instead generate groups of
+`Arc`s sharing allocations, for property testing code paths like `Arc::make_mut`, `Arc::try_unique` and
+`Arc::is_unique`:
+
+```rust
+# use elysees::Arc;
+# use elysees::strategy::arc_with_sharing;
+# use proptest::prelude::*;
+proptest! {
+    fn make_mut_preserves_clones(mut arcs in arc_with_sharing(any::<u32>(), 4)) {
+        let original = *arcs[0];
+        *Arc::make_mut(&mut arcs[0]) = original.wrapping_add(1);
+        prop_assert!(arcs[1..].iter().all(|arc| **arc == original));
+    }
+}
+# make_mut_preserves_clones();
+```
+*/
+use crate::*;
+use proptest::collection::{vec, SizeRange};
+use proptest::sample::Index;
+use proptest::strategy::Strategy;
+#[cfg(feature = "ptr-union")]
+use ptr_union::{Union2, Union4};
+
+/// Generate a unique `Arc` containing a value generated by `strategy`
+pub fn arc<S: Strategy>(strategy: S) -> impl Strategy<Value = Arc<S::Value>> {
+    strategy.prop_map(Arc::new)
+}
+
+/// Generate an `ArcBox` containing a value generated by `strategy`
+pub fn arc_box<S: Strategy>(strategy: S) -> impl Strategy<Value = ArcBox<S::Value>> {
+    strategy.prop_map(ArcBox::new)
+}
+
+/// Generate a unique `Arc<[T]>` with elements generated by `element` and a length in `size`
+pub fn arc_slice<S: Strategy>(
+    element: S,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = Arc<[S::Value]>> {
+    vec(element, size).prop_map(Arc::from)
+}
+
+/// Generate between `1` and `max_clones` clones of a single `Arc` containing a value generated by `strategy`
+///
+/// # Panics
+/// Panics if `max_clones` is zero
+pub fn arc_with_sharing<S: Strategy>(
+    strategy: S,
+    max_clones: usize,
+) -> impl Strategy<Value = Vec<Arc<S::Value>>> {
+    assert!(max_clones > 0, "max_clones must be at least 1");
+    (strategy, 1..=max_clones).prop_map(|(value, clones)| {
+        let arc = Arc::new(value);
+        let mut arcs = Vec::with_capacity(clones);
+        arcs.extend(Arc::clone_n(&arc, clones - 1));
+        arcs.push(arc);
+        arcs
+    })
+}
+
+/// Generate `len` `Arc`s, each a clone of one of `allocations` distinct allocations containing values generated by
+/// `strategy`, so that some allocations may be shared and others unique
+///
+/// If the number of allocations generated is zero, so is the number of `Arc`s.
+pub fn shared_arcs<S: Strategy>(
+    strategy: S,
+    allocations: impl Into<SizeRange>,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<Arc<S::Value>>> {
+    (
+        vec(arc(strategy), allocations),
+        vec(proptest::arbitrary::any::<Index>(), len),
+    )
+        .prop_map(|(pool, indices)| {
+            if pool.is_empty() {
+                return Vec::new();
+            }
+            indices
+                .iter()
+                .map(|index| index.get(&pool).clone())
+                .collect()
+        })
+}
+
+/// Generate a `Union2` of values generated by `a` or `b`
+#[cfg(feature = "ptr-union")]
+pub fn union2<A, B>(a: A, b: B) -> impl Strategy<Value = Union2<A::Value, B::Value>>
+where
+    A: Strategy,
+    B: Strategy,
+    A::Value: UnionAlign,
+    B::Value: UnionAlign,
+{
+    proptest::prop_oneof![
+        a.prop_map(UnionAlign::left::<B::Value>),
+        b.prop_map(UnionAlign::right::<A::Value>),
+    ]
+}
+
+/// Generate a `Union4` of values generated by `a`, `b`, `c` or `d`
+#[cfg(feature = "ptr-union")]
+pub fn union4<A, B, C, D>(
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+) -> impl Strategy<Value = Union4<A::Value, B::Value, C::Value, D::Value>>
+where
+    A: Strategy,
+    B: Strategy,
+    C: Strategy,
+    D: Strategy,
+    A::Value: UnionAlign,
+    B::Value: UnionAlign,
+    C::Value: UnionAlign,
+    D::Value: UnionAlign,
+{
+    proptest::prop_oneof![
+        a.prop_map(UnionAlign::a::<B::Value, C::Value, D::Value>),
+        b.prop_map(UnionAlign::b::<A::Value, C::Value, D::Value>),
+        c.prop_map(UnionAlign::c::<A::Value, B::Value, D::Value>),
+        d.prop_map(UnionAlign::d::<A::Value, B::Value, C::Value>),
+    ]
+}
@@ -3,6 +3,10 @@ use erasable::Thin;
 use ptr_union::{Builder2, Builder4, Union2, Union4};
 
 /// A value which can be made into *any* pointer union
+///
+/// # Safety
+/// Implementors must be erasable pointers which are always aligned to at least 4 bytes, i.e. which
+/// have at least two free low bits to store a union tag in.
 pub unsafe trait UnionAlign: Sized + ErasablePtr {
     fn left<B: UnionAlign>(this: Self) -> Union2<Self, B> {
         unsafe { Builder2::new_unchecked().a(this) }
@@ -33,3 +37,7 @@ where
 }
 unsafe impl<'a, T: ?Sized> UnionAlign for ArcBorrow<'a, T> where ArcBorrow<'a, T>: ErasablePtr {}
 unsafe impl<T: ?Sized> UnionAlign for ArcBox<T> where ArcBox<T>: ErasablePtr {}
+#[cfg(feature = "slice-dst")]
+unsafe impl<T> UnionAlign for ArcSlice<T> {}
+#[cfg(feature = "slice-dst")]
+unsafe impl UnionAlign for ArcStr {}
@@ -49,12 +49,28 @@ impl<T> ArcBox<T> {
     pub fn new(data: T) -> Self {
         ArcBox(Arc::new(data))
     }
+    /// Move the contents out of this `ArcBox`, freeing its allocation
+    #[inline]
+    pub fn into_inner(this: Self) -> T {
+        let arc = mem::ManuallyDrop::new(this.0);
+        let ptr = arc.ptr.as_ptr();
+        #[cfg(feature = "stats")]
+        stats::record_drops::<T>(1);
+        unsafe {
+            // We are the only reference to the data, so we may move it out and free the allocation
+            let data = ptr::read(ptr);
+            let (layout, inner) = ArcInner::inner_ptr_mut(ptr);
+            track_dealloc(ptr, layout);
+            dealloc(inner, layout);
+            data
+        }
+    }
 }
 
-impl<T: Clone> Clone for ArcBox<T> {
+impl<T: ?Sized + CloneToUninit> Clone for ArcBox<T> {
     #[inline]
     fn clone(&self) -> ArcBox<T> {
-        ArcBox(Arc::new(self.0.deref().clone()))
+        ArcBox(Arc::new_clone(self.0.deref()))
     }
 }
 
@@ -64,13 +80,22 @@ impl<T: ?Sized> ArcBox<T> {
     pub fn shareable(self) -> Arc<T> {
         self.0
     }
+    /// Borrow this `ArcBox<T>` as an `ArcBorrow<T>`, e.g. to pass it to an API taking one
+    ///
+    /// # Safety
+    /// Any `Arc<T>` created from the returned borrow, e.g. by `ArcBorrow::clone_arc`, must be dropped before this
+    /// `ArcBox<T>` is used again, as it would otherwise no longer be unique.
+    #[inline]
+    pub unsafe fn borrow_arc(&self) -> ArcBorrow<'_, T> {
+        self.0.borrow_arc()
+    }
 }
 
 impl<T: ?Sized> Deref for ArcBox<T> {
     type Target = T;
     #[inline]
     fn deref(&self) -> &T {
-        &*self.0
+        &self.0
     }
 }
 
@@ -85,28 +110,28 @@ impl<T: ?Sized> DerefMut for ArcBox<T> {
 impl<T: ?Sized> Borrow<T> for ArcBox<T> {
     #[inline]
     fn borrow(&self) -> &T {
-        &**self
+        self
     }
 }
 
 impl<T: ?Sized> AsRef<T> for ArcBox<T> {
     #[inline]
     fn as_ref(&self) -> &T {
-        &**self
+        self
     }
 }
 
 impl<T: ?Sized> BorrowMut<T> for ArcBox<T> {
     #[inline]
     fn borrow_mut(&mut self) -> &mut T {
-        &mut **self
+        self
     }
 }
 
 impl<T: ?Sized> AsMut<T> for ArcBox<T> {
     #[inline]
     fn as_mut(&mut self) -> &mut T {
-        &mut **self
+        self
     }
 }
 
@@ -151,6 +176,26 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for ArcBox<T> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ArcBox<str> {
+    fn deserialize<D>(deserializer: D) -> Result<ArcBox<str>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        Arc::<str>::deserialize(deserializer).map(ArcBox)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for ArcBox<[T]> {
+    fn deserialize<D>(deserializer: D) -> Result<ArcBox<[T]>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        Arc::<[T]>::deserialize(deserializer).map(ArcBox)
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<T: ?Sized + Serialize> Serialize for ArcBox<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -176,6 +221,8 @@ unsafe impl<T: ?Sized + Erasable> ErasablePtr for ArcBox<T> {
 mod slice_dst_impl {
     use super::*;
     use slice_dst::{AllocSliceDst, SliceDst, TryAllocSliceDst};
+    #[cfg(feature = "serde")]
+    use slice_dst::{SliceWithHeader, StrWithHeader};
 
     #[cfg(feature = "slice-dst")]
     unsafe impl<S: ?Sized + SliceDst> TryAllocSliceDst<S> for ArcBox<S> {
@@ -195,24 +242,121 @@ mod slice_dst_impl {
             ArcBox(Arc::new_slice_dst(len, init))
         }
     }
+
+    /// Deserializes a `SliceWithHeader` from a `(header, slice)` tuple
+    #[cfg(feature = "serde")]
+    impl<'de, H: Deserialize<'de>, T: Deserialize<'de>> Deserialize<'de>
+        for ArcBox<SliceWithHeader<H, T>>
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: ::serde::de::Deserializer<'de>,
+        {
+            Arc::<SliceWithHeader<H, T>>::deserialize(deserializer).map(ArcBox)
+        }
+    }
+
+    /// Deserializes a `StrWithHeader` from a `(header, str)` tuple
+    #[cfg(feature = "serde")]
+    impl<'de, H: Deserialize<'de>> Deserialize<'de> for ArcBox<StrWithHeader<H>> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: ::serde::de::Deserializer<'de>,
+        {
+            Arc::<StrWithHeader<H>>::deserialize(deserializer).map(ArcBox)
+        }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+mod rkyv_impl {
+    use super::*;
+    use crate::arc::alloc_rkyv;
+    use rkyv::boxed::{ArchivedBox, BoxResolver};
+    use rkyv::rancor::{Fallible, ResultExt, Source};
+    use rkyv::traits::LayoutRaw;
+    use rkyv::{Archive, ArchiveUnsized, DeserializeUnsized, Place, SerializeUnsized};
+
+    /// Archived as an `ArchivedBox`, sharing the representation of `Box`
+    impl<T: ArchiveUnsized + ?Sized> Archive for ArcBox<T> {
+        type Archived = ArchivedBox<T::Archived>;
+        type Resolver = BoxResolver;
+
+        fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+            ArchivedBox::resolve_from_ref(&**self, resolver, out)
+        }
+    }
+
+    impl<T, S> rkyv::Serialize<S> for ArcBox<T>
+    where
+        T: SerializeUnsized<S> + ?Sized,
+        S: Fallible + ?Sized,
+    {
+        fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            ArchivedBox::serialize_from_ref(&**self, serializer)
+        }
+    }
+
+    impl<T, D> rkyv::Deserialize<ArcBox<T>, D> for ArchivedBox<T::Archived>
+    where
+        T: ArchiveUnsized + LayoutRaw + ?Sized,
+        T::Archived: DeserializeUnsized<T, D>,
+        D: Fallible + ?Sized,
+        D::Error: Source,
+    {
+        fn deserialize(&self, deserializer: &mut D) -> Result<ArcBox<T>, D::Error> {
+            let metadata = self.get().deserialize_metadata();
+            let (layout, base, data) = alloc_rkyv::<T>(metadata).into_error()?;
+            match unsafe { self.get().deserialize_unsized(deserializer, data) } {
+                Ok(()) => Ok(ArcBox(unsafe { Arc::from_raw(data) })),
+                Err(err) => {
+                    track_dealloc(data, layout);
+                    unsafe { dealloc(base, layout) };
+                    Err(err)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impl {
+    use super::*;
+    use proptest::arbitrary::{any_with, Arbitrary, StrategyFor};
+    use proptest::strategy::{Map, Strategy};
+
+    /// Freshly generated `Arc`s are always unique
+    impl<T: ?Sized + fmt::Debug> Arbitrary for ArcBox<T>
+    where
+        Arc<T>: Arbitrary,
+    {
+        type Parameters = <Arc<T> as Arbitrary>::Parameters;
+        type Strategy = Map<StrategyFor<Arc<T>>, fn(Arc<T>) -> ArcBox<T>>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Arc<T>>(args).prop_map(ArcBox)
+        }
+    }
 }
 
 #[cfg(feature = "arbitrary")]
 mod arbitrary_impl {
     use super::*;
     use arbitrary::{Arbitrary, Result, Unstructured};
-    impl<T: Arbitrary> Arbitrary for ArcBox<T> {
-        fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
-            T::arbitrary(u).map(ArcBox::new)
+
+    /// Freshly generated `Arc`s are always unique
+    impl<'a, T: ?Sized> Arbitrary<'a> for ArcBox<T>
+    where
+        Arc<T>: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Arc::arbitrary(u).map(ArcBox)
         }
-        fn arbitrary_take_rest(u: Unstructured<'_>) -> Result<Self> {
-            T::arbitrary_take_rest(u).map(ArcBox::new)
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            Arc::arbitrary_take_rest(u).map(ArcBox)
         }
         fn size_hint(depth: usize) -> (usize, Option<usize>) {
-            T::size_hint(depth)
-        }
-        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-            Box::new(self.deref().shrink().map(ArcBox::new))
+            Arc::<T>::size_hint(depth)
         }
     }
-}
\ No newline at end of file
+}
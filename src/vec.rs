@@ -0,0 +1,507 @@
+use crate::*;
+use alloc::alloc::realloc;
+use core::slice;
+
+/// A growable, uniquely owned buffer which can be frozen into an `Arc<[T]>` without copying
+///
+/// An `ArcVec<T>` owns an allocation laid out exactly like an `ArcInner<[T]>`, with a reference count
+/// followed by space for `capacity` elements. It can be used much like a `Vec<T>` to build up a slice in place,
+/// after which [`freeze`](ArcVec::freeze) turns it into an `Arc<[T]>`, shrinking the allocation to fit if
+/// necessary (which will usually not move the data).
+///
+/// ```rust
+/// # use elysees::{Arc, ArcVec};
+/// let mut v = ArcVec::with_capacity(3);
+/// v.push(1);
+/// v.extend(vec![2, 3, 4]);
+/// v.truncate(3);
+/// let frozen: Arc<[i32]> = v.freeze();
+/// assert_eq!(*frozen, [1, 2, 3]);
+///
+/// let mut v = Arc::try_into_vec(frozen).expect("The Arc is unique");
+/// v.push(4);
+/// assert_eq!(*v, [1, 2, 3, 4]);
+/// ```
+pub struct ArcVec<T> {
+    ptr: ptr::NonNull<T>,
+    len: usize,
+    cap: usize,
+    phantom: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for ArcVec<T> {}
+unsafe impl<T: Sync> Sync for ArcVec<T> {}
+
+impl<T> ArcVec<T> {
+    /// Whether `T` is zero-sized, in which case the buffer never needs to grow
+    const IS_ZST: bool = mem::size_of::<T>() == 0;
+
+    /// Construct a new, empty `ArcVec<T>`
+    ///
+    /// Note that, unlike `Vec::new`, this allocates space for the reference count.
+    #[inline]
+    pub fn new() -> ArcVec<T> {
+        ArcVec::with_capacity(0)
+    }
+    /// Construct a new, empty `ArcVec<T>` with space for at least `capacity` elements
+    ///
+    /// # Panics
+    /// Panics if the buffer would be larger than `isize::MAX` bytes
+    pub fn with_capacity(capacity: usize) -> ArcVec<T> {
+        let cap = if Self::IS_ZST { usize::MAX } else { capacity };
+        Self::check_capacity(cap);
+        let (layout, offset) = ArcInner::<[T]>::slice_layout(cap);
+        unsafe {
+            let inner = alloc(layout);
+            if inner.is_null() {
+                handle_alloc_error(layout)
+            }
+            #[allow(clippy::cast_ptr_alignment)]
+            ptr::write(
+                inner as *mut atomic::AtomicUsize,
+                atomic::AtomicUsize::new(1),
+            );
+            ArcVec {
+                ptr: ptr::NonNull::new_unchecked(inner.add(offset) as *mut T),
+                len: 0,
+                cap,
+                phantom: PhantomData,
+            }
+        }
+    }
+    /// Get the number of elements in this `ArcVec`
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether this `ArcVec` is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Get the number of elements this `ArcVec` can hold without reallocating
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+    /// Get the elements of this `ArcVec` as a slice
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+    /// Get the elements of this `ArcVec` as a mutable slice
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+    /// Get a pointer to the start of the allocation along with its current layout
+    #[inline]
+    fn inner_ptr(&self) -> (Layout, *mut u8) {
+        let (layout, offset) = ArcInner::<[T]>::slice_layout(self.cap);
+        let inner = unsafe { (self.ptr.as_ptr() as *mut u8).sub(offset) };
+        (layout, inner)
+    }
+    /// Panic if a buffer of `cap` elements would be too large to allocate, as `Vec` does, rather than leaving it to
+    /// `ArcInner::slice_layout` to abort
+    #[inline]
+    fn check_capacity(cap: usize) {
+        let fits = Layout::array::<T>(cap)
+            .and_then(|array| Layout::new::<atomic::AtomicUsize>().extend(array))
+            .is_ok();
+        if !fits {
+            capacity_overflow()
+        }
+    }
+    /// Reallocate this `ArcVec` to have a capacity of exactly `cap` elements, which must be at least `len`
+    fn realloc(&mut self, cap: usize) {
+        debug_assert!(cap >= self.len);
+        Self::check_capacity(cap);
+        let (old_layout, inner) = self.inner_ptr();
+        let (layout, offset) = ArcInner::<[T]>::slice_layout(cap);
+        if layout != old_layout {
+            unsafe {
+                let inner = realloc(inner, old_layout, layout.size());
+                if inner.is_null() {
+                    handle_alloc_error(layout)
+                }
+                self.ptr = ptr::NonNull::new_unchecked(inner.add(offset) as *mut T);
+            }
+        }
+        self.cap = cap;
+    }
+    /// Reserve space for at least `additional` more elements, growing the buffer geometrically
+    ///
+    /// # Panics
+    /// Panics if the new capacity overflows `usize` or the buffer would be larger than `isize::MAX` bytes
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self
+            .len
+            .checked_add(additional)
+            .unwrap_or_else(|| capacity_overflow());
+        if required > self.cap {
+            let cap = required.max(self.cap.saturating_mul(2)).max(4);
+            self.realloc(cap)
+        }
+    }
+    /// Reserve space for exactly `additional` more elements
+    ///
+    /// # Panics
+    /// Panics if the new capacity overflows `usize` or the buffer would be larger than `isize::MAX` bytes
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let required = self
+            .len
+            .checked_add(additional)
+            .unwrap_or_else(|| capacity_overflow());
+        if required > self.cap {
+            self.realloc(required)
+        }
+    }
+    /// Shrink the capacity of this `ArcVec` to its length
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        if !Self::IS_ZST && self.cap > self.len {
+            self.realloc(self.len)
+        }
+    }
+    /// Push an element to the end of this `ArcVec`
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        if self.len == self.cap {
+            self.reserve(1)
+        }
+        unsafe { ptr::write(self.ptr.as_ptr().add(self.len), value) }
+        self.len += 1;
+    }
+    /// Pop an element from the end of this `ArcVec`
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            unsafe { Some(ptr::read(self.ptr.as_ptr().add(self.len))) }
+        }
+    }
+    /// Insert an element at position `index`, shifting all elements after it to the right.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the length of this `ArcVec`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        if index > self.len {
+            panic!(
+                "insertion index {} out of bounds for ArcVec of length {}",
+                index, self.len
+            )
+        }
+        if self.len == self.cap {
+            self.reserve(1)
+        }
+        unsafe {
+            let slot = self.ptr.as_ptr().add(index);
+            ptr::copy(slot, slot.add(1), self.len - index);
+            ptr::write(slot, value);
+        }
+        self.len += 1;
+    }
+    /// Remove and return the element at position `index`, shifting all elements after it to the left.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        if index >= self.len {
+            panic!(
+                "removal index {} out of bounds for ArcVec of length {}",
+                index, self.len
+            )
+        }
+        self.len -= 1;
+        unsafe {
+            let slot = self.ptr.as_ptr().add(index);
+            let value = ptr::read(slot);
+            ptr::copy(slot.add(1), slot, self.len - index);
+            value
+        }
+    }
+    /// Shorten this `ArcVec`, keeping the first `len` elements and dropping the rest.
+    /// Does nothing if `len` is greater than the current length.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            let tail_len = self.len - len;
+            // Set the length first, so that we do not double drop if a destructor panics
+            self.len = len;
+            unsafe {
+                let tail = self.ptr.as_ptr().add(len);
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(tail, tail_len))
+            }
+        }
+    }
+    /// Clear this `ArcVec`, dropping all of its elements
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+    /// Freeze this `ArcVec` into an `Arc<[T]>`, shrinking the allocation to fit first if necessary
+    #[inline]
+    pub fn freeze(mut self) -> Arc<[T]> {
+        self.shrink_to_fit();
+        let ptr = ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len);
+        track_alloc(ptr, ArcInner::<[T]>::slice_layout(self.len).0);
+        mem::forget(self);
+        // The reference count was initialized to 1 on allocation, and the allocation now has exactly the
+        // layout of an `ArcInner<[T]>`
+        unsafe { Arc::from_raw(ptr) }
+    }
+}
+
+impl<T: Clone> ArcVec<T> {
+    /// Clone and append all elements of a slice to this `ArcVec`
+    #[inline]
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.reserve(other.len());
+        for item in other {
+            self.push(item.clone())
+        }
+    }
+}
+
+impl<T> Arc<[T]> {
+    /// Try to convert this `Arc<[T]>` into a growable `ArcVec<T>` if it is unique, without copying
+    #[inline]
+    pub fn try_into_vec(this: Self) -> Result<ArcVec<T>, Arc<[T]>> {
+        if this.is_unique() {
+            let len = this.len();
+            track_dealloc(this.ptr.as_ptr(), ArcInner::<[T]>::slice_layout(len).0);
+            let ptr = this.ptr.cast();
+            mem::forget(this);
+            Ok(ArcVec {
+                ptr,
+                len,
+                cap: if ArcVec::<T>::IS_ZST { usize::MAX } else { len },
+                phantom: PhantomData,
+            })
+        } else {
+            Err(this)
+        }
+    }
+}
+
+impl<T: Clone> Arc<[T]> {
+    /// Get a growable buffer holding the elements of this slice, reusing its allocation if it is not shared
+    #[inline]
+    pub(crate) fn into_vec(this: Self) -> ArcVec<T> {
+        Arc::try_into_vec(this).unwrap_or_else(|shared| ArcVec::from(&*shared))
+    }
+}
+
+/// Panic because the capacity of an `ArcVec` would be too large
+#[cold]
+#[inline(never)]
+fn capacity_overflow() -> ! {
+    panic!("capacity overflow")
+}
+
+impl<T> Drop for ArcVec<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+            let (layout, inner) = self.inner_ptr();
+            dealloc(inner, layout)
+        }
+    }
+}
+
+impl<T> Default for ArcVec<T> {
+    #[inline]
+    fn default() -> ArcVec<T> {
+        ArcVec::new()
+    }
+}
+
+impl<T: Clone> Clone for ArcVec<T> {
+    #[inline]
+    fn clone(&self) -> ArcVec<T> {
+        ArcVec::from(self.as_slice())
+    }
+}
+
+impl<T> Deref for ArcVec<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for ArcVec<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> Borrow<[T]> for ArcVec<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> BorrowMut<[T]> for ArcVec<T> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> AsRef<[T]> for ArcVec<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsMut<[T]> for ArcVec<T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T> Extend<T> for ArcVec<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.push(item)
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ArcVec<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> ArcVec<T> {
+        let mut result = ArcVec::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<T: Clone> From<&[T]> for ArcVec<T> {
+    #[inline]
+    fn from(slice: &[T]) -> ArcVec<T> {
+        let mut result = ArcVec::with_capacity(slice.len());
+        result.extend_from_slice(slice);
+        result
+    }
+}
+
+impl<T> From<Vec<T>> for ArcVec<T> {
+    #[inline]
+    fn from(vec: Vec<T>) -> ArcVec<T> {
+        let mut result = ArcVec::with_capacity(vec.len());
+        result.extend(vec);
+        result
+    }
+}
+
+impl<T> From<ArcVec<T>> for Arc<[T]> {
+    #[inline]
+    fn from(vec: ArcVec<T>) -> Arc<[T]> {
+        vec.freeze()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcVec<T> {
+    #[inline]
+    fn eq(&self, other: &ArcVec<T>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq> Eq for ArcVec<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArcVec<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &ArcVec<T>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord> Ord for ArcVec<T> {
+    #[inline]
+    fn cmp(&self, other: &ArcVec<T>) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Hash> Hash for ArcVec<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for ArcVec<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for ArcVec<T> {
+    fn deserialize<D>(deserializer: D) -> Result<ArcVec<T>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        use serde::de::{SeqAccess, Visitor};
+
+        struct ArcVecVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for ArcVecVisitor<T> {
+            type Value = ArcVec<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a sequence")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<ArcVec<T>, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                // Do not trust size hints too much, to avoid giant allocations on malicious input
+                let mut result = ArcVec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(item) = seq.next_element()? {
+                    result.push(item)
+                }
+                Ok(result)
+            }
+        }
+
+        deserializer.deserialize_seq(ArcVecVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for ArcVec<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.collect()
+    }
+}
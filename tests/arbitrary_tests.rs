@@ -0,0 +1,103 @@
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
+#[cfg(feature = "arbitrary")]
+use elysees::*;
+
+/// Deterministic pseudo-random bytes to drive `Unstructured`
+#[cfg(feature = "arbitrary")]
+fn bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn arbitrary_pointer_types() {
+    for seed in 0..64 {
+        let data = bytes(seed, 256);
+        let mut u = Unstructured::new(&data);
+        let arc = Arc::<u32>::arbitrary(&mut u).unwrap();
+        assert!(arc.is_unique());
+        let slice = Arc::<[u16]>::arbitrary(&mut u).unwrap();
+        assert!(slice.is_unique());
+        let s = Arc::<str>::arbitrary(&mut u).unwrap();
+        assert!(s.is_unique());
+        let b = ArcBox::<[u8]>::arbitrary(&mut u).unwrap();
+        assert!(Arc::from(b).is_unique());
+        let v = ArcVec::<u8>::arbitrary(&mut u).unwrap();
+        assert!(v.len() <= v.capacity());
+        let r = ArcSliceRef::<u8>::arbitrary(&mut u).unwrap();
+        let range = ArcSliceRef::range(&r);
+        assert!(range.end <= ArcSliceRef::parent(&r).len());
+        let rest = Arc::<[u8]>::arbitrary_take_rest(u).unwrap();
+        assert!(rest.is_unique());
+    }
+}
+
+#[cfg(all(feature = "arbitrary", feature = "slice-dst"))]
+#[test]
+fn arbitrary_slice_dst() {
+    use slice_dst::{SliceWithHeader, StrWithHeader};
+    for seed in 0..64 {
+        let data = bytes(seed, 128);
+        let mut u = Unstructured::new(&data);
+        let a = Arc::<SliceWithHeader<u8, u16>>::arbitrary(&mut u).unwrap();
+        assert!(a.is_unique());
+        let b = Arc::<StrWithHeader<bool>>::arbitrary(&mut u).unwrap();
+        assert!(b.is_unique());
+        #[cfg(feature = "erasable")]
+        {
+            let s = ArcStr::arbitrary(&mut u).unwrap();
+            assert!(s.is_unique());
+            let v = ArcSlice::<u8>::arbitrary(&mut u).unwrap();
+            assert!(v.is_unique());
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn arbitrary_sharing() {
+    let mut shared = 0;
+    let mut unique = 0;
+    for seed in 0..256 {
+        let data = bytes(seed, 512);
+        let mut u = Unstructured::new(&data);
+        let SharedArcs(mut arcs) = SharedArcs::<[u8]>::arbitrary(&mut u).unwrap();
+        let counts: Vec<_> = arcs
+            .iter()
+            .map(|arc| arcs.iter().filter(|other| Arc::ptr_eq(arc, other)).count())
+            .collect();
+        for (arc, &count) in arcs.iter().zip(&counts) {
+            assert_eq!(arc.is_unique(), count == 1);
+            if count == 1 {
+                unique += 1
+            } else {
+                shared += 1
+            }
+        }
+        // Mutating a shared Arc must not affect its other clones
+        if let Some(first) = arcs.first().cloned() {
+            let len = first.len();
+            if len > 0 {
+                Arc::make_mut(&mut arcs[0])[0] ^= 0xFF;
+                assert_ne!(arcs[0][0], first[0]);
+                for (arc, &count) in arcs.iter().zip(&counts).skip(1) {
+                    if Arc::ptr_eq(arc, &first) {
+                        assert!(count > 1);
+                        assert_eq!(arc[0], first[0]);
+                    }
+                }
+            }
+        }
+    }
+    assert!(shared > 0);
+    assert!(unique > 0);
+}
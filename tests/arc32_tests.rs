@@ -0,0 +1,79 @@
+use elysees::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+fn arc32_layout() {
+    assert_eq!(Arc32Inner::data_offset(&5u32).0.size(), 8);
+    assert_eq!(Arc32Inner::data_offset(&5u8).0.size(), 8);
+    assert_eq!(Arc32Inner::data_offset(&[0u16; 2][..]).0.size(), 8);
+    assert_eq!(ArcInner::data_offset(&5u32).0.size(), 16);
+    assert_eq!(
+        std::mem::size_of::<Arc32<u32>>(),
+        std::mem::size_of::<usize>()
+    );
+}
+
+#[test]
+fn basic_arc32_usage() {
+    let x = Arc32::new(String::from("interned"));
+    assert!(x.is_unique());
+    let mut y = x.clone();
+    assert_eq!(Arc32::count(&x, Relaxed), 2);
+    assert!(Arc32::get_mut(&mut y).is_none());
+    Arc32::make_mut(&mut y).push('!');
+    assert_eq!(*x, "interned");
+    assert_eq!(*y, "interned!");
+    assert!(!Arc32::ptr_eq(&x, &y));
+    assert!(x.is_unique());
+
+    let raw = Arc32::into_raw(x);
+    let x = unsafe { Arc32::from_raw(raw) };
+    assert_eq!(format!("{} {:?}", x, y), "interned \"interned!\"");
+    assert!(x < y);
+
+    let slice: Arc32<[u8]> = Arc32::from(&[1, 2, 3][..]);
+    let mut copy = slice.clone();
+    Arc32::make_mut(&mut copy)[0] = 7;
+    assert_eq!(*slice, [1, 2, 3]);
+    assert_eq!(*copy, [7, 2, 3]);
+    let s: Arc32<str> = "hello".into();
+    assert_eq!(&*s, "hello");
+}
+
+fn panic_on_overflow(_count: usize) -> OverflowAction {
+    OverflowAction::Panic
+}
+
+fn saturate_on_overflow(_count: usize) -> OverflowAction {
+    OverflowAction::Saturate
+}
+
+/// The overflow hook is global, so every scenario is run sequentially in a single test
+#[test]
+fn arc32_overflow() {
+    let default = set_overflow_hook(Some(panic_on_overflow));
+
+    // The limit is `i32::MAX`, rather than `isize::MAX`
+    let x = Arc32::new(5u32);
+    Arc32::increment_by(&x, i32::MAX as u32 - 1);
+    assert_eq!(Arc32::count(&x, Relaxed), i32::MAX as u32);
+    let y = x.clone();
+    assert!(catch_unwind(AssertUnwindSafe(|| x.clone())).is_err());
+    assert_eq!(Arc32::count(&x, Relaxed), i32::MAX as u32 + 1);
+    drop(y);
+    unsafe { Arc32::decrement_by(&x, i32::MAX as u32 - 1) };
+    assert!(x.is_unique());
+
+    set_overflow_hook(Some(saturate_on_overflow));
+    Arc32::increment_by(&x, u32::MAX);
+    let saturated = Arc32::count(&x, Relaxed);
+    assert!(saturated > i32::MAX as u32);
+    // Releases leave the count pinned, however many references they drop
+    drop(x.clone());
+    unsafe { Arc32::decrement_by(&x, u32::MAX) };
+    assert_eq!(Arc32::count(&x, Relaxed), saturated);
+    drop(x);
+
+    set_overflow_hook(default);
+}
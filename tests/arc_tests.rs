@@ -197,3 +197,221 @@ fn arc_hash() {
     assert!(map.insert(Arc::new(9)));
     assert!(!map.insert(Arc::new(7)));
 }
+
+#[test]
+fn arc_slice_construction() {
+    let from_vec: Arc<[usize]> = vec![1, 2, 3].into();
+    assert_eq!(*from_vec, [1, 2, 3]);
+    assert!(from_vec.is_unique());
+    let from_slice: Arc<[usize]> = Arc::from(&from_vec[1..]);
+    assert_eq!(*from_slice, [2, 3]);
+    let from_iter: Arc<[String]> = (0..5).map(|x| x.to_string()).collect();
+    assert_eq!(from_iter.len(), 5);
+    assert_eq!(from_iter[3], "3");
+    let empty: Arc<[String]> = Vec::new().into();
+    assert!(empty.is_empty());
+    let from_str: Arc<str> = "Hello".into();
+    assert_eq!(&*from_str, "Hello");
+    let from_string: Arc<str> = String::from("world").into();
+    assert_eq!(&*from_string, "world");
+    let zsts: Arc<[()]> = vec![(); 10].into();
+    assert_eq!(zsts.len(), 10);
+}
+
+#[test]
+fn unsized_make_mut() {
+    let mut slice: Arc<[String]> = vec!["a".to_string(), "b".to_string()].into();
+    let ptr = Arc::as_ptr(&slice);
+    Arc::make_mut(&mut slice)[0].push('c');
+    assert_eq!(Arc::as_ptr(&slice), ptr);
+    assert_eq!(*slice, ["ac", "b"]);
+    let shared = slice.clone();
+    Arc::make_mut(&mut slice)[1].push('d');
+    assert!(!Arc::ptr_eq(&slice, &shared));
+    assert!(slice.is_unique());
+    assert!(shared.is_unique());
+    assert_eq!(*slice, ["ac", "bd"]);
+    assert_eq!(*shared, ["ac", "b"]);
+
+    let mut s: Arc<str> = "hello".into();
+    let t = s.clone();
+    Arc::make_mut(&mut s).make_ascii_uppercase();
+    assert_eq!(&*s, "HELLO");
+    assert_eq!(&*t, "hello");
+
+    let u = Arc::unique(t.clone());
+    assert_eq!(&*u, "hello");
+    assert!(!Arc::ptr_eq(&u.shareable(), &t));
+    let mut v = Arc::unique(t);
+    v.make_ascii_uppercase();
+    let w = v.clone();
+    assert_eq!(&*v, "HELLO");
+    assert_eq!(&*w, "HELLO");
+
+    let empty: Arc<[u8]> = Vec::new().into();
+    let empty_clone = Arc::new_clone(&*empty);
+    assert!(empty_clone.is_empty());
+    assert!(empty.is_unique());
+}
+
+#[test]
+fn bulk_refcount_operations() {
+    let x = Arc::new(String::from("config"));
+    let clones: Vec<_> = Arc::clone_n(&x, 5).collect();
+    assert_eq!(Arc::count(&x, Relaxed), 6);
+    assert!(clones.iter().all(|c| Arc::ptr_eq(c, &x)));
+    std::mem::drop(clones);
+    assert!(x.is_unique());
+
+    let mut partial = Arc::clone_n(&x, 10);
+    assert_eq!(partial.len(), 10);
+    let first = partial.next().unwrap();
+    let second = partial.next().unwrap();
+    assert_eq!(partial.len(), 8);
+    assert_eq!(Arc::count(&x, Relaxed), 11);
+    std::mem::drop(partial);
+    assert_eq!(Arc::count(&x, Relaxed), 3);
+    std::mem::drop((first, second));
+
+    Arc::increment_by(&x, 4);
+    assert_eq!(Arc::count(&x, Relaxed), 5);
+    unsafe { Arc::decrement_by(&x, 2) };
+    assert_eq!(Arc::count(&x, Relaxed), 3);
+    let borrowed = x.borrow_arc();
+    let mut reclaimed = unsafe { borrowed.into_arc_n(2) };
+    assert_eq!(*reclaimed.next().unwrap(), "config");
+    std::mem::drop(reclaimed);
+    assert!(x.is_unique());
+
+    // Dropping the original before the remaining clones
+    let y = Arc::new(vec![1, 2, 3]);
+    let clones = Arc::clone_n(&y, 3);
+    std::mem::drop(y);
+    assert_eq!(format!("{:?}", clones.len()), "3");
+    std::mem::drop(clones);
+
+    let z = Arc::new(0);
+    assert_eq!(Arc::clone_n(&z, 0).count(), 0);
+    assert!(z.is_unique());
+}
+
+/// Sum a value through any pointer into an `Arc`, reporting how many owners it has
+fn sum_with_count<P: ArcPtr<[u32]>>(pointer: &P) -> (u32, usize) {
+    let sum = P::get(pointer).iter().sum();
+    (sum, P::count(pointer, Relaxed))
+}
+
+#[test]
+fn arc_ptr_abstracts_over_pointers() {
+    let arc: Arc<[u32]> = Arc::from(&[1, 2, 3][..]);
+    assert_eq!(sum_with_count(&arc), (6, 1));
+    assert!(ArcPtr::is_unique(&arc));
+    let borrowed = arc.borrow_arc();
+    assert_eq!(ArcPtr::as_ptr(&borrowed), Arc::as_ptr(&arc));
+    let clone = SharedArcPtr::clone_arc(&borrowed);
+    assert!(Arc::ptr_eq(&clone, &arc));
+    assert_eq!(sum_with_count(&borrowed), (6, 2));
+    assert!(!ArcPtr::is_unique(&borrowed));
+    drop(clone);
+
+    let mut unique = Arc::unique(arc);
+    unique[0] = 10;
+    assert_eq!(sum_with_count(&unique), (15, 1));
+    assert!(ArcPtr::is_unique(&unique));
+    assert_eq!(ArcPtr::get(&unique), [10, 2, 3]);
+}
+
+/// Stands in for a C function taking a nullable `const uint32_t*`
+extern "C" fn read_nullable(ptr: *const u32) -> u32 {
+    if ptr.is_null() {
+        0
+    } else {
+        unsafe { *ptr }
+    }
+}
+
+/// Stands in for a Rust function exported to C, taking ownership of a nullable `const uint32_t*`
+extern "C" fn consume_nullable(arc: NullableArc<u32>) -> u32 {
+    arc.as_deref().copied().unwrap_or(0)
+}
+
+#[test]
+fn nullable_arc_round_trips() {
+    let arc = Arc::new(7u32);
+    let raw = Arc::option_into_raw(Some(arc.clone()));
+    assert_eq!(raw, Arc::as_ptr(&arc));
+    assert_eq!(read_nullable(raw), 7);
+    assert_eq!(
+        unsafe { ArcBorrow::from_nullable(raw) }.map(|borrow| *borrow.get()),
+        Some(7)
+    );
+    let back = unsafe { Arc::option_from_raw(raw) }.unwrap();
+    assert!(Arc::ptr_eq(&back, &arc));
+    assert!(Arc::option_into_raw(None::<Arc<u32>>).is_null());
+    assert!(unsafe { Arc::<u32>::option_from_raw(std::ptr::null()) }.is_none());
+    assert!(unsafe { ArcBorrow::<u32>::from_nullable(std::ptr::null()) }.is_none());
+
+    // C can hand ownership of a `T*` to a function taking a `NullableArc<T>`
+    let consume: extern "C" fn(*const u32) -> u32 =
+        unsafe { std::mem::transmute(consume_nullable as extern "C" fn(NullableArc<u32>) -> u32) };
+    assert_eq!(consume(Arc::into_raw(arc.clone())), 7);
+    assert_eq!(consume(std::ptr::null()), 0);
+    assert_eq!(Arc::count(&arc, Relaxed), 2);
+    let mut nullable = NullableArc::from(back);
+    assert_eq!(read_nullable(NullableArc::as_ptr(&nullable)), 7);
+    assert_eq!(nullable.borrow_arc().map(|borrow| *borrow.get()), Some(7));
+    assert_eq!(format!("{:?}", nullable), "NullableArc(7)");
+    let taken = nullable.take().unwrap();
+    assert!(nullable.is_null());
+    assert_eq!(read_nullable(NullableArc::as_ptr(&nullable)), 0);
+    assert_eq!(format!("{:?}", nullable), "NullableArc(null)");
+    assert_eq!(nullable, NullableArc::default());
+    assert!(nullable.replace(taken).is_none());
+    assert_eq!(nullable, NullableArc::new(7));
+    assert_eq!(*nullable.as_ref(), Arc::as_ptr(&arc));
+    let option: Option<Arc<u32>> = nullable.into();
+    assert!(Arc::ptr_eq(&option.unwrap(), &arc));
+    assert!(arc.is_unique());
+}
+
+/// Stands in for an API which takes borrowed handles
+fn first_and_count(borrow: ArcBorrow<'_, Vec<u32>>) -> (u32, usize) {
+    (borrow[0], ArcBorrow::count(borrow, Relaxed))
+}
+
+#[test]
+fn arc_borrow_handles() {
+    let arc = Arc::new(vec![1, 2, 3]);
+    let borrow: ArcBorrow<'_, _> = (&arc).into();
+    assert!(ArcBorrow::ptr_eq(borrow, arc.borrow_arc()));
+    assert_eq!(first_and_count(borrow.reborrow()), (1, 1));
+
+    struct Holder<'a> {
+        borrow: ArcBorrow<'a, Vec<u32>>,
+    }
+    let holder = Holder { borrow };
+    assert_eq!(first_and_count(holder.borrow.reborrow()), (1, 1));
+
+    let tail = ArcBorrow::map(borrow, |vec| &vec[1..]);
+    assert_eq!(*tail, [2, 3]);
+    let last = MappedArcBorrow::map(tail, |tail| &tail[1]);
+    assert_eq!(format!("{:?} {:?}", tail, last), "[2, 3] 3");
+    assert!(ArcBorrow::ptr_eq(MappedArcBorrow::owner(last), borrow));
+    let owner = MappedArcBorrow::clone_owner(&last);
+    assert_eq!(Arc::count(&arc, Relaxed), 2);
+    drop(owner);
+
+    let mut unique = ArcBox::new(vec![4, 5]);
+    // Nothing keeps an `Arc` cloned from the borrow beyond this call
+    assert_eq!(first_and_count(unsafe { unique.borrow_arc() }), (4, 1));
+    unique.push(6);
+    assert_eq!(*unique, [4, 5, 6]);
+
+    #[cfg(feature = "erasable")]
+    {
+        let thin: erasable::Thin<Arc<u32>> = Arc::new(7).into();
+        let borrow = ArcBorrow::from(&thin);
+        assert_eq!(*borrow.get(), 7);
+        assert_eq!(ArcBorrow::count(borrow, Relaxed), 1);
+    }
+}
@@ -0,0 +1,152 @@
+#![cfg(feature = "std")]
+use elysees::*;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+use std::thread;
+
+#[test]
+fn cache_sweeps_unreferenced_values() {
+    let cache = ArcCache::new();
+    assert!(cache.is_empty());
+    assert_eq!(cache.max_entries(), None);
+    let hello = cache.get_or_insert_with("hello".to_string(), || 5);
+    let world = cache.get_or_insert_with("world".to_string(), || 5);
+    assert!(!Arc::ptr_eq(&hello, &world));
+    assert!(Arc::ptr_eq(&cache.get("hello").unwrap(), &hello));
+    assert!(cache.contains_key("world"));
+    assert!(cache.get("other").is_none());
+
+    // Nothing is swept while it is referenced elsewhere
+    assert_eq!(cache.sweep(), 0);
+    drop(world);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.sweep(), 1);
+    assert!(!cache.contains_key("world"));
+    assert_eq!(*cache.get_or_insert_with("world".to_string(), || 6), 6);
+
+    let old = cache.insert("hello".to_string(), Arc::new(7)).unwrap();
+    assert!(Arc::ptr_eq(&old, &hello));
+    assert_eq!(*cache.get("hello").unwrap(), 7);
+    assert_eq!(*cache.remove("hello").unwrap(), 7);
+    assert!(cache.remove("hello").is_none());
+    cache.clear();
+    assert!(cache.is_empty());
+    drop(old);
+    assert!(hello.is_unique());
+
+    // Inserting sweeps the cache often enough to keep unreferenced values from piling up
+    for i in 0..1000 {
+        cache.get_or_insert_with(i.to_string(), || i);
+        assert!(cache.len() <= 64);
+    }
+    let kept: Vec<_> = (0..100)
+        .map(|i| cache.get_or_insert_with(format!("kept {}", i), || i))
+        .collect();
+    for i in 0..1000 {
+        cache.get_or_insert_with(i.to_string(), || i);
+    }
+    for (i, value) in kept.iter().enumerate() {
+        assert!(Arc::ptr_eq(
+            &cache.get(&format!("kept {}", i)).unwrap(),
+            value
+        ));
+    }
+    assert_eq!(
+        format!("{:?}", ArcCache::<u8, u8>::new()),
+        "ArcCache { len: 0, max_entries: None, .. }"
+    );
+}
+
+#[test]
+fn cache_evicts_least_recently_used() {
+    let cache = ArcCache::with_max_entries(3);
+    assert_eq!(cache.max_entries(), Some(3));
+    let a = cache.get_or_insert_with('a', || 1);
+    cache.get_or_insert_with('b', || 2);
+    cache.get_or_insert_with('c', || 3);
+    cache.get(&'b');
+
+    // Unreferenced entries are forgotten first, least recently used first
+    cache.get_or_insert_with('d', || 4);
+    assert_eq!(cache.len(), 3);
+    assert!(!cache.contains_key(&'c'));
+    cache.get_or_insert_with('e', || 5);
+    assert!(!cache.contains_key(&'b'));
+    assert!(cache.contains_key(&'a'));
+
+    // Failing that, referenced entries are forgotten, but stay alive for their owners
+    let d = cache.get(&'d').unwrap();
+    let e = cache.get(&'e').unwrap();
+    cache.get_or_insert_with('f', || 6);
+    assert!(!cache.contains_key(&'a'));
+    assert_eq!(*a, 1);
+    let new_a = cache.get_or_insert_with('a', || 10);
+    assert_eq!(*new_a, 10);
+    assert!(!cache.contains_key(&'f'));
+    assert!(Arc::ptr_eq(&cache.get(&'d').unwrap(), &d));
+    assert!(Arc::ptr_eq(&cache.get(&'e').unwrap(), &e));
+    assert_eq!(cache.len(), 3);
+}
+
+#[test]
+fn cache_concurrent_memoization() {
+    const THREADS: usize = 8;
+    const KEYS: u64 = 100;
+    let cache = ArcCache::with_max_entries(2 * KEYS as usize);
+    let computed = AtomicUsize::new(0);
+    let results: Vec<Vec<Arc<u64>>> = thread::scope(|scope| {
+        let threads: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let (cache, computed) = (&cache, &computed);
+                scope.spawn(move || {
+                    (0..KEYS)
+                        .map(|key| {
+                            cache.get_or_insert_with(key, || {
+                                computed.fetch_add(1, SeqCst);
+                                // Computing a value may use the cache itself
+                                if key > 0 {
+                                    *cache.get_or_insert_with(key + KEYS, || 0) + key * key
+                                } else {
+                                    0
+                                }
+                            })
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        threads.into_iter().map(|t| t.join().unwrap()).collect()
+    });
+    // Every thread got the same value for each key, however many threads computed one
+    for values in &results[1..] {
+        for (value, first) in values.iter().zip(&results[0]) {
+            assert!(Arc::ptr_eq(value, first));
+        }
+    }
+    for (key, value) in results[0].iter().enumerate() {
+        assert_eq!(**value, (key * key) as u64);
+    }
+    assert!(computed.load(SeqCst) >= KEYS as usize);
+    drop(results);
+    let len = cache.len();
+    assert_eq!(cache.sweep(), len);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn cache_poisoned_by_panicking_keys() {
+    /// A key which panics when cloned, which the cache only does while locked
+    #[derive(Hash, PartialEq, Eq)]
+    struct Fragile(u32);
+
+    impl Clone for Fragile {
+        fn clone(&self) -> Fragile {
+            panic!("cloned a fragile key")
+        }
+    }
+
+    let cache = ArcCache::new();
+    let inserted = std::panic::catch_unwind(|| cache.get_or_insert_with(Fragile(1), || 1));
+    assert!(inserted.is_err());
+    let looked_up = std::panic::catch_unwind(|| cache.get(&Fragile(1)));
+    assert!(looked_up.is_err());
+}
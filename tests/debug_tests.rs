@@ -0,0 +1,125 @@
+#[cfg(feature = "debug-tracking")]
+use elysees::*;
+#[cfg(feature = "debug-tracking")]
+use std::mem::ManuallyDrop;
+
+#[cfg(feature = "debug-tracking")]
+fn find<T: ?Sized>(ptr: *const T) -> Option<debug::LiveAllocation> {
+    debug::live_allocations()
+        .into_iter()
+        .find(|allocation| allocation.address() == ptr as *const ())
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+fn live_allocations_are_tracked() {
+    let arc = Arc::new(5u64);
+    let clone = arc.clone();
+    let allocation = find(Arc::as_ptr(&arc)).expect("Arc::new is registered");
+    assert_eq!(allocation.type_name(), "u64");
+    assert_eq!(allocation.count(), 2);
+
+    let slice: Arc<[u8]> = Arc::from(vec![1, 2, 3]);
+    assert_eq!(find(Arc::as_ptr(&slice)).unwrap().type_name(), "[u8]");
+    let copy = Arc::new_clone(&*slice);
+    assert!(debug::is_live(Arc::as_ptr(&copy)));
+
+    // Converting to an `ArcVec` and back unregisters and re-registers the allocation
+    let address = Arc::as_ptr(&copy);
+    let mut vec = Arc::try_into_vec(copy).unwrap();
+    assert!(!debug::is_live(address));
+    vec.push(4);
+    let frozen = vec.freeze();
+    assert_eq!(find(Arc::as_ptr(&frozen)).unwrap().count(), 1);
+
+    let address = Arc::as_ptr(&arc);
+    drop(arc);
+    assert!(debug::is_live(address));
+    drop(clone);
+    assert!(!debug::is_live(address));
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+#[should_panic(expected = "not a live Arc allocation")]
+fn double_free_panics() {
+    let arc = Arc::new(String::from("double free"));
+    let raw = Arc::into_raw(arc.clone());
+    unsafe {
+        drop(Arc::from_raw(raw));
+        // Releases the reference still owned by `arc`, freeing the allocation
+        drop(Arc::from_raw(raw));
+    }
+    drop(arc)
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+#[should_panic(expected = "Arc::from_raw")]
+fn from_raw_after_free_panics() {
+    let raw = Arc::into_raw(Arc::new(7u32));
+    unsafe {
+        drop(Arc::from_raw(raw));
+        Arc::from_raw(raw);
+    }
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+#[should_panic(expected = "ArcBorrow::from_raw")]
+fn borrow_after_free_panics() {
+    let raw = Arc::into_raw(Arc::new(7u32));
+    unsafe {
+        drop(Arc::from_raw(raw));
+        ArcBorrow::from_raw(raw);
+    }
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+#[should_panic(expected = "reference count underflow")]
+fn underflow_panics() {
+    let arc = ManuallyDrop::new(Arc::new(7u32));
+    unsafe { Arc::decrement_by(&arc, 2) }
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+fn arc32_allocations_are_tracked() {
+    let arc = Arc32::new(5u16);
+    let clone = arc.clone();
+    let allocation = find(Arc32::as_ptr(&arc)).expect("Arc32::new is registered");
+    assert_eq!(allocation.type_name(), "u16");
+    assert_eq!(allocation.count(), 2);
+
+    let copy = Arc32::new_clone("copy");
+    assert_eq!(find(Arc32::as_ptr(&copy)).unwrap().type_name(), "str");
+    let address = Arc32::as_ptr(&copy);
+    drop(copy);
+    assert!(!debug::is_live(address));
+
+    let address = Arc32::as_ptr(&arc);
+    drop(arc);
+    assert_eq!(find(address).unwrap().count(), 1);
+    drop(clone);
+    assert!(!debug::is_live(address));
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+#[should_panic(expected = "Arc32::from_raw")]
+fn arc32_from_raw_after_free_panics() {
+    let raw = Arc32::into_raw(Arc32::new(7u32));
+    unsafe {
+        drop(Arc32::from_raw(raw));
+        Arc32::from_raw(raw);
+    }
+}
+
+#[cfg(feature = "debug-tracking")]
+#[test]
+#[should_panic(expected = "reference count underflow")]
+fn arc32_underflow_panics() {
+    let arc = ManuallyDrop::new(Arc32::new(7u32));
+    unsafe { Arc32::decrement_by(&arc, 2) }
+}
@@ -3,7 +3,7 @@ use elysees::*;
 #[cfg(feature = "slice-dst")]
 use erasable::Thin;
 #[cfg(feature = "slice-dst")]
-use slice_dst::{SliceWithHeader, TryAllocSliceDst};
+use slice_dst::{SliceWithHeader, StrWithHeader, TryAllocSliceDst};
 #[cfg(feature = "slice-dst")]
 use std::borrow::BorrowMut;
 #[cfg(feature = "slice-dst")]
@@ -110,3 +110,51 @@ fn failed_dst_alloc() {
         assert_eq!(ArcBox::try_new_slice_dst(57, fallible_alloc), Err("Bad!"));
     }
 }
+
+#[cfg(feature = "slice-dst")]
+#[test]
+fn dst_make_mut() {
+    let mut arc: Arc<SliceWithHeader<String, String>> =
+        SliceWithHeader::new("header".to_string(), (0..5).map(|x| x.to_string()));
+    let shared = arc.clone();
+    let unique = Arc::make_mut(&mut arc);
+    unique.header.push('!');
+    unique.slice[3] = "three".to_string();
+    assert_eq!(arc.header, "header!");
+    assert_eq!(arc.slice, ["0", "1", "2", "three", "4"]);
+    assert_eq!(shared.header, "header");
+    assert_eq!(shared.slice, ["0", "1", "2", "3", "4"]);
+    assert!(arc.is_unique());
+    let thin: Thin<_> = arc.into();
+    assert_eq!(thin.slice.len(), 5);
+
+    let arc: Arc<StrWithHeader<u32>> = StrWithHeader::new(5, "hello");
+    let mut unique = Arc::unique(arc.clone());
+    unique.header += 1;
+    unique.str.make_ascii_uppercase();
+    assert_eq!(unique.header, 6);
+    assert_eq!(&unique.str, "HELLO");
+    assert_eq!(arc.header, 5);
+    assert_eq!(&arc.str, "hello");
+}
+
+#[cfg(feature = "slice-dst")]
+#[test]
+fn thin_arc_ptr_test() {
+    fn header<P: SharedArcPtr<SliceWithHeader<u8, u8>>>(pointer: &P) -> u8 {
+        P::borrow_arc(pointer).header
+    }
+    let arc: Arc<SliceWithHeader<u8, u8>> = SliceWithHeader::new(7, 0..3);
+    let thin: Thin<Arc<_>> = arc.clone().into();
+    assert_eq!(header(&thin), 7);
+    assert_eq!(ArcPtr::as_ptr(&thin), Arc::as_ptr(&arc));
+    assert_eq!(
+        ArcPtr::count(&thin, std::sync::atomic::Ordering::Relaxed),
+        2
+    );
+    assert!(Arc::ptr_eq(&SharedArcPtr::clone_arc(&thin), &arc));
+    let thin_borrowed: Thin<ArcBorrow<_>> = arc.borrow_arc().into();
+    assert_eq!(header(&thin_borrowed), 7);
+    drop(thin);
+    assert!(ArcPtr::is_unique(&thin_borrowed));
+}
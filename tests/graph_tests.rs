@@ -0,0 +1,295 @@
+use elysees::*;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Const(i64),
+    Var(String),
+    Add(Arc<Expr>, Arc<Expr>),
+    Call(Arc<Name>, Vec<Arc<Expr>>),
+}
+
+#[derive(Debug, PartialEq)]
+struct Name(String);
+
+impl ArcGraphNode for Name {
+    fn encode(&self, encoder: &mut ArcGraphEncoder) {
+        encoder.write_str(&self.0)
+    }
+    fn decode(decoder: &mut ArcGraphDecoder) -> Result<Name, ArcGraphError> {
+        Ok(Name(decoder.read_str()?.to_owned()))
+    }
+}
+
+impl ArcGraphNode for Expr {
+    fn encode(&self, encoder: &mut ArcGraphEncoder) {
+        match self {
+            Expr::Const(c) => {
+                encoder.write_u8(0);
+                encoder.write_i64(*c)
+            }
+            Expr::Var(v) => {
+                encoder.write_u8(1);
+                encoder.write_str(v)
+            }
+            Expr::Add(l, r) => {
+                encoder.write_u8(2);
+                encoder.write_arc(l);
+                encoder.write_arc(r)
+            }
+            Expr::Call(f, args) => {
+                encoder.write_u8(3);
+                encoder.write_arc(f);
+                encoder.write_len(args.len());
+                for arg in args {
+                    encoder.write_arc(arg)
+                }
+            }
+        }
+    }
+    fn decode(decoder: &mut ArcGraphDecoder) -> Result<Expr, ArcGraphError> {
+        match decoder.read_u8()? {
+            0 => Ok(Expr::Const(decoder.read_i64()?)),
+            1 => Ok(Expr::Var(decoder.read_str()?.to_owned())),
+            2 => Ok(Expr::Add(decoder.read_arc()?, decoder.read_arc()?)),
+            3 => {
+                let f = decoder.read_arc()?;
+                let len = decoder.read_len()?;
+                let args = (0..len)
+                    .map(|_| decoder.read_arc())
+                    .collect::<Result<_, _>>()?;
+                Ok(Expr::Call(f, args))
+            }
+            _ => Err(ArcGraphError::Invalid("unknown expression tag")),
+        }
+    }
+}
+
+/// Collect the addresses of every node reachable from `expr`, in a canonical order, numbering each allocation
+fn shape(expr: &Arc<Expr>, ids: &mut HashMap<*const (), usize>, out: &mut Vec<usize>) {
+    let len = ids.len();
+    let id = *ids.entry(Arc::as_ptr(expr) as *const ()).or_insert(len);
+    out.push(id);
+    if id != len {
+        return;
+    }
+    match &**expr {
+        Expr::Add(l, r) => {
+            shape(l, ids, out);
+            shape(r, ids, out)
+        }
+        Expr::Call(f, args) => {
+            let len = ids.len();
+            out.push(*ids.entry(Arc::as_ptr(f) as *const ()).or_insert(len));
+            for arg in args {
+                shape(arg, ids, out)
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Round-trip a set of roots, checking that both their values and sharing structure are preserved
+fn round_trip(roots: &[Arc<Expr>]) -> Vec<Arc<Expr>> {
+    let mut writer = ArcGraphWriter::new();
+    for root in roots {
+        writer.write_root(root);
+    }
+    let nodes = writer.len();
+    let bytes = writer.finish();
+    let mut reader = ArcGraphReader::new(&bytes).unwrap();
+    assert_eq!(reader.len(), nodes);
+    let loaded: Vec<Arc<Expr>> = (0..roots.len())
+        .map(|i| reader.read_root(i).unwrap())
+        .collect();
+    assert_eq!(loaded, roots);
+    let (mut before, mut after) = (Vec::new(), Vec::new());
+    let (mut before_ids, mut after_ids) = (HashMap::new(), HashMap::new());
+    for (root, loaded) in roots.iter().zip(&loaded) {
+        shape(root, &mut before_ids, &mut before);
+        shape(loaded, &mut after_ids, &mut after);
+    }
+    assert_eq!(before, after);
+    loaded
+}
+
+#[test]
+fn graph_round_trip() {
+    let x = Arc::new(Expr::Var("x".into()));
+    let one = Arc::new(Expr::Const(-1));
+    let sum = Arc::new(Expr::Add(x.clone(), one.clone()));
+    let twice = Arc::new(Expr::Add(sum.clone(), sum.clone()));
+    let f = Arc::new(Name("f".into()));
+    let call = Arc::new(Expr::Call(f.clone(), vec![twice.clone(), sum.clone(), x]));
+    let other = Arc::new(Expr::Call(f, vec![]));
+
+    let loaded = round_trip(&[call.clone(), twice.clone(), call, other]);
+    assert!(Arc::ptr_eq(&loaded[0], &loaded[2]));
+    match (&*loaded[1], &*loaded[3]) {
+        (Expr::Add(l, r), Expr::Call(f, _)) => {
+            assert!(Arc::ptr_eq(l, r));
+            match &*loaded[0] {
+                Expr::Call(g, args) => {
+                    assert!(Arc::ptr_eq(f, g));
+                    assert!(Arc::ptr_eq(&args[0], &loaded[1]));
+                    assert!(Arc::ptr_eq(&args[1], l));
+                }
+                _ => unreachable!(),
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    assert!(round_trip(&[]).is_empty());
+    round_trip(&[
+        Arc::new(Expr::Const(i64::MIN)),
+        Arc::new(Expr::Const(i64::MAX)),
+    ]);
+}
+
+#[test]
+fn deep_sharing_is_linear() {
+    // A chain where every node references the previous one twice would be exponential as a tree
+    let mut expr = Arc::new(Expr::Const(0));
+    for _ in 0..64 {
+        expr = Arc::new(Expr::Add(expr.clone(), expr));
+    }
+    let mut writer = ArcGraphWriter::new();
+    writer.write_root(&expr);
+    assert_eq!(writer.len(), 65);
+    let bytes = writer.finish();
+    assert!(bytes.len() < 65 * 8);
+    let mut loaded: Arc<Expr> = ArcGraphReader::new(&bytes).unwrap().read_root(0).unwrap();
+    for _ in 0..64 {
+        loaded = match &*loaded {
+            Expr::Add(l, r) => {
+                assert!(Arc::ptr_eq(l, r));
+                l.clone()
+            }
+            _ => unreachable!(),
+        }
+    }
+    assert_eq!(*loaded, Expr::Const(0));
+}
+
+#[test]
+fn invalid_graphs() {
+    assert_eq!(
+        ArcGraphReader::new(b"nope").unwrap_err(),
+        ArcGraphError::InvalidMagic
+    );
+    let x = Arc::new(Expr::Var("x".into()));
+    let mut writer = ArcGraphWriter::new();
+    writer.write_root(&Arc::new(Expr::Add(x.clone(), x)));
+    let bytes = writer.finish();
+    for len in 4..bytes.len() {
+        assert!(ArcGraphReader::new(&bytes[..len]).is_err());
+    }
+    let mut reader = ArcGraphReader::new(&bytes).unwrap();
+    assert_eq!(
+        reader.read::<Name>(0).unwrap_err(),
+        ArcGraphError::TrailingBytes(0)
+    );
+    assert!(reader.read::<Expr>(0).is_ok());
+    assert_eq!(
+        reader.read::<Name>(0).unwrap_err(),
+        ArcGraphError::TypeMismatch(0)
+    );
+    assert_eq!(
+        reader.read::<Expr>(2).unwrap_err(),
+        ArcGraphError::InvalidId(2)
+    );
+
+    // Node 0 referencing node 1
+    let forward = b"EAG\x01\x02\x03\x02\x01\x01\x02\x00\x00\x01\x00";
+    let mut reader = ArcGraphReader::new(forward).unwrap();
+    assert_eq!(
+        reader.read_root::<Expr>(0).unwrap_err(),
+        ArcGraphError::ForwardReference(1)
+    );
+}
+
+/// A singly linked list, dropped iteratively so that long lists do not overflow the stack when dropped either
+struct Link(u64, Option<Arc<Link>>);
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        let mut next = self.1.take();
+        while let Some(link) = next {
+            next = Arc::try_unique(link)
+                .ok()
+                .and_then(|mut link| link.1.take());
+        }
+    }
+}
+
+impl ArcGraphNode for Link {
+    fn encode(&self, encoder: &mut ArcGraphEncoder) {
+        encoder.write_u64(self.0);
+        encoder.write_bool(self.1.is_some());
+        if let Some(next) = &self.1 {
+            encoder.write_arc(next)
+        }
+    }
+    fn decode(decoder: &mut ArcGraphDecoder) -> Result<Link, ArcGraphError> {
+        let value = decoder.read_u64()?;
+        let next = if decoder.read_bool()? {
+            Some(decoder.read_arc()?)
+        } else {
+            None
+        };
+        Ok(Link(value, next))
+    }
+}
+
+#[test]
+fn deep_graphs() {
+    const LEN: u64 = 100_000;
+    let mut list = Arc::new(Link(0, None));
+    for i in 1..LEN {
+        list = Arc::new(Link(i, Some(list)));
+    }
+    let mut writer = ArcGraphWriter::new();
+    writer.write_root(&list);
+    assert_eq!(writer.len(), LEN as usize);
+    let bytes = writer.finish();
+
+    let mut reader = ArcGraphReader::new(&bytes).unwrap();
+    let loaded: Arc<Link> = reader.read_root(0).unwrap();
+    let mut node = Some(&loaded);
+    for i in (0..LEN).rev() {
+        let link = node.unwrap();
+        assert_eq!(link.0, i);
+        node = link.1.as_ref();
+    }
+    assert!(node.is_none());
+    // Nodes deferred while decoding are shared like any other
+    let middle: Arc<Link> = reader.read(LEN / 2).unwrap();
+    assert_eq!(middle.0, LEN / 2);
+}
+
+/// A node made of elements which take no bytes to encode
+#[derive(Debug, PartialEq)]
+struct Units(Vec<()>);
+
+impl ArcGraphNode for Units {
+    fn encode(&self, encoder: &mut ArcGraphEncoder) {
+        encoder.write_len(self.0.len())
+    }
+    fn decode(decoder: &mut ArcGraphDecoder) -> Result<Units, ArcGraphError> {
+        let len = decoder.read_len()?;
+        let mut units = Vec::with_capacity(decoder.capacity_for(len));
+        units.resize(len, ());
+        Ok(Units(units))
+    }
+}
+
+#[test]
+fn zero_sized_elements() {
+    let units = Arc::new(Units(vec![(); 1000]));
+    let mut writer = ArcGraphWriter::new();
+    writer.write_root(&units);
+    let bytes = writer.finish();
+    let loaded: Arc<Units> = ArcGraphReader::new(&bytes).unwrap().read_root(0).unwrap();
+    assert_eq!(loaded, units);
+}
@@ -0,0 +1,182 @@
+use elysees::*;
+use std::sync::atomic::Ordering::SeqCst;
+
+#[derive(Debug, Clone, Default)]
+struct Item {
+    value: i32,
+    first: ArcLink,
+    second: ArcLink,
+}
+
+impl Item {
+    fn new(value: i32) -> Arc<Item> {
+        Arc::new(Item {
+            value,
+            ..Item::default()
+        })
+    }
+}
+
+#[derive(Debug)]
+struct First;
+
+unsafe impl ArcAdapter<Item> for First {
+    fn link(item: &Item) -> &ArcLink {
+        &item.first
+    }
+}
+
+#[derive(Debug)]
+struct Second;
+
+unsafe impl ArcAdapter<Item> for Second {
+    fn link(item: &Item) -> &ArcLink {
+        &item.second
+    }
+}
+
+fn values<A: ArcAdapter<Item>>(list: &ArcIntrusiveList<Item, A>) -> Vec<i32> {
+    list.iter().map(|item| item.value).collect()
+}
+
+#[test]
+fn intrusive_list_operations() {
+    let mut list = ArcIntrusiveList::<Item, First>::new();
+    assert!(list.is_empty());
+    assert!(list.front().is_none());
+    assert!(list.pop_back().is_none());
+
+    let items: Vec<_> = (0..5).map(Item::new).collect();
+    for item in &items[1..] {
+        list.push(item.clone()).unwrap();
+    }
+    list.push_front(items[0].clone()).unwrap();
+    assert_eq!(list.len(), 5);
+    assert_eq!(values(&list), [0, 1, 2, 3, 4]);
+    assert_eq!(
+        list.iter().rev().map(|item| item.value).collect::<Vec<_>>(),
+        [4, 3, 2, 1, 0]
+    );
+    assert_eq!(list.iter().len(), 5);
+    assert!(list.contains(&items[3]));
+    assert!(items[3].first.is_linked());
+    assert!(!items[3].second.is_linked());
+
+    // A link can only be in one list at a time, even the same one
+    let rejected = list.push(items[2].clone()).unwrap_err();
+    assert!(Arc::ptr_eq(&rejected, &items[2]));
+    let mut other = ArcIntrusiveList::<Item, First>::new();
+    assert!(other.push(items[2].clone()).is_err());
+    assert!(!other.contains(&items[2]));
+    assert!(other.remove(items[2].borrow_arc()).is_none());
+
+    let removed = list.remove(items[2].borrow_arc()).unwrap();
+    assert!(Arc::ptr_eq(&removed, &items[2]));
+    assert!(!items[2].first.is_linked());
+    assert!(list.remove(items[2].borrow_arc()).is_none());
+    assert_eq!(values(&list), [0, 1, 3, 4]);
+    other.push(removed).unwrap();
+
+    assert_eq!(list.pop_front().unwrap().value, 0);
+    assert_eq!(list.pop_back().unwrap().value, 4);
+    assert_eq!(list.front().unwrap().value, 1);
+    assert_eq!(list.back().unwrap().value, 3);
+    assert_eq!(values(&list), [1, 3]);
+    assert_eq!(
+        format!("{:?}", other),
+        "[Item { value: 2, first: ArcLink { linked: true }, second: ArcLink { linked: false } }]"
+    );
+
+    list.clear();
+    assert!(list.is_empty());
+    assert!(!items[1].first.is_linked());
+    list.push(items[1].clone()).unwrap();
+    assert_eq!(values(&list), [1]);
+}
+
+#[test]
+fn intrusive_list_cursors() {
+    let mut list = ArcIntrusiveList::<Item, First>::new();
+    let items: Vec<_> = (0..4).map(Item::new).collect();
+    for item in &items {
+        list.push(item.clone()).unwrap();
+    }
+
+    let mut cursor = list.cursor_front();
+    assert_eq!(cursor.current().unwrap().value, 0);
+    assert!(cursor.peek_prev().is_none());
+    cursor.move_prev();
+    assert!(cursor.current().is_none());
+    assert_eq!(cursor.peek_next().unwrap().value, 0);
+    assert_eq!(cursor.peek_prev().unwrap().value, 3);
+    cursor.move_prev();
+    assert_eq!(cursor.current().unwrap().value, 3);
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.current().unwrap().value, 0);
+    assert_eq!(list.cursor_back().current().unwrap().value, 3);
+
+    // Find a member in constant time and edit around it
+    let mut cursor = list.cursor_mut_at(items[1].borrow_arc()).unwrap();
+    cursor.insert_before(Item::new(10)).unwrap();
+    cursor.insert_after(Item::new(11)).unwrap();
+    assert!(cursor.insert_after(items[3].clone()).is_err());
+    assert_eq!(cursor.peek_prev().unwrap().value, 10);
+    let removed = cursor.remove_current().unwrap();
+    assert!(Arc::ptr_eq(&removed, &items[1]));
+    assert_eq!(cursor.current().unwrap().value, 11);
+    assert_eq!(values(&list), [0, 10, 11, 2, 3]);
+    assert!(list.cursor_mut_at(items[1].borrow_arc()).is_none());
+
+    // At the ghost position, insertions go to the ends of the list
+    let mut cursor = list.cursor_back_mut();
+    cursor.move_next();
+    assert!(cursor.remove_current().is_none());
+    cursor.insert_after(Item::new(-1)).unwrap();
+    cursor.insert_before(Item::new(12)).unwrap();
+    assert_eq!(cursor.as_cursor().peek_next().unwrap().value, -1);
+    assert_eq!(values(&list), [-1, 0, 10, 11, 2, 3, 12]);
+
+    let mut cursor = list.cursor_front_mut();
+    while let Some(item) = cursor.current() {
+        if item.value >= 10 {
+            cursor.remove_current();
+        } else {
+            cursor.move_next();
+        }
+    }
+    assert_eq!(values(&list), [-1, 0, 2, 3]);
+}
+
+#[test]
+fn intrusive_list_membership() {
+    let item = Item::new(7);
+    let mut first = ArcIntrusiveList::<Item, First>::new();
+    let mut second = ArcIntrusiveList::<Item, Second>::new();
+    first.push(item.clone()).unwrap();
+    second.push(item.clone()).unwrap();
+    assert_eq!(Arc::count(&item, SeqCst), 3);
+    assert!(Arc::ptr_eq(
+        &first.front().unwrap().clone_arc(),
+        &second.front().unwrap().clone_arc()
+    ));
+
+    // Copies made while an item is in a list are not linked anywhere
+    let mut copy = item.clone();
+    Arc::make_mut(&mut copy).value = 8;
+    assert!(!copy.first.is_linked() && !copy.second.is_linked());
+    first.push(copy).unwrap();
+    assert_eq!(values(&first), [7, 8]);
+
+    // Lists can be moved across threads, and release their members when dropped
+    let handle = std::thread::spawn(move || {
+        first.push_front(Item::new(6)).unwrap();
+        values(&first)
+    });
+    assert_eq!(handle.join().unwrap(), [6, 7, 8]);
+    assert_eq!(Arc::count(&item, SeqCst), 2);
+    assert!(!item.first.is_linked());
+    drop(second);
+    assert!(item.is_unique());
+    assert!(!item.second.is_linked());
+}
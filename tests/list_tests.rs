@@ -0,0 +1,99 @@
+use elysees::*;
+use std::cell::Cell;
+
+#[test]
+fn list_operations() {
+    let empty = ArcList::new();
+    assert!(empty.is_empty());
+    assert_eq!(empty.head(), None);
+    assert_eq!(empty.tail(), None);
+
+    let one = empty.cons(1);
+    let two = one.cons(2);
+    let other = one.cons(3);
+    assert_eq!(two.len(), 2);
+    assert_eq!(two.head(), Some(&2));
+    assert!(ArcList::ptr_eq(&two.tail().unwrap(), &one));
+    assert!(ArcList::ptr_eq(&other.tail().unwrap(), &one));
+    assert_eq!(format!("{:?} {:?}", two, other), "[2, 1] [3, 1]");
+    assert!(two < other);
+
+    let mut list: ArcList<i32> = (0..5).collect();
+    assert_eq!(list.iter().len(), 5);
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    list.push_front(-1);
+    assert_eq!(list.pop_front(), Some(-1));
+    assert_eq!(list.pop_front(), Some(0));
+    assert_eq!(list.clone().into_iter().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    assert_eq!(list.len(), 4);
+}
+
+#[test]
+fn list_mutation_copies_only_shared_nodes() {
+    let base: ArcList<i32> = (0..4).collect();
+    let mut list = base.clone();
+    *list.head_mut().unwrap() = 10;
+    assert_eq!(base.head(), Some(&0));
+    // Only the first node was copied
+    assert!(ArcList::ptr_eq(
+        &list.tail().unwrap(),
+        &base.tail().unwrap()
+    ));
+
+    // Mutating in place when unique does not move the node
+    let mut unique: ArcList<i32> = (0..4).collect();
+    let before = unique.head().unwrap() as *const i32;
+    for x in &mut unique {
+        *x += 1
+    }
+    assert_eq!(unique.head().unwrap() as *const i32, before);
+    assert_eq!(unique.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+    // Mutation through a shared suffix copies the rest of the list
+    let mut prefixed = base.tail().unwrap().cons(7);
+    for x in prefixed.iter_mut().skip(1) {
+        *x *= 2;
+    }
+    assert_eq!(prefixed.iter().copied().collect::<Vec<_>>(), [7, 2, 4, 6]);
+    assert_eq!(base.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+}
+
+/// Counts comparisons, to check that equality short-circuits on shared nodes
+#[derive(Debug, Clone)]
+struct Counted<'a>(u32, &'a Cell<usize>);
+
+impl PartialEq for Counted<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.1.set(self.1.get() + 1);
+        self.0 == other.0
+    }
+}
+
+#[test]
+fn list_equality_short_circuits() {
+    let comparisons = Cell::new(0);
+    let shared: ArcList<_> = (0..100).map(|i| Counted(i, &comparisons)).collect();
+    let left = shared.cons(Counted(5, &comparisons));
+    let right = shared.cons(Counted(5, &comparisons));
+    assert_eq!(left, right);
+    assert_eq!(comparisons.get(), 1);
+    assert_eq!(shared, shared.clone());
+    assert_eq!(comparisons.get(), 1);
+
+    let copy: ArcList<_> = (0..100).map(|i| Counted(i, &comparisons)).collect();
+    assert_eq!(copy, shared);
+    assert_eq!(comparisons.get(), 101);
+    assert_ne!(copy, left);
+}
+
+#[test]
+fn long_list_drop_does_not_overflow() {
+    let list: ArcList<u64> = (0..1_000_000).collect();
+    let tail = list.tail().unwrap();
+    drop(list);
+    assert_eq!(tail.len(), 999_999);
+    let mut owned = tail.clone();
+    assert_eq!(owned.pop_front(), Some(1));
+    drop(tail);
+    drop(owned);
+}
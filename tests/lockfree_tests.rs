@@ -0,0 +1,118 @@
+use elysees::*;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+use std::thread;
+
+const THREADS: usize = 4;
+const PER_THREAD: usize = 10_000;
+
+#[test]
+fn stack_single_thread() {
+    let stack = ArcStack::new();
+    assert!(stack.is_empty());
+    for i in 0..10 {
+        stack.push(ArcNode::new(i));
+    }
+    let mut top = stack.pop().unwrap();
+    **top += 100;
+    let shared = top.shareable();
+    assert_eq!(**shared, 109);
+    let popped: Vec<i32> = std::iter::from_fn(|| stack.pop())
+        .map(ArcNode::into_inner)
+        .collect();
+    assert_eq!(popped, (0..9).rev().collect::<Vec<_>>());
+    assert!(stack.is_empty());
+
+    // Nodes left in a stack are freed when it is dropped
+    let counter = Arc::new(());
+    let stack = ArcStack::new();
+    stack.push(ArcNode::new(counter.clone()));
+    stack.push(ArcNode::new(counter.clone()));
+    drop(stack);
+    assert!(counter.is_unique());
+}
+
+#[test]
+fn stack_concurrent_push_pop() {
+    let stack = ArcStack::new();
+    let sum = AtomicUsize::new(0);
+    let popped = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let (stack, sum, popped) = (&stack, &sum, &popped);
+            scope.spawn(move || {
+                let mut node = ArcNode::new(0);
+                for i in 0..PER_THREAD {
+                    // Recycle popped nodes, to give the ABA problem a chance to occur
+                    **node = t * PER_THREAD + i;
+                    stack.push(node);
+                    node = loop {
+                        if let Some(node) = stack.pop() {
+                            break node;
+                        }
+                    };
+                    sum.fetch_add(**node, SeqCst);
+                    popped.fetch_add(1, SeqCst);
+                }
+            });
+        }
+    });
+    assert!(stack.is_empty());
+    assert_eq!(popped.load(SeqCst), THREADS * PER_THREAD);
+    let n = THREADS * PER_THREAD;
+    assert_eq!(sum.load(SeqCst), n * (n - 1) / 2);
+}
+
+#[test]
+fn queue_single_thread() {
+    let queue = ArcQueue::new();
+    let mut consumer = queue.consumer().unwrap();
+    assert!(consumer.pop().is_none());
+    for i in 0..10 {
+        queue.push(ArcNode::new(i));
+        if i % 3 == 0 {
+            assert_eq!(ArcNode::into_inner(consumer.pop().unwrap()), i / 3);
+        }
+    }
+    let rest: Vec<i32> = std::iter::from_fn(|| consumer.pop())
+        .map(ArcNode::into_inner)
+        .collect();
+    assert_eq!(rest, (4..10).collect::<Vec<_>>());
+    assert!(queue.consumer().is_none());
+    drop(consumer);
+    assert!(queue.consumer().is_some());
+
+    let counter = Arc::new(());
+    let queue = ArcQueue::new();
+    queue.push(ArcNode::new(counter.clone()));
+    queue.push(ArcNode::new(counter.clone()));
+    drop(queue);
+    assert!(counter.is_unique());
+}
+
+#[test]
+fn queue_concurrent_producers() {
+    let queue = ArcQueue::new();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let queue = &queue;
+            scope.spawn(move || {
+                for i in 0..PER_THREAD {
+                    queue.push(ArcNode::new((t, i)));
+                }
+            });
+        }
+        let mut consumer = queue.consumer().unwrap();
+        // Each producer's nodes arrive in the order it pushed them
+        let mut next = [0; THREADS];
+        let mut received = 0;
+        while received < THREADS * PER_THREAD {
+            if let Some(node) = consumer.pop() {
+                let (t, i) = ArcNode::into_inner(node);
+                assert_eq!(next[t], i);
+                next[t] += 1;
+                received += 1;
+            }
+        }
+        assert!(consumer.pop().is_none());
+    });
+}
@@ -0,0 +1,271 @@
+//! Model-checked tests of the reference counting implementation.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --test loom_tests`.
+#[cfg(loom)]
+use elysees::*;
+#[cfg(loom)]
+use loom::cell::UnsafeCell;
+#[cfg(loom)]
+use loom::sync::atomic::AtomicUsize;
+#[cfg(loom)]
+use loom::thread;
+#[cfg(loom)]
+use std::sync::atomic::Ordering::SeqCst;
+
+/// A payload which reads its contents on every access and writes them on drop, so that loom reports a data race
+/// if a drop or mutation is not ordered after every other `Arc`'s last access
+#[cfg(loom)]
+struct Payload {
+    value: UnsafeCell<usize>,
+    drops: loom::sync::Arc<AtomicUsize>,
+}
+
+#[cfg(loom)]
+unsafe impl Sync for Payload {}
+
+#[cfg(loom)]
+impl Payload {
+    fn new(value: usize, drops: &loom::sync::Arc<AtomicUsize>) -> Payload {
+        Payload {
+            value: UnsafeCell::new(value),
+            drops: drops.clone(),
+        }
+    }
+
+    fn get(&self) -> usize {
+        self.value.with(|value| unsafe { *value })
+    }
+
+    fn set(&mut self, value: usize) {
+        self.value.with_mut(|old| unsafe { *old = value })
+    }
+}
+
+#[cfg(loom)]
+impl Clone for Payload {
+    fn clone(&self) -> Payload {
+        Payload::new(self.get(), &self.drops)
+    }
+}
+
+#[cfg(loom)]
+impl Drop for Payload {
+    fn drop(&mut self) {
+        self.set(0);
+        self.drops.fetch_add(1, SeqCst);
+    }
+}
+
+#[cfg(loom)]
+#[test]
+fn clone_drop_race() {
+    loom::model(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let arc = Arc::new(Payload::new(5, &drops));
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let arc = arc.clone();
+                thread::spawn(move || {
+                    let clone = arc.clone();
+                    assert_eq!(clone.get(), 5);
+                })
+            })
+            .collect();
+        drop(arc);
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(drops.load(SeqCst), 1);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn bulk_clone_drop_race() {
+    loom::model(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let arc = Arc::new(Payload::new(5, &drops));
+        let mut clones = Arc::clone_n(&arc, 2);
+        let first = clones.next().unwrap();
+        let thread = thread::spawn(move || {
+            assert_eq!(first.get(), 5);
+        });
+        drop(clones);
+        drop(arc);
+        thread.join().unwrap();
+        assert_eq!(drops.load(SeqCst), 1);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn try_unique_race() {
+    loom::model(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let arc = Arc::new(Payload::new(5, &drops));
+        let clone = arc.clone();
+        let thread = thread::spawn(move || {
+            assert_eq!(clone.get(), 5);
+        });
+        match Arc::try_unique(arc) {
+            // The other thread's read must happen before our write
+            Ok(mut unique) => unique.set(7),
+            Err(shared) => assert_eq!(shared.get(), 5),
+        }
+        thread.join().unwrap();
+        assert_eq!(drops.load(SeqCst), 1);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn try_unique_concurrent_clone() {
+    loom::model(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let arc = Arc::new(Payload::new(5, &drops));
+        let clone = arc.clone();
+        let thread = thread::spawn(move || {
+            let another = clone.clone();
+            drop(clone);
+            assert_eq!(another.get(), 5);
+        });
+        match Arc::try_unique(arc) {
+            Ok(mut unique) => unique.set(7),
+            Err(shared) => assert_eq!(shared.get(), 5),
+        }
+        thread.join().unwrap();
+        assert_eq!(drops.load(SeqCst), 1);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn make_mut_race() {
+    loom::model(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let mut arc = Arc::new(Payload::new(5, &drops));
+        let clone = arc.clone();
+        let thread = thread::spawn(move || {
+            assert_eq!(clone.get(), 5);
+        });
+        let original = Arc::as_ptr(&arc);
+        Arc::make_mut(&mut arc).set(7);
+        let cloned = Arc::as_ptr(&arc) != original;
+        assert_eq!(arc.get(), 7);
+        thread.join().unwrap();
+        drop(arc);
+        assert_eq!(drops.load(SeqCst), if cloned { 2 } else { 1 });
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn arc32_make_mut_race() {
+    loom::model(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let mut arc = Arc32::new(Payload::new(5, &drops));
+        let clone = arc.clone();
+        let thread = thread::spawn(move || {
+            let another = clone.clone();
+            drop(clone);
+            assert_eq!(another.get(), 5);
+        });
+        let original = Arc32::as_ptr(&arc);
+        Arc32::make_mut(&mut arc).set(7);
+        let cloned = Arc32::as_ptr(&arc) != original;
+        thread.join().unwrap();
+        drop(arc);
+        assert_eq!(drops.load(SeqCst), if cloned { 2 } else { 1 });
+    });
+}
+
+/// Check a concurrent structure, whose retry and wait loops are too long to explore exhaustively, with a bounded number
+/// of preemptions unless `LOOM_MAX_PREEMPTIONS` says otherwise
+#[cfg(loom)]
+fn model_bounded<F: Fn() + Sync + Send + 'static>(f: F) {
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound.get_or_insert(3);
+    builder.check(f)
+}
+
+#[cfg(loom)]
+#[test]
+fn stack_push_pop_race() {
+    model_bounded(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let stack = Arc::new(ArcStack::new());
+        stack.push(ArcNode::new(Payload::new(1, &drops)));
+        let (other, other_drops) = (stack.clone(), drops.clone());
+        let thread = thread::spawn(move || {
+            other.push(ArcNode::new(Payload::new(2, &other_drops)));
+            // A popped node must be fully visible to, and exclusively owned by, the popping thread
+            let mut node = other.pop().expect("at least one node is left");
+            let value = node.get();
+            node.set(value + 10);
+            value
+        });
+        let mut node = stack.pop().expect("at least one node is left");
+        let mine = node.get();
+        node.set(mine + 10);
+        drop(node);
+        let theirs = thread.join().unwrap();
+        assert_eq!(mine + theirs, 3);
+        assert!(stack.pop().is_none());
+        assert_eq!(drops.load(SeqCst), 2);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn stack_concurrent_pops() {
+    model_bounded(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let stack = Arc::new(ArcStack::new());
+        stack.push(ArcNode::new(Payload::new(1, &drops)));
+        stack.push(ArcNode::new(Payload::new(2, &drops)));
+        let other = stack.clone();
+        let thread = thread::spawn(move || {
+            let node = other.pop().expect("the stack has two nodes");
+            ArcNode::into_inner(node).get()
+        });
+        let mine = stack.pop().expect("the stack has two nodes").get();
+        let theirs = thread.join().unwrap();
+        assert_eq!(mine + theirs, 3);
+        assert!(stack.pop().is_none());
+        assert_eq!(drops.load(SeqCst), 2);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn queue_producers_consumer() {
+    model_bounded(|| {
+        let drops = loom::sync::Arc::new(AtomicUsize::new(0));
+        let queue = Arc::new(ArcQueue::new());
+        let threads: Vec<_> = (1..3)
+            .map(|value| {
+                let (queue, drops) = (queue.clone(), drops.clone());
+                thread::spawn(move || queue.push(ArcNode::new(Payload::new(value, &drops))))
+            })
+            .collect();
+        let mut consumer = queue.consumer().unwrap();
+        let mut sum = 0;
+        let mut received = 0;
+        while received < 2 {
+            match consumer.pop() {
+                Some(mut node) => {
+                    sum += node.get();
+                    node.set(0);
+                    received += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        assert!(consumer.pop().is_none());
+        assert_eq!(sum, 3);
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(drops.load(SeqCst), 2);
+    });
+}
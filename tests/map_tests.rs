@@ -0,0 +1,198 @@
+#![cfg(all(feature = "std", feature = "slice-dst"))]
+
+use elysees::*;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+
+/// A deterministic pseudorandom number generator, to drive operations without extra dependencies
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+/// A hasher which keeps only a few bits of its input, to force deep branches and collisions
+#[derive(Default)]
+struct PoorHasher(u64);
+
+impl Hasher for PoorHasher {
+    fn finish(&self) -> u64 {
+        // Keep bits at the top and bottom, so that keys share long prefixes and some hashes are equal
+        (self.0 & 0b111) | ((self.0 & 0b111000) << 58)
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = self.0.wrapping_mul(31).wrapping_add(*byte as u64);
+        }
+    }
+    fn write_u64(&mut self, n: u64) {
+        self.0 = n;
+    }
+}
+
+fn check<S: std::hash::BuildHasher>(map: &ArcHashMap<u64, u64, S>, model: &HashMap<u64, u64>) {
+    assert_eq!(map.len(), model.len());
+    assert_eq!(map.iter().len(), model.len());
+    let entries: HashMap<u64, u64> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(&entries, model);
+    for (key, value) in model {
+        assert_eq!(map.get(key), Some(value));
+    }
+}
+
+fn random_operations<S: std::hash::BuildHasher + Default + Clone>(key_range: u64) {
+    let mut rng = Lcg(7);
+    let mut map: ArcHashMap<u64, u64, S> = ArcHashMap::default();
+    let mut model = HashMap::new();
+    let mut snapshots = Vec::new();
+    for step in 0..5000 {
+        let key = rng.next(key_range);
+        match rng.next(10) {
+            0..=5 => assert_eq!(map.insert(key, step), model.insert(key, step)),
+            6..=8 => assert_eq!(map.remove(&key), model.remove(&key)),
+            _ => snapshots.push((map.clone(), model.clone())),
+        }
+        assert_eq!(map.get(&key), model.get(&key));
+        if step % 500 == 0 {
+            check(&map, &model);
+        }
+    }
+    check(&map, &model);
+    for (map, model) in &snapshots {
+        check(map, model);
+    }
+    for key in 0..key_range {
+        assert_eq!(map.remove(&key), model.remove(&key));
+    }
+    assert!(map.is_empty());
+    assert_eq!(map.iter().next(), None);
+}
+
+#[test]
+fn map_random_operations() {
+    random_operations::<std::collections::hash_map::RandomState>(1000);
+    random_operations::<BuildHasherDefault<PoorHasher>>(1000);
+    random_operations::<BuildHasherDefault<PoorHasher>>(50);
+}
+
+#[test]
+fn map_sharing() {
+    let mut map: ArcHashMap<String, usize> = (0..1000).map(|i| (i.to_string(), i)).collect();
+    let snapshot = map.clone();
+    assert!(ArcHashMap::ptr_eq(&map, &snapshot));
+    assert_eq!(map.remove("missing"), None);
+    assert!(ArcHashMap::ptr_eq(&map, &snapshot));
+
+    map.insert("5".to_string(), 0);
+    assert_eq!(map["5"], 0);
+    assert_eq!(snapshot["5"], 5);
+    assert_ne!(map, snapshot);
+    assert_eq!(map.remove_entry("7"), Some(("7".to_string(), 7)));
+    assert!(!map.contains_key("7"));
+    assert!(snapshot.contains_key("7"));
+    map.insert("5".to_string(), 5);
+    map.insert("7".to_string(), 7);
+    assert_eq!(map, snapshot);
+
+    // Once unique, updating an existing entry happens in place
+    drop(snapshot);
+    let before = map.get("100").unwrap() as *const usize;
+    assert_eq!(map.insert("100".to_string(), 101), Some(100));
+    assert_eq!(map.get("100").unwrap() as *const usize, before);
+    assert_eq!(format!("{:?}", ArcHashMap::<u8, u8>::new()), "{}");
+}
+
+#[test]
+fn map_transient() {
+    let map: ArcHashMap<String, usize> = (0..1000).map(|i| (i.to_string(), i)).collect();
+    let snapshot = map.clone();
+    let mut batch = map.transient();
+    assert_eq!(batch.get_mut("missing"), None);
+    *batch.get_mut("5").unwrap() += 1;
+    assert_eq!(batch.get("5"), Some(&6));
+    assert_eq!(snapshot["5"], 5);
+
+    // The path to the entry was copied by the first update, and is modified in place from then on
+    let before = batch.get("5").unwrap() as *const usize;
+    *batch.get_mut("5").unwrap() += 1;
+    assert_eq!(batch.insert("5".to_string(), 8), Some(7));
+    assert_eq!(batch.get("5").unwrap() as *const usize, before);
+
+    let mut model: HashMap<String, usize> = snapshot.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    model.insert("5".to_string(), 8);
+    let mut rng = Lcg(11);
+    for step in 0..2000 {
+        let key = rng.next(1500).to_string();
+        match rng.next(3) {
+            0 => assert_eq!(batch.insert(key.clone(), step), model.insert(key, step)),
+            1 => assert_eq!(batch.remove(&key), model.remove(&key)),
+            _ => {
+                if let Some(value) = batch.get_mut(&key) {
+                    *value += 1;
+                }
+                if let Some(value) = model.get_mut(&key) {
+                    *value += 1;
+                }
+            }
+        }
+    }
+    assert_eq!(batch.len(), model.len());
+    let map = batch.persistent();
+    let entries: HashMap<String, usize> = map.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    assert_eq!(entries, model);
+    assert_eq!(snapshot.len(), 1000);
+    assert!((0..1000).all(|i| snapshot[&i.to_string()] == i));
+}
+
+/// Whether cloning a `Fragile` key panics
+static FRAGILE: AtomicBool = AtomicBool::new(false);
+
+/// A key which panics when cloned while `FRAGILE` is set, with only four distinct hashes, so that keys collide
+#[derive(Debug, PartialEq, Eq)]
+struct Fragile(u64);
+
+impl Clone for Fragile {
+    fn clone(&self) -> Self {
+        if FRAGILE.load(Relaxed) {
+            panic!("cloned {:?}", self)
+        }
+        Fragile(self.0)
+    }
+}
+
+impl Hash for Fragile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0 % 4)
+    }
+}
+
+#[test]
+fn map_panicking_clone() {
+    let mut map: ArcHashMap<Fragile, u64> = (0..16).map(|i| (Fragile(i), i)).collect();
+    let snapshot = map.clone();
+
+    // Adding to or removing from a collision shared with `snapshot` clones its entries, which panics
+    FRAGILE.store(true, Relaxed);
+    let insert = panic::catch_unwind(AssertUnwindSafe(|| map.insert(Fragile(16), 16)));
+    let remove = panic::catch_unwind(AssertUnwindSafe(|| map.remove(&Fragile(5))));
+    FRAGILE.store(false, Relaxed);
+    assert!(insert.is_err());
+    assert!(remove.is_err());
+
+    // Leaving both maps as they were
+    assert_eq!(map.iter().count(), 16);
+    assert_eq!(map, snapshot);
+    assert_eq!(map.insert(Fragile(16), 16), None);
+    assert_eq!(map.remove(&Fragile(5)), Some(5));
+    assert_eq!(map.len(), 16);
+    assert!((0..17).all(|i| map.get(&Fragile(i)) == (i != 5).then_some(&i)));
+    assert!((0..16).all(|i| snapshot[&Fragile(i)] == i));
+}
@@ -0,0 +1,68 @@
+use elysees::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::Ordering::Relaxed;
+
+fn panic_on_overflow(_count: usize) -> OverflowAction {
+    OverflowAction::Panic
+}
+
+fn saturate_on_overflow(_count: usize) -> OverflowAction {
+    OverflowAction::Saturate
+}
+
+/// The overflow hook is global, so every scenario is run sequentially in a single test
+#[test]
+fn overflow_hooks() {
+    let default = set_overflow_hook(Some(panic_on_overflow));
+    assert!(default.is_none());
+
+    // Panicking undoes the increment, whether it overflows the count or is too large by itself
+    let arc = Arc::new(5);
+    let half = usize::MAX / 3;
+    Arc::increment_by(&arc, half);
+    assert!(catch_unwind(AssertUnwindSafe(|| Arc::increment_by(&arc, half))).is_err());
+    assert_eq!(Arc::count(&arc, Relaxed), half + 1);
+    assert!(catch_unwind(AssertUnwindSafe(|| Arc::increment_by(&arc, usize::MAX))).is_err());
+    assert_eq!(Arc::count(&arc, Relaxed), half + 1);
+    unsafe { Arc::decrement_by(&arc, half) };
+    assert!(arc.is_unique());
+
+    // The hook applies to single clones and borrowed clones too
+    let previous = set_overflow_hook(Some(saturate_on_overflow));
+    assert_eq!(
+        previous.map(|hook| hook as usize),
+        Some(panic_on_overflow as OverflowHook as usize)
+    );
+    let leaked = Arc::new(String::from("leaked forever"));
+    Arc::increment_by(&leaked, usize::MAX / 2);
+    let saturated = Arc::count(&leaked, Relaxed);
+    assert!(saturated > usize::MAX / 2);
+    // Increments past the limit leave the count pinned
+    let clone = leaked.clone();
+    let borrowed = clone.borrow_arc().clone_arc();
+    let clones: Vec<_> = Arc::clone_n(&leaked, 16).collect();
+    assert_eq!(Arc::count(&leaked, Relaxed), saturated);
+    // Releases leave the count pinned too
+    drop(clones);
+    drop(borrowed);
+    drop(clone);
+    unsafe { Arc::decrement_by(&leaked, 16) };
+    assert_eq!(Arc::count(&leaked, Relaxed), saturated);
+    assert_eq!(*leaked, "leaked forever");
+
+    set_overflow_hook(default);
+
+    // Saturating by a single huge increment cannot be undone by releasing as many references
+    #[cfg(feature = "overflow-saturate")]
+    {
+        let arc = Arc::new(String::from("still alive"));
+        let clones = Arc::clone_n(&arc, usize::MAX - usize::MAX / 4);
+        drop(clones);
+        let clones = Arc::clone_n(&arc, usize::MAX);
+        assert_eq!(clones.len(), usize::MAX);
+        drop(clones);
+        assert!(Arc::count(&arc, Relaxed) > usize::MAX / 2);
+        drop(arc.clone());
+        assert_eq!(*arc, "still alive");
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(feature = "proptest")]
+use elysees::strategy::*;
+#[cfg(feature = "proptest")]
+use elysees::*;
+#[cfg(all(feature = "proptest", feature = "ptr-union"))]
+use erasable::ErasablePtr;
+#[cfg(feature = "proptest")]
+use proptest::prelude::*;
+
+#[cfg(feature = "proptest")]
+proptest! {
+    #[test]
+    fn arbitrary_arcs_are_unique(arc in any::<Arc<u64>>(), slice in any::<Arc<[u8]>>(), s in any::<Arc<str>>()) {
+        prop_assert!(arc.is_unique());
+        prop_assert!(slice.is_unique());
+        prop_assert!(s.is_unique());
+    }
+
+    #[test]
+    fn make_mut_detaches_shared(mut arcs in arc_with_sharing(any::<u32>(), 8)) {
+        let original = *arcs[0];
+        let shared = arcs.len() > 1;
+        let before = Arc::as_ptr(&arcs[0]);
+        *Arc::make_mut(&mut arcs[0]) = original.wrapping_add(1);
+        prop_assert_eq!(Arc::as_ptr(&arcs[0]) != before, shared);
+        prop_assert_eq!(*arcs[0], original.wrapping_add(1));
+        for arc in &arcs[1..] {
+            prop_assert_eq!(**arc, original);
+            prop_assert_eq!(Arc::count(arc, std::sync::atomic::Ordering::Acquire), arcs.len() - 1);
+        }
+    }
+
+    #[test]
+    fn try_unique_matches_count(arcs in shared_arcs(any::<i16>(), 1..4, 0..8)) {
+        for arc in &arcs {
+            let clones = arcs.iter().filter(|other| Arc::ptr_eq(arc, other)).count();
+            prop_assert_eq!(Arc::count(arc, std::sync::atomic::Ordering::Acquire), clones);
+        }
+        let mut arcs = arcs;
+        while let Some(arc) = arcs.pop() {
+            let shared = arcs.iter().any(|other| Arc::ptr_eq(&arc, other));
+            match Arc::try_unique(arc) {
+                Ok(unique) => prop_assert!(!shared, "unique {:?} still has clones", unique),
+                Err(arc) => prop_assert!(shared, "shared {:?} has no clones", arc),
+            }
+        }
+    }
+
+    #[test]
+    fn arc_box_round_trip(mut boxed in any::<ArcBox<Vec<u8>>>(), extra in any::<u8>()) {
+        boxed.push(extra);
+        let arc = boxed.shareable();
+        let boxed = Arc::try_unique(arc).ok().unwrap();
+        prop_assert_eq!(boxed.last(), Some(&extra));
+    }
+}
+
+#[cfg(all(feature = "proptest", feature = "ptr-union"))]
+proptest! {
+    #[test]
+    fn union2_round_trip(union in union2(arc(any::<u32>()), arc_box(any::<u64>()))) {
+        let address = union.as_untagged_ptr();
+        let value = (union.a().copied(), union.b().copied());
+        let erased = ErasablePtr::erase(union);
+        let restored: ptr_union::Union2<Arc<u32>, ArcBox<u64>> =
+            unsafe { ErasablePtr::unerase(erased) };
+        prop_assert_eq!(restored.as_untagged_ptr(), address);
+        prop_assert_eq!((restored.a().copied(), restored.b().copied()), value);
+    }
+
+    #[test]
+    fn union4_round_trip(
+        union in union4(arc(any::<u8>()), arc(any::<u16>()), arc(any::<u32>()), arc(any::<u64>()))
+    ) {
+        let erased = ErasablePtr::erase(union.clone());
+        let restored: ptr_union::Union4<Arc<u8>, Arc<u16>, Arc<u32>, Arc<u64>> =
+            unsafe { ErasablePtr::unerase(erased) };
+        prop_assert!(restored.ptr_eq(&union));
+        prop_assert_eq!(restored.a(), union.a());
+        prop_assert_eq!(restored.b(), union.b());
+        prop_assert_eq!(restored.c(), union.c());
+        prop_assert_eq!(restored.d(), union.d());
+    }
+}
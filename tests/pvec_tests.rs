@@ -0,0 +1,127 @@
+use elysees::*;
+
+/// A simple deterministic pseudorandom number generator, to drive operations without extra dependencies
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound
+    }
+}
+
+fn check(v: &ArcPVec<usize>, model: &[usize]) {
+    assert_eq!(v.len(), model.len());
+    assert_eq!(v.iter().len(), model.len());
+    assert!(v.iter().eq(model.iter()));
+    for (i, x) in model.iter().enumerate().step_by(7) {
+        assert_eq!(v.get(i), Some(x));
+    }
+    assert_eq!(v.get(model.len()), None);
+}
+
+#[test]
+fn pvec_push_and_set() {
+    let mut v = ArcPVec::new();
+    let mut model = Vec::new();
+    for i in 0..40_000 {
+        v.push_back(i);
+        model.push(i);
+    }
+    check(&v, &model);
+
+    let snapshot = v.clone();
+    assert!(ArcPVec::ptr_eq(&v, &snapshot));
+    assert_eq!(v.set(12_345, 0), 12_345);
+    model[12_345] = 0;
+    check(&v, &model);
+    assert_eq!(snapshot[12_345], 12_345);
+    assert!(!ArcPVec::ptr_eq(&v, &snapshot));
+    // Elements away from the modified path are still shared
+    assert!(std::ptr::eq(&v[0], &snapshot[0]));
+    assert!(!std::ptr::eq(&v[12_345], &snapshot[12_345]));
+
+    // Once unique, the path is modified in place
+    drop(snapshot);
+    let before = &v[12_345] as *const usize;
+    *v.get_mut(12_345).unwrap() = 5;
+    assert_eq!(&v[12_345] as *const usize, before);
+    assert_eq!(v.get_mut(40_000), None);
+}
+
+#[test]
+fn pvec_split_and_append() {
+    let full: ArcPVec<usize> = (0..5000).collect();
+    let model: Vec<usize> = (0..5000).collect();
+    for &at in &[0, 1, 31, 32, 33, 1023, 1024, 1025, 2500, 4999, 5000] {
+        let mut left = full.clone();
+        let mut right = left.split_off(at);
+        check(&left, &model[..at]);
+        check(&right, &model[at..]);
+        left.append(&mut right);
+        assert!(right.is_empty());
+        check(&left, &model);
+        assert_eq!(left, full);
+    }
+    check(&full, &model);
+
+    // Appending many small vectors merges them into full nodes
+    let mut v = ArcPVec::new();
+    for i in 0..2000 {
+        let mut small: ArcPVec<usize> = (i * 3..i * 3 + 3).collect();
+        v.append(&mut small);
+    }
+    check(&v, &(0..6000).collect::<Vec<_>>());
+}
+
+#[test]
+fn pvec_random_operations() {
+    let mut rng = Lcg(5);
+    let mut v: ArcPVec<usize> = ArcPVec::new();
+    let mut model: Vec<usize> = Vec::new();
+    let mut snapshots = Vec::new();
+    for step in 0..3000 {
+        match rng.next(6) {
+            0 => {
+                for _ in 0..rng.next(100) {
+                    v.push_back(step);
+                    model.push(step);
+                }
+            }
+            1 if !model.is_empty() => {
+                let i = rng.next(model.len());
+                assert_eq!(v.set(i, step), model[i]);
+                model[i] = step;
+            }
+            2 => {
+                let at = rng.next(model.len() + 1);
+                let right = v.split_off(at);
+                let right_model = model.split_off(at);
+                check(&right, &right_model);
+                snapshots.push((right, right_model));
+            }
+            3 if !snapshots.is_empty() => {
+                let (mut other, other_model) = snapshots.swap_remove(rng.next(snapshots.len()));
+                v.append(&mut other);
+                model.extend(other_model);
+            }
+            4 => snapshots.push((v.clone(), model.clone())),
+            _ => {
+                let mut other = v.clone();
+                v.append(&mut other);
+                model.extend_from_slice(&model.clone());
+                if model.len() > 20_000 {
+                    v = v.split_off(model.len() - 1000);
+                    model = model.split_off(model.len() - 1000);
+                }
+            }
+        }
+        check(&v, &model);
+    }
+    for (v, model) in &snapshots {
+        check(v, model);
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(feature = "rkyv")]
+use elysees::*;
+#[cfg(feature = "rkyv")]
+use rkyv::rancor::Error;
+
+#[cfg(feature = "rkyv")]
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+struct Shared {
+    first: Arc<String>,
+    second: Arc<String>,
+    slice: Arc<[u32]>,
+    slices: Vec<Arc<[u32]>>,
+    name: Arc<str>,
+    unique: ArcBox<[u8]>,
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv_round_trip() {
+    let string = Arc::new(String::from("shared string"));
+    let slice: Arc<[u32]> = Arc::from(vec![1, 2, 3, 4]);
+    let value = Shared {
+        first: string.clone(),
+        second: string,
+        slice: slice.clone(),
+        slices: vec![slice.clone(), slice, Arc::from(vec![5])],
+        name: Arc::from("name"),
+        unique: Arc::unique(Arc::from(vec![6u8, 7])),
+    };
+    let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+    // The shared string and slice are only archived once
+    let duplicate = Shared {
+        second: Arc::new(String::from("shared string")),
+        slices: vec![
+            Arc::from(vec![1, 2, 3, 4]),
+            Arc::from(vec![1, 2, 3, 4]),
+            Arc::from(vec![5]),
+        ],
+        ..value
+    };
+    assert!(bytes.len() < rkyv::to_bytes::<Error>(&duplicate).unwrap().len());
+
+    let archived = rkyv::access::<ArchivedShared, Error>(&bytes).unwrap();
+    assert_eq!(*archived.first, "shared string");
+    assert_eq!(archived.slice.get(), [1, 2, 3, 4]);
+    assert_eq!(&*archived.name, "name");
+    assert_eq!(archived.unique.get(), [6, 7]);
+
+    let loaded: Shared = rkyv::deserialize::<_, Error>(archived).unwrap();
+    assert!(Arc::ptr_eq(&loaded.first, &loaded.second));
+    assert_eq!(
+        Arc::count(&loaded.first, std::sync::atomic::Ordering::Relaxed),
+        2
+    );
+    assert!(Arc::ptr_eq(&loaded.slice, &loaded.slices[0]));
+    assert!(Arc::ptr_eq(&loaded.slice, &loaded.slices[1]));
+    assert!(!Arc::ptr_eq(&loaded.slice, &loaded.slices[2]));
+    assert_eq!(*loaded.first, "shared string");
+    assert_eq!(&*loaded.slices[2], &[5]);
+    assert_eq!(&*loaded.name, "name");
+    assert_eq!(&*loaded.unique, &[6, 7]);
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv_std_compatible() {
+    let arc = Arc::new(vec![1u16, 2]);
+    let bytes = rkyv::to_bytes::<Error>(&arc).unwrap();
+    let loaded: std::sync::Arc<Vec<u16>> =
+        rkyv::from_bytes::<std::sync::Arc<Vec<u16>>, Error>(&bytes).unwrap();
+    assert_eq!(*loaded, [1, 2]);
+    let bytes = rkyv::to_bytes::<Error>(&loaded).unwrap();
+    let loaded = rkyv::from_bytes::<Arc<Vec<u16>>, Error>(&bytes).unwrap();
+    assert_eq!(*loaded, [1, 2]);
+    assert!(loaded.is_unique());
+}
+
+#[cfg(all(feature = "rkyv", feature = "slice-dst"))]
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+struct Dsts {
+    #[rkyv(with = ArchiveSliceDst)]
+    first: Arc<slice_dst::SliceWithHeader<u64, String>>,
+    #[rkyv(with = ArchiveSliceDst)]
+    second: Arc<slice_dst::SliceWithHeader<u64, String>>,
+    #[rkyv(with = ArchiveSliceDst)]
+    text: Arc<slice_dst::StrWithHeader<u8>>,
+    #[rkyv(with = ArchiveSliceDst)]
+    empty: Arc<slice_dst::SliceWithHeader<(), u8>>,
+}
+
+#[cfg(all(feature = "rkyv", feature = "slice-dst"))]
+#[test]
+fn rkyv_slice_dst_round_trip() {
+    use slice_dst::{SliceWithHeader, StrWithHeader};
+    let slice: Arc<SliceWithHeader<u64, String>> =
+        SliceWithHeader::new(42, vec!["a".to_string(), "bc".to_string()]);
+    let value = Dsts {
+        first: slice.clone(),
+        second: slice,
+        text: StrWithHeader::new(3, "text"),
+        empty: SliceWithHeader::new((), vec![]),
+    };
+    let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+    let archived = rkyv::access::<ArchivedDsts, Error>(&bytes).unwrap();
+    assert_eq!(archived.first.header, 42);
+    assert_eq!(archived.first.slice.len(), 2);
+    assert_eq!(archived.first.slice[1], "bc");
+    assert_eq!(archived.text.header, 3);
+    assert_eq!(&archived.text.str, "text");
+    assert!(archived.empty.slice.is_empty());
+
+    let loaded: Dsts = rkyv::deserialize::<_, Error>(archived).unwrap();
+    assert!(Arc::ptr_eq(&loaded.first, &loaded.second));
+    assert_eq!(loaded.first.header, 42);
+    assert_eq!(loaded.first.slice, ["a", "bc"]);
+    assert_eq!(&loaded.text.str, "text");
+    assert!(loaded.empty.slice.is_empty());
+}
@@ -0,0 +1,149 @@
+use elysees::*;
+
+/// A deterministic pseudorandom number generator, to drive edits without extra dependencies
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound
+    }
+
+    /// Pick a random `char` boundary in `text`
+    fn boundary(&mut self, text: &str) -> usize {
+        let mut at = self.next(text.len() + 1);
+        while !text.is_char_boundary(at) {
+            at -= 1;
+        }
+        at
+    }
+}
+
+const PIECES: &[&str] = &[
+    "a",
+    "bc",
+    "é",
+    "\n",
+    "λx. x\n",
+    "🦀🦀",
+    "line\nline\n",
+    "    ",
+];
+
+fn check(rope: &ArcRope, model: &str) {
+    assert_eq!(rope.len_bytes(), model.len());
+    assert_eq!(rope.len_chars(), model.chars().count());
+    assert_eq!(rope.len_lines(), model.split('\n').count());
+    assert_eq!(rope.is_empty(), model.is_empty());
+    assert_eq!(rope, model);
+    assert_eq!(String::from(rope), model);
+    assert!(rope.chars().eq(model.chars()));
+}
+
+fn check_indices(rope: &ArcRope, model: &str) {
+    for (index, (byte, c)) in model.char_indices().enumerate() {
+        assert_eq!(rope.char(index), c);
+        assert_eq!(rope.byte(byte), model.as_bytes()[byte]);
+        assert_eq!(rope.char_to_byte(index), byte);
+        assert_eq!(rope.byte_to_char(byte), index);
+        let line = model[..byte].matches('\n').count();
+        assert_eq!(rope.byte_to_line(byte), line);
+        assert_eq!(rope.char_to_line(index), line);
+    }
+    assert_eq!(rope.char_to_byte(rope.len_chars()), model.len());
+    assert_eq!(rope.byte_to_char(model.len()), rope.len_chars());
+    let mut start = 0;
+    for (index, line) in model.split_inclusive('\n').enumerate() {
+        assert_eq!(rope.line_to_byte(index), start);
+        assert_eq!(rope.line_to_char(index), model[..start].chars().count());
+        assert_eq!(rope.line(index), line);
+        start += line.len();
+    }
+}
+
+#[test]
+fn rope_edits() {
+    let mut rng = Lcg(3);
+    let mut rope = ArcRope::new();
+    let mut model = String::new();
+    let mut snapshots = Vec::new();
+    for step in 0..4000 {
+        match rng.next(8) {
+            0..=3 => {
+                let at = rng.boundary(&model);
+                let text: String = (0..rng.next(40))
+                    .map(|_| PIECES[rng.next(PIECES.len())])
+                    .collect();
+                rope.insert(at, &text);
+                model.insert_str(at, &text);
+            }
+            4 | 5 => {
+                let start = rng.boundary(&model);
+                let end = start + rng.boundary(&model[start..]);
+                rope.remove(start..end);
+                model.replace_range(start..end, "");
+            }
+            6 => {
+                let start = rng.boundary(&model);
+                let end = start + rng.boundary(&model[start..]);
+                check(&rope.slice(start..end), &model[start..end]);
+                check(&rope.slice(..end), &model[..end]);
+                check(&rope.slice(start..), &model[start..]);
+            }
+            _ => snapshots.push((rope.clone(), model.clone())),
+        }
+        if step % 200 == 0 {
+            check(&rope, &model);
+        }
+    }
+    check(&rope, &model);
+    check_indices(&rope, &model);
+    for (rope, model) in &snapshots {
+        check(rope, model);
+    }
+}
+
+#[test]
+fn rope_large_text() {
+    let text: String = (0..20_000)
+        .map(|i| format!("line {} with ünïcödé\n", i))
+        .collect();
+    let rope = ArcRope::from(text.as_str());
+    check(&rope, &text);
+    assert_eq!(rope.len_lines(), 20_001);
+    assert_eq!(rope.line(12_345), "line 12345 with ünïcödé\n");
+    assert_eq!(rope.line(20_000), "");
+
+    // Editing a snapshot leaves the original untouched
+    let mut edited = rope.clone();
+    let at = edited.line_to_byte(10_000);
+    edited.insert(at, "inserted\n");
+    assert_eq!(edited.line(10_000), "inserted\n");
+    assert_eq!(edited.line(10_001), "line 10000 with ünïcödé\n");
+    assert_eq!(rope.line(10_000), "line 10000 with ünïcödé\n");
+    assert!(rope > edited);
+
+    // Snapshots can be read from other threads
+    let snapshot = edited.clone();
+    let lines = std::thread::spawn(move || snapshot.len_lines())
+        .join()
+        .unwrap();
+    assert_eq!(lines, 20_002);
+}
+
+#[test]
+#[should_panic]
+fn rope_insert_off_boundary() {
+    let mut rope = ArcRope::from("é");
+    rope.insert(1, "x");
+}
+
+#[test]
+#[should_panic]
+fn rope_slice_inclusive_end_overflow() {
+    let rope = ArcRope::from("text");
+    rope.slice(..=usize::MAX);
+}
@@ -0,0 +1,78 @@
+#[cfg(feature = "serde")]
+use elysees::*;
+
+#[cfg(feature = "serde")]
+#[test]
+fn unsized_round_trip() {
+    let s: Arc<str> = serde_json::from_str("\"hello\"").unwrap();
+    assert_eq!(&*s, "hello");
+    let v: Arc<[u32]> = serde_json::from_str("[1,2,3]").unwrap();
+    assert_eq!(&*v, &[1, 2, 3]);
+    assert_eq!(serde_json::to_string(&v).unwrap(), "[1,2,3]");
+    let b: ArcBox<[u32]> = serde_json::from_str("[]").unwrap();
+    assert!(b.is_empty());
+    let vec: ArcVec<u8> = serde_json::from_str("[4,5]").unwrap();
+    assert_eq!(vec.freeze(), Arc::from(&[4u8, 5][..]));
+}
+
+#[cfg(all(feature = "serde", feature = "slice-dst"))]
+#[test]
+fn slice_dst_round_trip() {
+    use slice_dst::{SliceWithHeader, StrWithHeader};
+    let loaded: Arc<SliceWithHeader<u8, u16>> = serde_json::from_str("[7,[1,2]]").unwrap();
+    assert_eq!(loaded.header, 7);
+    assert_eq!(loaded.slice, [1, 2]);
+    let s: Arc<StrWithHeader<bool>> = serde_json::from_str("[true,\"text\"]").unwrap();
+    assert!(s.header);
+    assert_eq!(&s.str, "text");
+}
+
+#[cfg(all(feature = "serde", feature = "erasable", feature = "slice-dst"))]
+#[test]
+fn thin_round_trip() {
+    let s: ArcStr = serde_json::from_str("\"thin\"").unwrap();
+    assert_eq!(s, "thin");
+    let v = ArcSlice::from(vec![1u8, 2]);
+    let json = serde_json::to_string(&v).unwrap();
+    assert_eq!(serde_json::from_str::<ArcSlice<u8>>(&json).unwrap(), v);
+}
+
+#[cfg(feature = "serde_shared")]
+#[test]
+fn shared_round_trip() {
+    use elysees::serde_shared::{self, Shared};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Graph {
+        #[serde(with = "serde_shared")]
+        left: Arc<[u32]>,
+        #[serde(with = "serde_shared")]
+        right: Arc<[u32]>,
+        names: Vec<Shared<str>>,
+    }
+
+    let data: Arc<[u32]> = Arc::from(vec![1, 2, 3]);
+    let name: Arc<str> = Arc::from("name");
+    let graph = Graph {
+        left: data.clone(),
+        right: data.clone(),
+        names: vec![
+            Shared(name.clone()),
+            Shared(name.clone()),
+            Shared(Arc::from("other")),
+        ],
+    };
+    let json = serde_shared::scope(|| serde_json::to_string(&graph)).unwrap();
+    assert_eq!(json.matches("name").count(), 2);
+    let loaded: Graph = serde_shared::scope(|| serde_json::from_str(&json)).unwrap();
+    assert!(Arc::ptr_eq(&loaded.left, &loaded.right));
+    assert_eq!(&*loaded.left, &[1, 2, 3]);
+    assert!(Arc::ptr_eq(&loaded.names[0], &loaded.names[1]));
+    assert!(!Arc::ptr_eq(&loaded.names[0], &loaded.names[2]));
+    assert_eq!(&*loaded.names[2].0, "other");
+
+    assert!(serde_json::to_string(&Shared(name)).is_err());
+    let dangling: Result<Shared<str>, _> = serde_shared::scope(|| serde_json::from_str("[3,null]"));
+    assert!(dangling.is_err());
+}
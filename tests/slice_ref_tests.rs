@@ -0,0 +1,76 @@
+use elysees::*;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+fn basic_slice_ref_test() {
+    let parent: Arc<[u8]> = (0..100).collect();
+    let mut whole = ArcSliceRef::from(parent.clone());
+    assert_eq!(whole.len(), 100);
+    assert_eq!(Arc::count(&parent, Relaxed), 2);
+
+    let sub = whole.slice(10..20);
+    assert_eq!(*sub, *(10..20).collect::<Vec<_>>());
+    assert_eq!(ArcSliceRef::range(&sub), 10..20);
+    let subsub = sub.slice(5..=6);
+    assert_eq!(*subsub, [15, 16]);
+    assert_eq!(ArcSliceRef::range(&subsub), 15..17);
+    assert_eq!(sub.slice(..).len(), 10);
+    assert_eq!(sub.slice(8..).len(), 2);
+    assert_eq!(Arc::count(&parent, Relaxed), 4);
+
+    let head = whole.split_to(50);
+    assert_eq!(head.len(), 50);
+    assert_eq!(whole.len(), 50);
+    assert_eq!(whole[0], 50);
+    let tail = whole.split_off(25);
+    assert_eq!(whole.len(), 25);
+    assert_eq!(tail[0], 75);
+    let (left, right) = tail.split_at(5);
+    assert_eq!(*left, [75, 76, 77, 78, 79]);
+    assert_eq!(right.len(), 20);
+    let (empty, full) = right.clone().split_at(0);
+    assert!(empty.is_empty());
+    assert_eq!(full, right);
+    assert!(Arc::ptr_eq(ArcSliceRef::parent(&full).as_arc(), &parent));
+    assert!(Arc::ptr_eq(
+        &ArcSliceRef::into_parent(full.clone()),
+        &parent
+    ));
+
+    std::mem::drop((head, whole, left, right, empty, full, sub, subsub));
+    assert!(parent.is_unique());
+}
+
+#[test]
+#[should_panic]
+fn slice_ref_out_of_bounds() {
+    let parent: Arc<[u8]> = vec![1, 2, 3].into();
+    ArcSliceRef::with_range(parent, 1..4);
+}
+
+#[test]
+fn slice_ref_recovers_parent() {
+    let parent: Arc<[String]> = (0..10).map(|i| i.to_string()).collect();
+    let view = ArcSliceRef::with_range(parent.clone(), 3..8);
+    let moved = std::thread::spawn(move || {
+        let (left, right) = view.split_at(2);
+        assert_eq!(*left, ["3", "4"]);
+        (left.slice(1..), right)
+    })
+    .join()
+    .unwrap();
+    assert_eq!(ArcSliceRef::range(&moved.0), 4..5);
+    assert_eq!(ArcSliceRef::range(&moved.1), 5..8);
+    assert_eq!(Arc::count(&parent, Relaxed), 3);
+    assert_eq!(ArcSliceRef::parent(&moved.1).len(), 10);
+    drop(moved);
+    assert!(parent.is_unique());
+
+    let units: Arc<[()]> = vec![(); 5].into();
+    let mut view = ArcSliceRef::from(units.clone());
+    let head = view.split_to(2);
+    assert_eq!((head.len(), view.len()), (2, 3));
+    assert_eq!(ArcSliceRef::range(&view), 2..5);
+    drop((head, view));
+    assert!(units.is_unique());
+}
@@ -0,0 +1,69 @@
+#[cfg(all(feature = "erasable", feature = "slice-dst"))]
+use elysees::*;
+#[cfg(all(feature = "erasable", feature = "slice-dst"))]
+use std::collections::{HashMap, HashSet};
+#[cfg(all(feature = "erasable", feature = "slice-dst"))]
+use std::sync::atomic::Ordering::Relaxed;
+
+#[cfg(all(feature = "erasable", feature = "slice-dst"))]
+#[test]
+fn basic_arc_slice_test() {
+    let slice: ArcSlice<_> = (0..10).collect();
+    assert_eq!(std::mem::size_of_val(&slice), std::mem::size_of::<usize>());
+    assert_eq!(slice.len(), 10);
+    assert_eq!(slice, *(0..10).collect::<Vec<_>>());
+    assert!(slice.is_unique());
+    let slice2 = slice.clone();
+    assert!(ArcSlice::ptr_eq(&slice, &slice2));
+    assert_eq!(ArcSlice::count(&slice, Relaxed), 2);
+    assert!(!slice.is_unique());
+    let slice3 = ArcSlice::from(&slice[..]);
+    assert!(!ArcSlice::ptr_eq(&slice, &slice3));
+    assert_eq!(slice, slice3);
+    std::mem::drop(slice2);
+    let arc = ArcSlice::into_arc(slice);
+    assert!(arc.is_unique());
+    assert_eq!(arc.slice, slice3[..]);
+    let empty = ArcSlice::<u8>::from(vec![]);
+    assert!(empty.is_empty());
+}
+
+#[cfg(all(feature = "erasable", feature = "slice-dst"))]
+#[test]
+fn basic_arc_str_test() {
+    let s = ArcStr::from("Hello, world!");
+    assert_eq!(std::mem::size_of_val(&s), std::mem::size_of::<usize>());
+    assert_eq!(s, "Hello, world!");
+    assert_eq!(format!("{}", s), "Hello, world!");
+    assert_eq!(format!("{:?}", s), "\"Hello, world!\"");
+    let t = s.clone();
+    assert!(ArcStr::ptr_eq(&s, &t));
+    assert_eq!(ArcStr::count(&t, Relaxed), 2);
+
+    let mut set = HashSet::new();
+    assert!(set.insert(s));
+    assert!(!set.insert(ArcStr::from(String::from("Hello, world!"))));
+    assert!(set.contains("Hello, world!"));
+
+    let mut map = HashMap::new();
+    map.insert(ArcStr::new("key"), 5);
+    assert_eq!(map.get("key"), Some(&5));
+    assert_eq!(map.get("other"), None);
+}
+
+#[cfg(all(feature = "erasable", feature = "slice-dst", feature = "ptr-union"))]
+#[test]
+fn thin_union_test() {
+    let s = ArcStr::new("string");
+    let v: ArcSlice<u32> = ArcSlice::new(0..5);
+    let mut union2 = UnionAlign::left(s.clone());
+    assert_eq!(union2.a().map(|s| s.len()), Some(6));
+    assert!(union2.b().is_none());
+    union2 = UnionAlign::right(v.clone());
+    assert!(union2.a().is_none());
+    assert_eq!(union2.b().map(|v| v.len()), Some(5));
+    assert_eq!(ArcSlice::count(&v, Relaxed), 2);
+    std::mem::drop(union2);
+    assert!(v.is_unique());
+    assert!(s.is_unique());
+}
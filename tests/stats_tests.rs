@@ -0,0 +1,185 @@
+#[cfg(feature = "stats")]
+use elysees::{stats::ArcStats, *};
+
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, PartialEq)]
+struct Node(u64);
+
+/// Statistics are global, so every scenario is run sequentially in a single test
+#[cfg(feature = "stats")]
+#[test]
+fn arc_statistics() {
+    assert_eq!(stats::type_stats::<Node>(), None);
+    stats::register::<Node>();
+    stats::register::<Node>();
+    assert_eq!(stats::type_stats::<Node>(), Some(ArcStats::default()));
+    let node_bytes = ArcInner::data_offset(&Node(0)).0.size();
+
+    let a = Arc::new(Node(1));
+    let b = a.clone();
+    let c = b.borrow_arc().clone_arc();
+    let stats = stats::type_stats::<Node>().unwrap();
+    assert_eq!(stats.live, 1);
+    assert_eq!(stats.live_bytes, node_bytes);
+    assert_eq!(stats.allocations, 1);
+    assert_eq!(stats.clones, 2);
+    assert_eq!(stats.drops, 0);
+    assert!(stats::global().live >= 1);
+    assert!(stats::global().live_bytes >= node_bytes);
+
+    drop(b);
+    drop(c);
+    let clones: Vec<_> = Arc::clone_n(&a, 3).collect();
+    drop(clones);
+    let mut d = a.clone();
+    Arc::make_mut(&mut d).0 = 2;
+    let stats = stats::type_stats::<Node>().unwrap();
+    assert_eq!(stats.live, 2);
+    assert_eq!(stats.allocations, 2);
+    assert_eq!(stats.allocated_bytes, 2 * node_bytes);
+    assert_eq!(stats.clones, 6);
+    assert_eq!(stats.drops, 6);
+    assert_eq!(stats.deallocations, 0);
+
+    // Resetting keeps the number of live allocations
+    stats::reset();
+    drop(a);
+    drop(d);
+    let stats = stats::type_stats::<Node>().unwrap();
+    assert_eq!(
+        stats,
+        ArcStats {
+            deallocations: 2,
+            drops: 2,
+            ..ArcStats::default()
+        }
+    );
+
+    // Slices are counted through `ArcVec`, and unregistered while they are growable
+    stats::register::<[Node]>();
+    let slice: Arc<[Node]> = vec![Node(1), Node(2)].into();
+    let stats = stats::type_stats::<[Node]>().unwrap();
+    assert_eq!(stats.live, 1);
+    assert_eq!(
+        stats.live_bytes,
+        ArcInner::<[Node]>::slice_layout(2).0.size()
+    );
+    let mut vec = Arc::try_into_vec(slice).unwrap();
+    assert_eq!(stats::type_stats::<[Node]>().unwrap().live, 0);
+    vec.push(Node(3));
+    let slice = vec.freeze();
+    let stats = stats::type_stats::<[Node]>().unwrap();
+    assert_eq!(stats.live, 1);
+    assert_eq!(
+        stats.live_bytes,
+        ArcInner::<[Node]>::slice_layout(3).0.size()
+    );
+    drop(slice);
+    assert_eq!(stats::type_stats::<[Node]>().unwrap().live_bytes, 0);
+}
+
+#[cfg(all(feature = "stats", feature = "slice-dst"))]
+#[test]
+fn slice_dst_statistics() {
+    use slice_dst::SliceWithHeader;
+    stats::register::<SliceWithHeader<u8, Node>>();
+    let arc: Arc<SliceWithHeader<u8, Node>> = SliceWithHeader::new(7, vec![Node(1), Node(2)]);
+    let stats = stats::type_stats::<SliceWithHeader<u8, Node>>().unwrap();
+    assert_eq!(stats.live, 1);
+    assert_eq!(stats.live_bytes, ArcInner::data_offset(&*arc).0.size());
+    drop(arc);
+    let stats = stats::type_stats::<SliceWithHeader<u8, Node>>().unwrap();
+    assert_eq!((stats.live, stats.live_bytes), (0, 0));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn concurrent_registration() {
+    struct Tagged<const N: usize>;
+
+    fn register_and_count<const N: usize>() {
+        stats::register::<Tagged<N>>();
+        let arc = Arc::new(Tagged::<N>);
+        let clone = arc.clone();
+        drop(clone);
+        drop(arc);
+    }
+
+    // No registration may be lost to another made at the same time
+    let threads = vec![
+        std::thread::spawn(register_and_count::<0>),
+        std::thread::spawn(register_and_count::<1>),
+        std::thread::spawn(register_and_count::<2>),
+        std::thread::spawn(register_and_count::<3>),
+    ];
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    let expected = ArcStats {
+        allocations: 1,
+        allocated_bytes: ArcInner::data_offset(&Tagged::<0>).0.size(),
+        deallocations: 1,
+        clones: 1,
+        drops: 2,
+        ..ArcStats::default()
+    };
+    assert_eq!(stats::type_stats::<Tagged<0>>(), Some(expected));
+    assert_eq!(stats::type_stats::<Tagged<1>>(), Some(expected));
+    assert_eq!(stats::type_stats::<Tagged<2>>(), Some(expected));
+    assert_eq!(stats::type_stats::<Tagged<3>>(), Some(expected));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn arc32_statistics() {
+    #[derive(Clone)]
+    struct Interned(u32);
+
+    stats::register::<Interned>();
+    stats::register::<[Interned]>();
+    let bytes = Arc32Inner::data_offset(&Interned(0)).0.size();
+    let a = Arc32::new(Interned(1));
+    let b = a.clone();
+    assert_eq!(b.0, 1);
+    Arc32::increment_by(&a, 3);
+    unsafe { Arc32::decrement_by(&a, 3) };
+    let stats = stats::type_stats::<Interned>().unwrap();
+    assert_eq!((stats.live, stats.live_bytes), (1, bytes));
+    assert_eq!((stats.clones, stats.drops), (4, 3));
+    drop(b);
+    drop(a);
+    let stats = stats::type_stats::<Interned>().unwrap();
+    assert_eq!(
+        (stats.live, stats.live_bytes, stats.deallocations),
+        (0, 0, 1)
+    );
+    assert_eq!(stats.drops, 5);
+
+    let slice: Arc32<[Interned]> = Arc32::from(&[Interned(1), Interned(2)][..]);
+    let stats = stats::type_stats::<[Interned]>().unwrap();
+    assert_eq!(stats.live, 1);
+    assert_eq!(stats.live_bytes, Arc32Inner::data_offset(&*slice).0.size());
+    drop(slice);
+    assert_eq!(stats::type_stats::<[Interned]>().unwrap().live, 0);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn registration_after_allocation() {
+    struct Early(u64);
+
+    let early: Vec<Arc<Early>> = (0..3).map(|i| Arc::new(Early(i))).collect();
+    stats::register::<Early>();
+    let late = Arc::new(Early(3));
+    assert_eq!(late.0, 3);
+    drop(early);
+    let stats = stats::type_stats::<Early>().unwrap();
+    assert_eq!((stats.live, stats.live_bytes), (0, 0));
+    assert_eq!((stats.allocations, stats.deallocations), (1, 3));
+    drop(late);
+    let stats = stats::type_stats::<Early>().unwrap();
+    assert_eq!(
+        (stats.live, stats.live_bytes, stats.deallocations),
+        (0, 0, 4)
+    );
+}
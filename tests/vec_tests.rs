@@ -0,0 +1,97 @@
+use elysees::*;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+fn basic_arc_vec_test() {
+    let mut v = ArcVec::new();
+    assert!(v.is_empty());
+    assert_eq!(v.capacity(), 0);
+    for i in 0..10 {
+        v.push(i.to_string());
+    }
+    assert_eq!(v.len(), 10);
+    assert!(v.capacity() >= 10);
+    v.extend((10..20).map(|i| i.to_string()));
+    assert_eq!(v.len(), 20);
+    assert_eq!(v.pop().as_deref(), Some("19"));
+    v.truncate(5);
+    assert_eq!(v.len(), 5);
+    v[0] = "zero".to_string();
+    v.insert(1, "one".to_string());
+    assert_eq!(v[1], "one");
+    assert_eq!(v[2], "1");
+    v.insert(6, "end".to_string());
+    assert_eq!(v.remove(6), "end");
+    assert_eq!(v.remove(1), "one");
+    assert_eq!(v[1], "1");
+    v.extend_from_slice(&["a".to_string(), "b".to_string()]);
+    v.reserve_exact(100);
+    assert!(v.capacity() >= 107);
+    let w = v.clone();
+    let frozen = v.freeze();
+    assert_eq!(*frozen, *w);
+    assert_eq!(frozen[0], "zero");
+    assert_eq!(frozen[6], "b");
+    assert!(frozen.is_unique());
+
+    let shared = frozen.clone();
+    let frozen = Arc::try_into_vec(frozen).expect_err("Not unique");
+    std::mem::drop(shared);
+    let mut v = Arc::try_into_vec(frozen).expect("Unique");
+    assert_eq!(v.capacity(), 7);
+    v.push("c".to_string());
+    v.clear();
+    assert!(v.is_empty());
+    let frozen = v.freeze();
+    assert!(frozen.is_empty());
+    assert_eq!(Arc::count(&frozen, Relaxed), 1);
+}
+
+#[test]
+fn zst_arc_vec_test() {
+    let mut v: ArcVec<()> = (0..100).map(|_| ()).collect();
+    assert_eq!(v.len(), 100);
+    v.push(());
+    v.shrink_to_fit();
+    let frozen = v.freeze();
+    assert_eq!(frozen.len(), 101);
+    let v = Arc::try_into_vec(frozen).expect("Unique");
+    assert_eq!(v.len(), 101);
+}
+
+#[test]
+fn arc_vec_drop_test() {
+    let arc = Arc::new(5);
+    let mut v = ArcVec::with_capacity(2);
+    for _ in 0..10 {
+        v.push(arc.clone())
+    }
+    assert_eq!(Arc::count(&arc, Relaxed), 11);
+    v.truncate(3);
+    assert_eq!(Arc::count(&arc, Relaxed), 4);
+    let frozen = v.freeze();
+    assert_eq!(Arc::count(&arc, Relaxed), 4);
+    std::mem::drop(frozen);
+    assert!(arc.is_unique());
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn vec_reserve_overflow() {
+    let mut v = ArcVec::new();
+    v.push(1u8);
+    v.reserve(usize::MAX);
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn vec_reserve_exact_too_large() {
+    let mut v: ArcVec<u64> = ArcVec::new();
+    v.reserve_exact(usize::MAX / 4);
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn vec_with_capacity_too_large() {
+    ArcVec::<u32>::with_capacity(usize::MAX / 2);
+}